@@ -36,14 +36,6 @@ mod zero {
         pub quote: Value,
     }
 
-    #[zero_copy]
-    #[repr(C)]
-    #[derive(Debug, Default, PartialEq, Eq)]
-    pub struct BothQuantities {
-        pub base: Quantity,
-        pub quote: Quantity,
-    }
-
     #[zero_copy]
     #[repr(C)]
     #[derive(Debug, Default, PartialEq, Eq)]
@@ -188,13 +180,6 @@ mod non_zero {
         pub quote: Value,
     }
 
-    #[repr(C)]
-    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
-    pub struct BothQuantities {
-        pub base: Quantity,
-        pub quote: Quantity,
-    }
-
     #[repr(C)]
     #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
     pub struct BothFractions {
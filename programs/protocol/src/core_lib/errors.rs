@@ -93,6 +93,88 @@ mod anchor_one {
         PositionAlreadyExists,
         #[msg("input amount cannot be zero")]
         ZeroAmountInput,
+        #[msg("Borrow exceeds debt ceiling of isolated collateral")]
+        DebtCeilingExceeded,
+        #[msg("Fee curve segments are invalid")]
+        InvalidFeeCurve,
+        #[msg("Kept fee parts sum up to more than one")]
+        InvalidKeptFeeSplit,
+        #[msg("There is no kept fee bucket for given index")]
+        InvalidKeptFeeBucket,
+        #[msg("Skew target has to be between zero and one and factor not greater than one")]
+        InvalidInventorySkew,
+        #[msg("Strategy provides to swap on its own")]
+        SharedSwapNotAllowed,
+        #[msg("Liquidation threshold has to be between collateral ratio and one")]
+        InvalidRiskParams,
+        #[msg("Strategy has locked balance")]
+        StrategyHasLockedBalance,
+        #[msg("Invalid strategy state")]
+        InvalidStrategyState,
+        #[msg("Strategy still holds liquidity")]
+        StrategyNotEmpty,
+        #[msg("Strategy does not accept deposits or withdrawals")]
+        StrategyClosed,
+        #[msg("Not enough liquidity in strategies allocating new locks")]
+        NotEnoughAvailableLiquidity,
+        #[msg("Not enough shares in the position")]
+        NotEnoughShares,
+        #[msg("Share mint is already initialized for the strategy")]
+        ShareMintExists,
+        #[msg("Withdraw queue of the strategy is full")]
+        WithdrawQueueFull,
+        #[msg("Withdraw request not found")]
+        WithdrawRequestNotFound,
+        #[msg("Withdraw request belongs to another user")]
+        WithdrawRequestNotOwned,
+        #[msg("Withdraw request is not filled yet")]
+        WithdrawRequestNotFilled,
+        #[msg("Withdraw request is already filled")]
+        WithdrawRequestFilled,
+        #[msg("Invalid insurance fund parameters")]
+        InvalidInsuranceParams,
+        #[msg("Statement is not insolvent")]
+        StatementSolvent,
+        #[msg("Statement still holds collateral")]
+        CollateralRemaining,
+        #[msg("Invalid pausable operation")]
+        InvalidOperation,
+        #[msg("Operation is paused")]
+        OperationPaused,
+        #[msg("Invalid role")]
+        InvalidRole,
+        #[msg("Signer does not hold the required role")]
+        MissingRole,
+        #[msg("Signer is not the proposed admin")]
+        NotPendingAdmin,
+        #[msg("Invalid kind of parameter change")]
+        InvalidChangeKind,
+        #[msg("Timelock queue is full")]
+        TimelockFull,
+        #[msg("Pending change not found")]
+        ChangeNotFound,
+        #[msg("Delay of the change has not passed yet")]
+        ChangeNotReady,
+        #[msg("Outflow exceeds the rate limit of the vault")]
+        VaultOutflowLimitExceeded,
+        #[msg("Outflow exceeds the rate limit of the statement")]
+        StatementOutflowLimitExceeded,
+        #[msg("Vault is delisted")]
+        VaultDelisted,
+        #[msg("Vault has to be delisting")]
+        VaultNotDelisting,
+        #[msg("Vault still has liquidity, debt or open positions")]
+        VaultNotEmpty,
+        #[msg("Statement still has open positions")]
+        StatementNotSettled,
+        #[msg("Vault has users, the change has to go through the timelock")]
+        TimelockRequired,
+        #[msg("Timelock delay is below the minimum")]
+        TimelockDelayTooShort,
+        #[msg("Strategy holds no liquidity")]
+        EmptyStrategy,
+        #[msg("Signer is not the owner of the statement")]
+        NotStatementOwner,
     }
 }
 
@@ -191,6 +273,88 @@ mod anchor_none {
         PositionAlreadyExists,
         #[error("input amount cannot be zero")]
         ZeroAmountInput,
+        #[error("Borrow exceeds debt ceiling of isolated collateral")]
+        DebtCeilingExceeded,
+        #[error("Fee curve segments are invalid")]
+        InvalidFeeCurve,
+        #[error("Kept fee parts sum up to more than one")]
+        InvalidKeptFeeSplit,
+        #[error("There is no kept fee bucket for given index")]
+        InvalidKeptFeeBucket,
+        #[error("Skew target has to be between zero and one and factor not greater than one")]
+        InvalidInventorySkew,
+        #[error("Strategy provides to swap on its own")]
+        SharedSwapNotAllowed,
+        #[error("Liquidation threshold has to be between collateral ratio and one")]
+        InvalidRiskParams,
+        #[error("Strategy has locked balance")]
+        StrategyHasLockedBalance,
+        #[error("Invalid strategy state")]
+        InvalidStrategyState,
+        #[error("Strategy still holds liquidity")]
+        StrategyNotEmpty,
+        #[error("Strategy does not accept deposits or withdrawals")]
+        StrategyClosed,
+        #[error("Not enough liquidity in strategies allocating new locks")]
+        NotEnoughAvailableLiquidity,
+        #[error("Not enough shares in the position")]
+        NotEnoughShares,
+        #[error("Share mint is already initialized for the strategy")]
+        ShareMintExists,
+        #[error("Withdraw queue of the strategy is full")]
+        WithdrawQueueFull,
+        #[error("Withdraw request not found")]
+        WithdrawRequestNotFound,
+        #[error("Withdraw request belongs to another user")]
+        WithdrawRequestNotOwned,
+        #[error("Withdraw request is not filled yet")]
+        WithdrawRequestNotFilled,
+        #[error("Withdraw request is already filled")]
+        WithdrawRequestFilled,
+        #[error("Invalid insurance fund parameters")]
+        InvalidInsuranceParams,
+        #[error("Statement is not insolvent")]
+        StatementSolvent,
+        #[error("Statement still holds collateral")]
+        CollateralRemaining,
+        #[error("Invalid pausable operation")]
+        InvalidOperation,
+        #[error("Operation is paused")]
+        OperationPaused,
+        #[error("Invalid role")]
+        InvalidRole,
+        #[error("Signer does not hold the required role")]
+        MissingRole,
+        #[error("Signer is not the proposed admin")]
+        NotPendingAdmin,
+        #[error("Invalid kind of parameter change")]
+        InvalidChangeKind,
+        #[error("Timelock queue is full")]
+        TimelockFull,
+        #[error("Pending change not found")]
+        ChangeNotFound,
+        #[error("Delay of the change has not passed yet")]
+        ChangeNotReady,
+        #[error("Outflow exceeds the rate limit of the vault")]
+        VaultOutflowLimitExceeded,
+        #[error("Outflow exceeds the rate limit of the statement")]
+        StatementOutflowLimitExceeded,
+        #[error("Vault is delisted")]
+        VaultDelisted,
+        #[error("Vault has to be delisting")]
+        VaultNotDelisting,
+        #[error("Vault still has liquidity, debt or open positions")]
+        VaultNotEmpty,
+        #[error("Statement still has open positions")]
+        StatementNotSettled,
+        #[error("Vault has users, the change has to go through the timelock")]
+        TimelockRequired,
+        #[error("Timelock delay is below the minimum")]
+        TimelockDelayTooShort,
+        #[error("Strategy holds no liquidity")]
+        EmptyStrategy,
+        #[error("Signer is not the owner of the statement")]
+        NotStatementOwner,
     }
 }
 
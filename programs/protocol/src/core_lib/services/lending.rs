@@ -44,6 +44,8 @@ mod zero {
         pub unclaimed_fee: Quantity,
         /// sum of all fees accrued (for statistics)
         pub total_fee: Quantity,
+        /// sum of all debt written off as unrecoverable (for statistics)
+        pub written_off: Quantity,
     }
 }
 
@@ -78,6 +80,8 @@ mod non_zero {
         pub unclaimed_fee: Quantity,
         /// sum of all fees accrued (for statistics)
         pub total_fee: Quantity,
+        /// sum of all debt written off as unrecoverable (for statistics)
+        pub written_off: Quantity,
     }
 }
 
@@ -309,9 +313,51 @@ impl Lend {
         self.last_fee_paid = current_time;
     }
 
+    /// Books debt the borrower did not ask for (a loss of a force closed trader), utilization limits don't apply
+    pub fn book_debt(&mut self, quantity: Quantity) -> Shares {
+        let additional_shares = self.borrow_shares.get_change_up(quantity, self.borrowed);
+
+        self.borrowed += quantity;
+        self.borrow_shares += additional_shares;
+        self.utilization = self.current_utilization();
+
+        additional_shares
+    }
+
     pub fn fee_curve(&mut self) -> &mut FeeCurve {
         &mut self.fee
     }
+
+    /// Clears whole debt of a position without anything being repaid
+    pub fn write_off(
+        &mut self,
+        borrowed: Quantity,
+        borrowed_shares: Shares,
+    ) -> Result<Repayment, LibErrors> {
+        let owed_quantity = self
+            .borrow_shares
+            .calculate_owed(borrowed_shares, self.borrowed);
+
+        let repayment = self.repay(owed_quantity, borrowed, borrowed_shares)?;
+        self.written_off += repayment.repaid;
+
+        Ok(repayment)
+    }
+}
+
+/// Outcome of a repay, `repaid` = `interest` + `principal`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Repayment {
+    /// quantity to be taken from user and unlocked in strategy
+    pub repaid: Quantity,
+    /// part of repaid quantity covering accrued fee
+    pub interest: Quantity,
+    /// part of repaid quantity covering borrowed quantity of position
+    pub principal: Quantity,
+    /// amount of borrow shares burned
+    pub shares: Shares,
+    /// part of requested quantity exceeding debt, not taken from user
+    pub refund: Quantity,
 }
 
 pub trait Borrowable {
@@ -321,11 +367,11 @@ pub trait Borrowable {
         repay_quantity: Quantity,
         borrowed: Quantity,
         borrowed_shares: Shares,
-    ) -> Result<(Quantity, Shares, Quantity), LibErrors>;
+    ) -> Result<Repayment, LibErrors>;
 }
 
 impl Borrowable for Lend {
-    /// Performs repay operation on Service
+    /// Performs repay operation on Service, accrued fee is repaid before borrowed quantity
     ///
     /// ## Arguments
     ///
@@ -334,42 +380,53 @@ impl Borrowable for Lend {
     /// * `borrowed_shares` - initial user borrowed shares
     ///
     /// # Returns
-    /// ## (`quantity`, `shares`)
+    /// ## Repayment
     ///
-    /// * `quantity` is amount to be unlocked in strategy, includes all the fees accrued
+    /// * `repaid` is amount to be unlocked in strategy, never more than owed
     ///
-    /// * `shares` is amount of borrow shares repaid
+    /// * `shares` is amount of borrow shares repaid, all of them when whole debt is repaid
     ///
     fn repay(
         &mut self,
         repay_quantity: Quantity,
         borrowed: Quantity,
         borrowed_shares: Shares,
-    ) -> Result<(Quantity, Shares, Quantity), LibErrors> {
-        let owed_quantity = self
-            .borrow_shares
-            .calculate_owed(borrowed_shares, self.borrowed);
-
-        let fee_owed = owed_quantity - borrowed;
-        let repay_amount = min(repay_quantity, owed_quantity);
+    ) -> Result<Repayment, LibErrors> {
+        let owed_quantity = min(
+            self.borrow_shares
+                .calculate_owed(borrowed_shares, self.borrowed),
+            self.borrowed,
+        );
 
-        if repay_quantity >= fee_owed {
-            let shares_to_burn = self
-                .borrow_shares
-                .get_change_down(repay_amount, self.borrowed);
+        let fee_owed = if owed_quantity > borrowed {
+            owed_quantity - borrowed
+        } else {
+            Quantity::new(0)
+        };
 
-            self.borrowed -= repay_quantity;
-            self.borrow_shares -= shares_to_burn;
-            self.utilization = self.current_utilization();
+        let repaid = min(repay_quantity, owed_quantity);
+        let interest = min(repaid, fee_owed);
 
-            Ok((
-                repay_quantity,
-                shares_to_burn,
-                min(repay_quantity, borrowed),
-            ))
+        let shares = if repaid == owed_quantity {
+            borrowed_shares
         } else {
-            Err(LibErrors::RepayLowerThanFee)
-        }
+            min(
+                self.borrow_shares.get_change_down(repaid, self.borrowed),
+                borrowed_shares,
+            )
+        };
+
+        self.borrowed -= repaid;
+        self.borrow_shares -= shares;
+        self.utilization = self.current_utilization();
+
+        Ok(Repayment {
+            repaid,
+            interest,
+            principal: min(repaid - interest, borrowed),
+            shares,
+            refund: repay_quantity - repaid,
+        })
     }
 
     /// Performs borrow operation on Service
@@ -492,7 +549,11 @@ mod lend_tests {
 
         assert!(lending.borrow(Quantity::new(1)).is_err(), "can't borrow");
 
-        let (partially_repaid, shares_partially_repaid, _) = lending.repay(
+        let Repayment {
+            repaid: partially_repaid,
+            shares: shares_partially_repaid,
+            ..
+        } = lending.repay(
             Quantity::new(1_530_264),
             Quantity::new(1_600_000),
             Shares::from_integer(1_600_000),
@@ -500,7 +561,10 @@ mod lend_tests {
 
         lending.add_available_base(partially_repaid);
 
-        let (full_repaid, _shares_fully_repaid, _) = lending.repay(
+        let Repayment {
+            repaid: full_repaid,
+            ..
+        } = lending.repay(
             Quantity::new(1_600_000) - partially_repaid,
             Quantity::new(1_600_000) - partially_repaid,
             Shares::from_integer(1_600_000) - shares_partially_repaid,
@@ -656,7 +720,11 @@ mod lend_tests {
             }
         );
 
-        let (repaid, first_repaid_shares, _) = lending.repay(
+        let Repayment {
+            repaid,
+            shares: first_repaid_shares,
+            ..
+        } = lending.repay(
             Quantity::new(184186871548154787),
             Quantity::new(184186871548154787),
             Shares::new(184186871548154787),
@@ -685,7 +753,11 @@ mod lend_tests {
             }
         );
 
-        let (repaid, second_repaid_shares, _) = lending.repay(
+        let Repayment {
+            repaid,
+            shares: second_repaid_shares,
+            ..
+        } = lending.repay(
             Quantity::new(11051825915530),
             Quantity::new(11051825915530),
             Shares::new(10941858411815),
@@ -720,7 +792,7 @@ mod lend_tests {
 
         //repay to zero, merge 2 debts
 
-        let (repaid, _, _) = lending.repay(
+        let Repayment { repaid, .. } = lending.repay(
             Quantity::new(1851108807930549),
             Quantity::new(1851108807930549),
             Shares::new(1832689967797802),
@@ -744,4 +816,149 @@ mod lend_tests {
 
         Ok(())
     }
+
+    fn lending_with_debt(borrow: Quantity) -> Result<(Lend, Shares), LibErrors> {
+        let mut fee = FeeCurve::default();
+        fee.add_constant_fee(Fraction::from_scale(1, 2), Fraction::from_integer(1));
+
+        let mut lending = Lend::new(
+            fee,
+            Utilization::from_integer(1),
+            Quantity::new(u64::MAX),
+            0,
+            0,
+        );
+        lending.add_available_base(Quantity::new(10_000_000));
+
+        let shares = lending.borrow(borrow)?;
+        lending.remove_available_base(borrow);
+
+        lending.accrue_interest_rate(HOUR_DURATION);
+        lending.accrue_fee();
+
+        Ok((lending, shares))
+    }
+
+    #[test]
+    fn repay_interest_only() -> Result<(), LibErrors> {
+        let (mut lending, shares) = lending_with_debt(Quantity::new(1_000_000))?;
+
+        // owed = 1_000_000 + 10051 of accrued fee
+        assert_eq!(
+            lending
+                .borrow_shares
+                .calculate_owed(shares, lending.borrowed),
+            Quantity::new(1_010_051)
+        );
+
+        // lower than the fee is still accepted
+        let repayment = lending.repay(Quantity::new(5000), Quantity::new(1_000_000), shares)?;
+
+        assert_eq!(
+            repayment,
+            Repayment {
+                repaid: Quantity::new(5000),
+                interest: Quantity::new(5000),
+                principal: Quantity::new(0),
+                // 1_000_000 * 5000 / 1_010_051 (ROUND DOWN)
+                shares: Shares::new(4950),
+                refund: Quantity::new(0),
+            }
+        );
+        assert_eq!(lending.borrowed(), Quantity::new(1_005_051));
+        assert_eq!(lending.borrow_shares(), Shares::new(995050));
+
+        // rest of the fee
+        let repayment = lending.repay(
+            Quantity::new(5051),
+            Quantity::new(1_000_000),
+            shares - repayment.shares,
+        )?;
+
+        assert_eq!(repayment.interest, Quantity::new(5051));
+        assert_eq!(repayment.principal, Quantity::new(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn repay_interest_before_principal() -> Result<(), LibErrors> {
+        let (mut lending, shares) = lending_with_debt(Quantity::new(1_000_000))?;
+
+        let repayment = lending.repay(Quantity::new(510_051), Quantity::new(1_000_000), shares)?;
+
+        assert_eq!(
+            repayment,
+            Repayment {
+                repaid: Quantity::new(510_051),
+                interest: Quantity::new(10051),
+                principal: Quantity::new(500_000),
+                // 1_000_000 * 510_051 / 1_010_051 (ROUND DOWN)
+                shares: Shares::new(504975),
+                refund: Quantity::new(0),
+            }
+        );
+        assert_eq!(lending.borrowed(), Quantity::new(500_000));
+        assert_eq!(lending.borrow_shares(), Shares::new(495025));
+
+        // owed is now rounded up in favour of the protocol
+        let owed = lending
+            .borrow_shares
+            .calculate_owed(Shares::new(495025), lending.borrowed);
+        assert_eq!(owed, Quantity::new(500_000));
+
+        let repayment = lending.repay(owed, Quantity::new(500_000), Shares::new(495025))?;
+
+        assert_eq!(repayment.interest, Quantity::new(0));
+        assert_eq!(repayment.principal, Quantity::new(500_000));
+        assert_eq!(repayment.shares, Shares::new(495025));
+        assert_eq!(lending.borrowed(), Quantity::new(0));
+        assert_eq!(lending.borrow_shares(), Shares::new(0));
+        assert_eq!(lending.utilization(), Utilization::new(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn over_repay() -> Result<(), LibErrors> {
+        let (mut lending, shares) = lending_with_debt(Quantity::new(1_000_000))?;
+
+        // second borrower, not affected by the first one
+        let other_shares = lending.borrow(Quantity::new(1_010_051))?;
+        lending.remove_available_base(Quantity::new(1_010_051));
+
+        assert_eq!(other_shares, Shares::new(1_000_000));
+
+        let repayment =
+            lending.repay(Quantity::new(2_000_000), Quantity::new(1_000_000), shares)?;
+
+        assert_eq!(
+            repayment,
+            Repayment {
+                repaid: Quantity::new(1_010_051),
+                interest: Quantity::new(10051),
+                principal: Quantity::new(1_000_000),
+                shares,
+                refund: Quantity::new(989_949),
+            }
+        );
+
+        // only the clamped quantity is removed
+        assert_eq!(lending.borrowed(), Quantity::new(1_010_051));
+        assert_eq!(lending.borrow_shares(), other_shares);
+
+        let repayment = lending.repay(
+            Quantity::new(u64::MAX),
+            Quantity::new(1_010_051),
+            other_shares,
+        )?;
+
+        assert_eq!(repayment.repaid, Quantity::new(1_010_051));
+        assert_eq!(repayment.interest, Quantity::new(0));
+        assert_eq!(repayment.refund, Quantity::new(u64::MAX - 1_010_051));
+        assert_eq!(lending.borrowed(), Quantity::new(0));
+        assert_eq!(lending.borrow_shares(), Shares::new(0));
+
+        Ok(())
+    }
 }
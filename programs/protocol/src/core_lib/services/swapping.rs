@@ -5,12 +5,37 @@ use crate::core_lib::errors::LibErrors;
 use crate::core_lib::structs::{FeeCurve, Oracle};
 
 use super::ServiceUpdate;
+use std::cmp::min;
 
 #[cfg(feature = "anchor")]
 mod zero {
     use super::*;
     use anchor_lang::prelude::*;
 
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq, Eq)]
+    pub struct KeptFees {
+        /// Part of kept fee going to protocol owned liquidity.
+        pub pol_part: Fraction,
+        /// Part of kept fee to be burned, the rest goes to insurance fund.
+        pub burn_part: Fraction,
+
+        /// Kept fee waiting to be moved to insurance fund.
+        pub insurance: Balances,
+        /// Kept fee waiting to be swept to protocol owned liquidity.
+        pub pol: Balances,
+        /// Kept fee waiting to be burned.
+        pub burn: Balances,
+
+        /// Total amount of fee ever kept for insurance fund.
+        pub total_insurance: Balances,
+        /// Total amount of fee ever kept for protocol owned liquidity.
+        pub total_pol: Balances,
+        /// Total amount of fee ever kept for burn.
+        pub total_burn: Balances,
+    }
+
     #[zero_copy]
     #[repr(C)]
     #[derive(Debug, Default, PartialEq, Eq)]
@@ -26,12 +51,18 @@ mod zero {
         pub total_paid_fee: Balances,
         /// Total amount of fee tokens kept as fee (insurance, PoL or burn).
         pub total_kept_fee: Balances,
+        /// Kept fee split between insurance fund, protocol owned liquidity and burn.
+        pub kept: KeptFees,
 
         /// Struct for calculation of swapping fee.
         pub selling_fee: FeeCurve,
         pub buying_fee: FeeCurve,
         /// Fraction of paid fee to be kept.
         pub kept_fee: Fraction,
+        /// Target base proportion of the pool (by value), inventory skew is disabled when zero.
+        pub skew_target: Fraction,
+        /// Price shift per deviation of base proportion from the target.
+        pub skew_factor: Fraction,
     }
 }
 
@@ -39,6 +70,29 @@ mod zero {
 mod non_zero {
     use super::*;
 
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    #[repr(C)]
+    pub struct KeptFees {
+        /// Part of kept fee going to protocol owned liquidity.
+        pub pol_part: Fraction,
+        /// Part of kept fee to be burned, the rest goes to insurance fund.
+        pub burn_part: Fraction,
+
+        /// Kept fee waiting to be moved to insurance fund.
+        pub insurance: Balances,
+        /// Kept fee waiting to be swept to protocol owned liquidity.
+        pub pol: Balances,
+        /// Kept fee waiting to be burned.
+        pub burn: Balances,
+
+        /// Total amount of fee ever kept for insurance fund.
+        pub total_insurance: Balances,
+        /// Total amount of fee ever kept for protocol owned liquidity.
+        pub total_pol: Balances,
+        /// Total amount of fee ever kept for burn.
+        pub total_burn: Balances,
+    }
+
     #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
     #[repr(C)]
     pub struct Swap {
@@ -53,20 +107,143 @@ mod non_zero {
         pub total_paid_fee: Balances,
         /// Total amount of fee tokens kept as fee (insurance, PoL or burn).
         pub total_kept_fee: Balances,
+        /// Kept fee split between insurance fund, protocol owned liquidity and burn.
+        pub kept: KeptFees,
 
         /// Struct for calculation of swapping fee.
         pub selling_fee: FeeCurve,
         pub buying_fee: FeeCurve,
         /// Fraction of paid fee to be kept.
         pub kept_fee: Fraction,
+        /// Target base proportion of the pool (by value), inventory skew is disabled when zero.
+        pub skew_target: Fraction,
+        /// Price shift per deviation of base proportion from the target.
+        pub skew_factor: Fraction,
     }
 }
 
 #[cfg(feature = "anchor")]
-pub use zero::Swap;
+pub use zero::{KeptFees, Swap};
 
 #[cfg(not(feature = "anchor"))]
-pub use non_zero::Swap;
+pub use non_zero::{KeptFees, Swap};
+
+/// Result of a swap calculation, quantities are in the bought token
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// quantity bought before fee
+    pub gross: Quantity,
+    /// fee taken from gross quantity
+    pub fee: Quantity,
+    /// mean of the fee curve over the proportion change
+    pub fee_fraction: Fraction,
+    /// part of the fee kept by protocol
+    pub kept_fee: Quantity,
+    /// proportion of the sold token in the pool before swap
+    pub proportion_before: Fraction,
+    /// proportion of the sold token in the pool after swap
+    pub proportion_after: Fraction,
+    /// relative difference between output and the oracle spot quantity
+    pub price_impact: Fraction,
+}
+
+impl SwapQuote {
+    /// Quantity received by swapper
+    pub fn output(&self) -> Quantity {
+        self.gross - self.fee
+    }
+
+    /// Minimal output accepted with given slippage tolerance
+    pub fn min_output(&self, slippage: Fraction) -> Quantity {
+        self.output() * (Fraction::from_integer(1) - min(slippage, Fraction::from_integer(1)))
+    }
+}
+
+/// Destination of the fee kept by swap
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+#[repr(u8)]
+pub enum KeptFeeBucket {
+    Insurance,
+    Pol,
+    Burn,
+}
+
+impl TryFrom<u8> for KeptFeeBucket {
+    type Error = LibErrors;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Insurance),
+            1 => Ok(Self::Pol),
+            2 => Ok(Self::Burn),
+            _ => Err(LibErrors::InvalidKeptFeeBucket),
+        }
+    }
+}
+
+impl KeptFees {
+    /// Splits kept fee between buckets, rounding leftovers go to insurance fund
+    fn keep(&mut self, fee: Balances) {
+        let pol = Balances {
+            base: fee.base * self.pol_part,
+            quote: fee.quote * self.pol_part,
+        };
+        let burn = Balances {
+            base: fee.base * self.burn_part,
+            quote: fee.quote * self.burn_part,
+        };
+        let insurance = fee - pol - burn;
+
+        self.insurance += insurance;
+        self.pol += pol;
+        self.burn += burn;
+        self.total_insurance += insurance;
+        self.total_pol += pol;
+        self.total_burn += burn;
+    }
+
+    pub fn set_split(&mut self, pol_part: Fraction, burn_part: Fraction) -> Result<(), LibErrors> {
+        if pol_part + burn_part > Fraction::from_integer(1) {
+            return Err(LibErrors::InvalidKeptFeeSplit);
+        }
+
+        self.pol_part = pol_part;
+        self.burn_part = burn_part;
+
+        Ok(())
+    }
+
+    /// Fee waiting in the bucket to be swept
+    pub fn balance(&self, bucket: KeptFeeBucket) -> Balances {
+        match bucket {
+            KeptFeeBucket::Insurance => self.insurance,
+            KeptFeeBucket::Pol => self.pol,
+            KeptFeeBucket::Burn => self.burn,
+        }
+    }
+
+    /// Total fee ever kept for the bucket
+    pub fn total(&self, bucket: KeptFeeBucket) -> Balances {
+        match bucket {
+            KeptFeeBucket::Insurance => self.total_insurance,
+            KeptFeeBucket::Pol => self.total_pol,
+            KeptFeeBucket::Burn => self.total_burn,
+        }
+    }
+
+    /// Empties the bucket, returning quantities to be moved out of reserves
+    pub fn sweep(&mut self, bucket: KeptFeeBucket) -> Balances {
+        let balance = match bucket {
+            KeptFeeBucket::Insurance => &mut self.insurance,
+            KeptFeeBucket::Pol => &mut self.pol,
+            KeptFeeBucket::Burn => &mut self.burn,
+        };
+
+        let swept = *balance;
+        *balance = Balances::default();
+        swept
+    }
+}
 
 impl ServiceUpdate for Swap {
     fn add_liquidity_base(&mut self, quantity: Quantity) {
@@ -128,88 +305,253 @@ impl Swap {
             total_earned_fee: Balances::default(),
             total_paid_fee: Balances::default(),
             total_kept_fee: Balances::default(),
+            kept: KeptFees::default(),
             selling_fee,
             buying_fee,
             kept_fee,
+            skew_target: Fraction::new(0),
+            skew_factor: Fraction::new(0),
         };
 
         swap
     }
 
     fn get_proportion(&self, base_oracle: &Oracle, quote_oracle: &Oracle) -> Fraction {
-        let base_value = base_oracle.calculate_value(self.balances.base);
-        let quote_value = quote_oracle.calculate_value(self.balances.quote);
+        Self::proportion(self.balances, base_oracle, quote_oracle)
+    }
+
+    fn proportion(balances: Balances, base_oracle: &Oracle, quote_oracle: &Oracle) -> Fraction {
+        let base_value = base_oracle.calculate_value(balances.base);
+        let quote_value = quote_oracle.calculate_value(balances.quote);
         let base_proportion = base_value / (base_value + quote_value);
         Fraction::from_decimal(base_proportion)
     }
 
-    pub fn sell(
+    /// Enables inventory skew, zero target and factor disable it
+    pub fn set_inventory_skew(
         &mut self,
+        target: Fraction,
+        factor: Fraction,
+    ) -> Result<(), LibErrors> {
+        let zero = Fraction::new(0);
+        let one = Fraction::from_integer(1);
+
+        if !(target == zero && factor == zero) && (target == zero || target >= one || factor > one)
+        {
+            return Err(LibErrors::InvalidInventorySkew);
+        }
+
+        self.skew_target = target;
+        self.skew_factor = factor;
+
+        Ok(())
+    }
+
+    /// Multiplier of quantity bought by swapper, above one when the swap
+    /// moves base proportion of the pool towards the target
+    pub fn skew_multiplier(&self, proportion: Fraction, selling: bool) -> Fraction {
+        let one = Fraction::from_integer(1);
+
+        if self.skew_target == Fraction::new(0) {
+            return one;
+        }
+
+        let (shift, excess_base) = if proportion > self.skew_target {
+            (self.skew_factor * (proportion - self.skew_target), true)
+        } else {
+            (self.skew_factor * (self.skew_target - proportion), false)
+        };
+
+        if selling == excess_base {
+            one - shift
+        } else {
+            one + shift
+        }
+    }
+
+    /// Mean skew multiplier over a swap moving base proportion of the pool from `before` to `after`.
+    /// The multiplier is linear in the proportion, also when the swap crosses the target,
+    /// so its mean is the multiplier at the midpoint
+    pub fn mean_skew(&self, before: Fraction, after: Fraction, selling: bool) -> Fraction {
+        let midpoint = (before + after) / Fraction::from_integer(2);
+
+        self.skew_multiplier(midpoint, selling)
+    }
+
+    /// Skew multiplier for the current state of the pool
+    pub fn current_skew(
+        &self,
+        base_oracle: &Oracle,
+        quote_oracle: &Oracle,
+        selling: bool,
+    ) -> Fraction {
+        if self.balances == Balances::default() {
+            return Fraction::from_integer(1);
+        }
+
+        self.skew_multiplier(self.get_proportion(base_oracle, quote_oracle), selling)
+    }
+
+    /// Calculates selling of base without modifying the state
+    pub fn quote_sell(
+        &self,
         base_quantity: Quantity,
         base_oracle: &Oracle,
         quote_oracle: &Oracle,
-    ) -> Result<Quantity, LibErrors> {
+    ) -> Result<SwapQuote, LibErrors> {
         if self.available.quote == Quantity::new(0) {
             return Err(LibErrors::NotEnoughQuoteQuantity); // has to be checked before calculating proportion
         }
         let proportion_before = self.get_proportion(base_oracle, quote_oracle);
         let swap_value = base_oracle.calculate_value(base_quantity);
-        let quote_quantity = quote_oracle.calculate_quantity(swap_value);
+        let spot_quantity = quote_oracle.calculate_quantity(swap_value);
+        // path of the swap is estimated with the spot quantity
+        let spot_after = Self::proportion(
+            Balances {
+                base: self.balances.base + base_quantity,
+                quote: self.balances.quote - min(spot_quantity, self.balances.quote),
+            },
+            base_oracle,
+            quote_oracle,
+        );
+        let quote_quantity = spot_quantity * self.mean_skew(proportion_before, spot_after, true);
 
         if quote_quantity > self.available.quote {
             return Err(LibErrors::NotEnoughQuoteQuantity);
         }
 
-        self.balances.base += base_quantity;
-        self.balances.quote -= quote_quantity;
-        let proportion_after = self.get_proportion(base_oracle, quote_oracle);
-        self.balances.base -= base_quantity;
-        self.balances.quote += quote_quantity;
+        let proportion_after = Self::proportion(
+            Balances {
+                base: self.balances.base + base_quantity,
+                // shared liquidity is not a part of balances
+                quote: self.balances.quote - min(quote_quantity, self.balances.quote),
+            },
+            base_oracle,
+            quote_oracle,
+        );
 
         let fee_fraction = self
             .selling_fee
             .get_mean(proportion_before, proportion_after)?;
 
-        let fee = quote_quantity.mul_up(fee_fraction);
-        let fee_to_keep = fee * self.kept_fee;
-        self.total_kept_fee.quote = fee_to_keep;
-        self.total_earned_fee.quote += fee - fee_to_keep;
-
-        Ok(quote_quantity - fee)
+        Ok(self.swap_quote(
+            quote_quantity,
+            spot_quantity,
+            fee_fraction,
+            proportion_before,
+            proportion_after,
+        ))
     }
 
-    pub fn buy(
-        &mut self,
+    /// Calculates buying of base without modifying the state
+    pub fn quote_buy(
+        &self,
         quote_quantity: Quantity,
         base_oracle: &Oracle,
         quote_oracle: &Oracle,
-    ) -> Result<Quantity, LibErrors> {
-        let proportion_before =
-            Fraction::from_integer(1) - self.get_proportion(base_oracle, quote_oracle);
+    ) -> Result<SwapQuote, LibErrors> {
+        let base_proportion = self.get_proportion(base_oracle, quote_oracle);
+        let proportion_before = Fraction::from_integer(1) - base_proportion;
         let swap_value = quote_oracle.calculate_value(quote_quantity);
-        let base_quantity = base_oracle.calculate_quantity(swap_value);
+        let spot_quantity = base_oracle.calculate_quantity(swap_value);
+        // path of the swap is estimated with the spot quantity
+        let spot_after = Self::proportion(
+            Balances {
+                base: self.balances.base - min(spot_quantity, self.balances.base),
+                quote: self.balances.quote + quote_quantity,
+            },
+            base_oracle,
+            quote_oracle,
+        );
+        let base_quantity = spot_quantity * self.mean_skew(base_proportion, spot_after, false);
 
         if base_quantity > self.available.base {
             return Err(LibErrors::NotEnoughBaseQuantity);
         }
 
-        self.balances.quote += quote_quantity;
-        self.balances.base -= base_quantity;
-        let proportion_after =
-            Fraction::from_integer(1) - self.get_proportion(base_oracle, quote_oracle);
-        self.balances.quote -= quote_quantity;
-        self.balances.base += base_quantity;
+        let proportion_after = Fraction::from_integer(1)
+            - Self::proportion(
+                Balances {
+                    base: self.balances.base - min(base_quantity, self.balances.base),
+                    quote: self.balances.quote + quote_quantity,
+                },
+                base_oracle,
+                quote_oracle,
+            );
 
         let fee_fraction = self
             .buying_fee
             .get_mean(proportion_before, proportion_after)?;
 
-        let fee = base_quantity.mul_up(fee_fraction);
-        let fee_to_keep = fee * self.kept_fee;
-        self.total_kept_fee.base = fee_to_keep;
-        self.total_earned_fee.base += fee - fee_to_keep;
+        Ok(self.swap_quote(
+            base_quantity,
+            spot_quantity,
+            fee_fraction,
+            proportion_before,
+            proportion_after,
+        ))
+    }
 
-        Ok(base_quantity - fee)
+    fn swap_quote(
+        &self,
+        gross: Quantity,
+        spot: Quantity,
+        fee_fraction: Fraction,
+        proportion_before: Fraction,
+        proportion_after: Fraction,
+    ) -> SwapQuote {
+        let fee = gross.mul_up(fee_fraction);
+        let output = gross - fee;
+
+        SwapQuote {
+            gross,
+            fee,
+            fee_fraction,
+            kept_fee: fee * self.kept_fee,
+            proportion_before,
+            proportion_after,
+            price_impact: if output < spot {
+                Fraction::get_utilization(spot - output, spot)
+            } else {
+                Fraction::new(0)
+            },
+        }
+    }
+
+    pub fn sell(
+        &mut self,
+        base_quantity: Quantity,
+        base_oracle: &Oracle,
+        quote_oracle: &Oracle,
+    ) -> Result<Quantity, LibErrors> {
+        let quote = self.quote_sell(base_quantity, base_oracle, quote_oracle)?;
+
+        self.total_kept_fee.quote += quote.kept_fee;
+        self.kept.keep(Balances {
+            base: Quantity::new(0),
+            quote: quote.kept_fee,
+        });
+        self.total_earned_fee.quote += quote.fee - quote.kept_fee;
+
+        Ok(quote.output())
+    }
+
+    pub fn buy(
+        &mut self,
+        quote_quantity: Quantity,
+        base_oracle: &Oracle,
+        quote_oracle: &Oracle,
+    ) -> Result<Quantity, LibErrors> {
+        let quote = self.quote_buy(quote_quantity, base_oracle, quote_oracle)?;
+
+        self.total_kept_fee.base += quote.kept_fee;
+        self.kept.keep(Balances {
+            base: quote.kept_fee,
+            quote: Quantity::new(0),
+        });
+        self.total_earned_fee.base += quote.fee - quote.kept_fee;
+
+        Ok(quote.output())
     }
 
     pub fn fee_curve_sell(&mut self) -> &mut FeeCurve {
@@ -226,6 +568,189 @@ mod tests {
     use super::*;
     use checked_decimal_macro::Decimal;
 
+    #[test]
+    fn kept_fee_buckets() -> Result<(), LibErrors> {
+        let base_oracle = Oracle::new_for_test();
+        let quote_oracle = Oracle::new_stable_for_test();
+
+        let mut swap = Swap::new(
+            FeeCurve::default(),
+            FeeCurve::default(),
+            Fraction::from_scale(5, 1),
+        );
+        swap.fee_curve_buy()
+            .add_constant_fee(Fraction::from_scale(1, 2), Fraction::from_integer(1)); // 1% fee
+        swap.add_liquidity_base(Quantity::new(10_000000));
+        swap.add_liquidity_quote(Quantity::new(20_000000));
+
+        assert_eq!(
+            swap.kept
+                .set_split(Fraction::from_scale(6, 1), Fraction::from_scale(5, 1)),
+            Err(LibErrors::InvalidKeptFeeSplit)
+        );
+        swap.kept
+            .set_split(Fraction::from_scale(3, 1), Fraction::from_scale(2, 1))?;
+
+        // fee = 1_000000 * 1% = 10000, half of it is kept
+        swap.buy(Quantity::new(2_000000), &base_oracle, &quote_oracle)?;
+        swap.buy(Quantity::new(2_000000), &base_oracle, &quote_oracle)?;
+
+        assert_eq!(swap.total_kept_fee.base, Quantity::new(10000));
+        assert_eq!(
+            swap.kept.balance(KeptFeeBucket::Insurance).base,
+            Quantity::new(5000)
+        );
+        assert_eq!(
+            swap.kept.balance(KeptFeeBucket::Pol).base,
+            Quantity::new(3000)
+        );
+        assert_eq!(
+            swap.kept.balance(KeptFeeBucket::Burn).base,
+            Quantity::new(2000)
+        );
+
+        let swept = swap.kept.sweep(KeptFeeBucket::Pol);
+
+        assert_eq!(swept.base, Quantity::new(3000));
+        assert_eq!(swap.kept.balance(KeptFeeBucket::Pol), Balances::default());
+        assert_eq!(
+            swap.kept.total(KeptFeeBucket::Pol).base,
+            Quantity::new(3000)
+        );
+
+        assert_eq!(KeptFeeBucket::try_from(2), Ok(KeptFeeBucket::Burn));
+        assert_eq!(
+            KeptFeeBucket::try_from(3),
+            Err(LibErrors::InvalidKeptFeeBucket)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn swap_quote() -> Result<(), LibErrors> {
+        let base_oracle = Oracle::new_for_test();
+        let quote_oracle = Oracle::new_stable_for_test();
+
+        let mut swap = Swap::new(
+            FeeCurve::default(),
+            FeeCurve::default(),
+            Fraction::from_scale(5, 1),
+        );
+        swap.fee_curve_sell()
+            .add_constant_fee(Fraction::from_scale(1, 2), Fraction::from_integer(1)); // 1% fee
+        swap.add_liquidity_base(Quantity::new(10_000000));
+        swap.add_liquidity_quote(Quantity::new(20_000000));
+
+        let before = swap;
+        let quote = swap.quote_sell(Quantity::new(1_000000), &base_oracle, &quote_oracle)?;
+
+        assert_eq!(swap, before);
+        assert_eq!(
+            quote,
+            SwapQuote {
+                gross: Quantity::new(2_000000),
+                fee: Quantity::new(20000),
+                fee_fraction: Fraction::from_scale(1, 2),
+                kept_fee: Quantity::new(10000),
+                // 20 / (20 + 20)
+                proportion_before: Fraction::from_scale(5, 1),
+                // 22 / (22 + 18)
+                proportion_after: Fraction::from_scale(55, 2),
+                price_impact: Fraction::from_scale(1, 2),
+            }
+        );
+        assert_eq!(quote.output(), Quantity::new(1_980000));
+        // 1_980000 * (1 - 1%)
+        assert_eq!(
+            quote.min_output(Fraction::from_scale(1, 2)),
+            Quantity::new(1_960200)
+        );
+
+        assert_eq!(
+            swap.sell(Quantity::new(1_000000), &base_oracle, &quote_oracle),
+            Ok(quote.output())
+        );
+        assert_eq!(swap.total_kept_fee.quote, quote.kept_fee);
+
+        Ok(())
+    }
+
+    #[test]
+    fn inventory_skew() -> Result<(), LibErrors> {
+        let base_oracle = Oracle::new_for_test();
+        let quote_oracle = Oracle::new_stable_for_test();
+
+        let mut swap = Swap::default();
+        swap.fee_curve_sell()
+            .add_constant_fee(Fraction::from_integer(0), Fraction::from_integer(1));
+        swap.fee_curve_buy()
+            .add_constant_fee(Fraction::from_integer(0), Fraction::from_integer(1));
+
+        // base proportion = 20 / (20 + 20) = 0.5
+        swap.add_liquidity_base(Quantity::new(10_000000));
+        swap.add_liquidity_quote(Quantity::new(20_000000));
+
+        assert_eq!(
+            swap.set_inventory_skew(Fraction::from_integer(1), Fraction::from_scale(2, 1)),
+            Err(LibErrors::InvalidInventorySkew)
+        );
+        assert_eq!(
+            swap.set_inventory_skew(Fraction::new(0), Fraction::from_scale(2, 1)),
+            Err(LibErrors::InvalidInventorySkew)
+        );
+
+        // disabled
+        assert_eq!(
+            swap.sell(Quantity::new(1_000000), &base_oracle, &quote_oracle),
+            Ok(Quantity::new(2_000000))
+        );
+
+        // selling moves the proportion to 22 / (22 + 18) = 0.55 and buying to 18 / (18 + 22) = 0.45,
+        // the skew is taken at the midpoints 0.525 and 0.475
+
+        // too much base, shift = 0.2 * (0.525 - 0.25) = 0.055 and 0.2 * (0.475 - 0.25) = 0.045
+        swap.set_inventory_skew(Fraction::from_scale(25, 2), Fraction::from_scale(2, 1))?;
+
+        assert_eq!(
+            swap.sell(Quantity::new(1_000000), &base_oracle, &quote_oracle),
+            Ok(Quantity::new(1_890000))
+        );
+        assert_eq!(
+            swap.buy(Quantity::new(2_000000), &base_oracle, &quote_oracle),
+            Ok(Quantity::new(1_045000))
+        );
+
+        // too little base, shift = 0.2 * (0.75 - 0.525) = 0.045 and 0.2 * (0.75 - 0.475) = 0.055
+        swap.set_inventory_skew(Fraction::from_scale(75, 2), Fraction::from_scale(2, 1))?;
+
+        assert_eq!(
+            swap.sell(Quantity::new(1_000000), &base_oracle, &quote_oracle),
+            Ok(Quantity::new(2_090000))
+        );
+        assert_eq!(
+            swap.buy(Quantity::new(2_000000), &base_oracle, &quote_oracle),
+            Ok(Quantity::new(945000))
+        );
+
+        // selling crosses the target, the bonus below it is outweighed by the penalty above it,
+        // shift = 0.2 * (0.525 - 0.52) = 0.001
+        swap.set_inventory_skew(Fraction::from_scale(52, 2), Fraction::from_scale(2, 1))?;
+
+        assert_eq!(
+            swap.sell(Quantity::new(1_000000), &base_oracle, &quote_oracle),
+            Ok(Quantity::new(1_998000))
+        );
+
+        swap.set_inventory_skew(Fraction::new(0), Fraction::new(0))?;
+        assert_eq!(
+            swap.skew_multiplier(Fraction::from_scale(1, 1), true),
+            Fraction::from_integer(1)
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_buy_with_fee() {
         let base_oracle = Oracle::new_for_test();
@@ -7,9 +7,10 @@ use crate::core_lib::{
         Fraction, FundingRate, Quantity, Time, Utilization, Value,
     },
     errors::LibErrors,
+    strategy::Strategy,
     structs::{
         oracle::{Oracle, OraclePriceType},
-        Receipt, Side,
+        FeeCurve, Receipt, Side,
     },
     user::ValueChange,
 };
@@ -178,6 +179,25 @@ impl Trade {
         }
     }
 
+    /// risk parameters of positions, open positions are held to them as well
+    pub fn set_params(
+        &mut self,
+        open_fee: Fraction,
+        max_leverage: Fraction,
+        collateral_ratio: Fraction,
+        liquidation_threshold: Fraction,
+    ) -> Result<(), LibErrors> {
+        Strategy::validate_risk_params(collateral_ratio, liquidation_threshold)?;
+
+        self.open_fee = open_fee;
+        self.max_open_leverage = max_leverage;
+        self.max_leverage = max_leverage;
+        self.collateral_ratio = collateral_ratio;
+        self.liquidation_threshold = liquidation_threshold;
+
+        Ok(())
+    }
+
     /// opens a long position
     pub fn open_long(
         &mut self,
@@ -548,6 +568,14 @@ impl Trade {
         }
     }
 
+    pub fn fee_curve_base(&mut self) -> &mut FeeCurve {
+        &mut self.borrow_fee.base
+    }
+
+    pub fn fee_curve_quote(&mut self) -> &mut FeeCurve {
+        &mut self.borrow_fee.quote
+    }
+
     #[cfg(test)]
     pub fn new_test() -> Trade {
         Trade::new(
@@ -1,5 +1,9 @@
+pub mod queue;
 pub mod strategies;
 pub mod strategy;
+pub mod yields;
 
+pub use queue::*;
 pub use strategies::*;
 pub use strategy::*;
+pub use yields::*;
@@ -0,0 +1,192 @@
+use crate::core_lib::{
+    decimal::{Balances, Quantity, Shares},
+    errors::LibErrors,
+};
+use checked_decimal_macro::Decimal;
+use std::cmp::min;
+
+pub const WITHDRAW_QUEUE_SIZE: usize = 8;
+
+#[cfg(feature = "anchor")]
+mod zero {
+    use super::*;
+    use anchor_lang::prelude::*;
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct WithdrawRequest {
+        /// Key of the statement owner that made the request
+        pub owner: [u8; 32],
+        /// Order of the request in the queue, zero marks an empty slot
+        pub ticket: u64,
+        /// Quantities owed to the owner, fixed at the time of the request
+        pub owed: Balances,
+        /// Part of the owed quantities already set aside, out of reach of services
+        pub reserved: Balances,
+        /// Shares burned for the request, restored on cancel
+        pub shares: Shares,
+        /// Whether the owed quantities were set aside and can be claimed
+        pub filled: bool,
+    }
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct WithdrawQueue {
+        /// Ticket of the last request
+        pub last_ticket: u64,
+        /// Sum of quantities owed to requests that are not set aside yet
+        pub pending: Balances,
+        pub requests: [WithdrawRequest; WITHDRAW_QUEUE_SIZE],
+    }
+}
+
+#[cfg(not(feature = "anchor"))]
+mod non_zero {
+    use super::*;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct WithdrawRequest {
+        /// Key of the statement owner that made the request
+        pub owner: [u8; 32],
+        /// Order of the request in the queue, zero marks an empty slot
+        pub ticket: u64,
+        /// Quantities owed to the owner, fixed at the time of the request
+        pub owed: Balances,
+        /// Part of the owed quantities already set aside, out of reach of services
+        pub reserved: Balances,
+        /// Shares burned for the request, restored on cancel
+        pub shares: Shares,
+        /// Whether the owed quantities were set aside and can be claimed
+        pub filled: bool,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct WithdrawQueue {
+        /// Ticket of the last request
+        pub last_ticket: u64,
+        /// Sum of quantities owed to requests that are not set aside yet
+        pub pending: Balances,
+        pub requests: [WithdrawRequest; WITHDRAW_QUEUE_SIZE],
+    }
+}
+
+#[cfg(feature = "anchor")]
+pub use zero::{WithdrawQueue, WithdrawRequest};
+
+#[cfg(not(feature = "anchor"))]
+pub use non_zero::{WithdrawQueue, WithdrawRequest};
+
+impl WithdrawQueue {
+    /// Adds a request at the end of the queue, returns its ticket
+    pub fn push(
+        &mut self,
+        owner: [u8; 32],
+        owed: Balances,
+        shares: Shares,
+    ) -> Result<u64, LibErrors> {
+        let slot = self
+            .requests
+            .iter_mut()
+            .find(|request| request.ticket == 0)
+            .ok_or(LibErrors::WithdrawQueueFull)?;
+
+        self.last_ticket += 1;
+        *slot = WithdrawRequest {
+            owner,
+            ticket: self.last_ticket,
+            owed,
+            reserved: Balances::default(),
+            shares,
+            filled: false,
+        };
+        self.pending += owed;
+
+        Ok(self.last_ticket)
+    }
+
+    /// Index of the oldest request that is not filled yet
+    pub fn next_unfilled(&self) -> Option<usize> {
+        self.requests
+            .iter()
+            .enumerate()
+            .filter(|(_, request)| request.ticket != 0 && !request.filled)
+            .min_by_key(|(_, request)| request.ticket)
+            .map(|(index, _)| index)
+    }
+
+    /// Sets aside as much of the request as `available` allows, returns the quantities taken.
+    /// Request is filled once all of it is set aside
+    pub fn reserve(&mut self, index: usize, available: Balances) -> Balances {
+        let request = &mut self.requests[index];
+        let missing = request.owed - request.reserved;
+        let taken = Balances {
+            base: min(missing.base, available.base),
+            quote: min(missing.quote, available.quote),
+        };
+
+        request.reserved += taken;
+        request.filled = request.reserved == request.owed;
+        self.pending -= taken;
+
+        taken
+    }
+
+    /// Removes the request of the owner from the queue, filled ones are claimed and the rest cancelled
+    pub fn take(
+        &mut self,
+        owner: &[u8; 32],
+        ticket: u64,
+        filled: bool,
+    ) -> Result<WithdrawRequest, LibErrors> {
+        if ticket == 0 {
+            return Err(LibErrors::WithdrawRequestNotFound);
+        }
+
+        let request = self
+            .requests
+            .iter_mut()
+            .find(|request| request.ticket == ticket)
+            .ok_or(LibErrors::WithdrawRequestNotFound)?;
+
+        if request.owner != *owner {
+            return Err(LibErrors::WithdrawRequestNotOwned);
+        }
+
+        match (request.filled, filled) {
+            (false, true) => return Err(LibErrors::WithdrawRequestNotFilled),
+            (true, false) => return Err(LibErrors::WithdrawRequestFilled),
+            _ => {}
+        }
+
+        let taken = *request;
+        *request = WithdrawRequest::default();
+
+        if !taken.filled {
+            self.pending -= taken.owed - taken.reserved;
+        }
+
+        Ok(taken)
+    }
+
+    pub fn pending(&self) -> Balances {
+        self.pending
+    }
+
+    /// Whether no request is waiting to be filled or claimed
+    pub fn is_empty(&self) -> bool {
+        self.requests.iter().all(|request| request.ticket == 0)
+    }
+
+    /// Part of the `quantity` that is not owed to pending requests
+    pub fn exclude_pending(quantity: Quantity, pending: Quantity) -> Quantity {
+        if quantity > pending {
+            quantity - pending
+        } else {
+            Quantity::new(0)
+        }
+    }
+}
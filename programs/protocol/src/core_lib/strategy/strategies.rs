@@ -1,6 +1,6 @@
-use crate::core_lib::errors::LibErrors;
+use crate::core_lib::{decimal::Time, errors::LibErrors};
 
-use super::Strategy;
+use super::{Strategy, YieldSnapshots};
 use checked_decimal_macro::num_traits::ToPrimitive;
 use std::{
     ops::Range,
@@ -8,6 +8,12 @@ use std::{
 };
 use vec_macro::SafeArray;
 
+/// Number of strategy slots in a vault, yield histories keep snapshots for each of them
+pub const MAX_STRATEGIES: usize = 6;
+
+// `SafeArray` needs a literal length, the arrays below are sized by hand
+const _: () = assert!(MAX_STRATEGIES == 6);
+
 #[cfg(feature = "anchor")]
 mod zero {
     use super::*;
@@ -51,6 +57,16 @@ impl Strategies {
             .get_checked(id as usize)
             .ok_or(LibErrors::NoStrategyOnIndex)?)
     }
+
+    /// Takes snapshots of cumulative yields of every strategy into its slot of the `history`
+    pub fn snapshot_yields(&self, history: &mut [YieldSnapshots], now: Time) {
+        if let Some(strategies) = self.iter() {
+            history
+                .iter_mut()
+                .zip(strategies)
+                .for_each(|(snapshots, strategy)| snapshots.snapshot(&strategy.yields, now));
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,39 @@
-use crate::core_lib::decimal::{Balances, Fraction, Quantity, Shares};
+use crate::core_lib::decimal::{Balances, Fraction, Quantity, Shares, Time};
 use crate::core_lib::errors::LibErrors;
 use crate::core_lib::services::{ServiceType, ServiceUpdate, Services};
+use crate::core_lib::structs::Oracle;
+use checked_decimal_macro::Factories;
+
+use super::{WithdrawQueue, YieldSnapshots, Yields};
+
+/// Lifecycle of a strategy, allowing it to be wound down without forcing positions to close
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum StrategyState {
+    /// Accepts deposits and provides liquidity for new locks
+    #[default]
+    Active = 0,
+    /// Does not accept deposits, but still provides liquidity for new locks
+    DepositClosed = 1,
+    /// Does not accept deposits nor provide liquidity, capital is released as positions close
+    WithdrawOnly = 2,
+    /// Emptied and closed for good
+    Retired = 3,
+}
+
+impl TryFrom<u8> for StrategyState {
+    type Error = LibErrors;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Active),
+            1 => Ok(Self::DepositClosed),
+            2 => Ok(Self::WithdrawOnly),
+            3 => Ok(Self::Retired),
+            _ => Err(LibErrors::InvalidStrategyState),
+        }
+    }
+}
 
 #[cfg(feature = "anchor")]
 mod zero {
@@ -32,6 +65,15 @@ mod zero {
         pub collateral_ratio: Fraction,
         /// Ratio at which value of shares is calculated during liquidation
         pub liquidation_threshold: Fraction,
+        /// Lets swap service use available liquidity of this strategy, when it does not swap itself
+        pub shared_swap: bool,
+        /// Stage of the strategy lifecycle, used to wind it down
+        pub state: StrategyState,
+        /// Requests of withdrawals waiting for locked liquidity to return
+        pub withdraw_queue: WithdrawQueue,
+
+        /// Cumulative earnings and losses, snapshots of them are kept in the yield history account
+        pub yields: Yields,
     }
 }
 
@@ -62,6 +104,15 @@ mod non_zero {
         pub collateral_ratio: Fraction,
         /// Ratio at which value of shares is calculated during liquidation
         pub liquidation_threshold: Fraction,
+        /// Lets swap service use available liquidity of this strategy, when it does not swap itself
+        pub shared_swap: bool,
+        /// Stage of the strategy lifecycle, used to wind it down
+        pub state: StrategyState,
+        /// Requests of withdrawals waiting for locked liquidity to return
+        pub withdraw_queue: WithdrawQueue,
+
+        /// Cumulative earnings and losses, snapshots of them are kept in the yield history account
+        pub yields: Yields,
     }
 }
 
@@ -109,12 +160,29 @@ impl Strategy {
         self.locked.quote
     }
 
+    /// Balance of the strategy, without quantities owed to queued withdrawals
     pub fn balance(&self) -> Quantity {
-        self.available.base + self.locked.base
+        WithdrawQueue::exclude_pending(
+            self.available.base + self.locked.base,
+            self.withdraw_queue.pending.base,
+        )
     }
 
     pub fn balance_quote(&self) -> Quantity {
-        self.available.quote + self.locked.quote
+        WithdrawQueue::exclude_pending(
+            self.available.quote + self.locked.quote,
+            self.withdraw_queue.pending.quote,
+        )
+    }
+
+    /// Available quantities that can be withdrawn without skipping the withdraw queue
+    pub fn withdrawable(&self) -> Balances {
+        let pending = self.withdraw_queue.pending;
+
+        Balances {
+            base: WithdrawQueue::exclude_pending(self.available.base, pending.base),
+            quote: WithdrawQueue::exclude_pending(self.available.quote, pending.quote),
+        }
     }
 
     pub fn total_shares(&self) -> Shares {
@@ -141,6 +209,11 @@ impl Strategy {
         self.traded.is_some()
     }
 
+    /// Whether strategy takes part in operations of the service, directly or by sharing its liquidity
+    pub fn participates(&self, service: ServiceType) -> bool {
+        self.uses(service) || (matches!(service, ServiceType::Swap) && self.shared_swap)
+    }
+
     pub fn uses(&self, service: ServiceType) -> bool {
         match service {
             ServiceType::Lend => self.lent.is_some(),
@@ -149,6 +222,137 @@ impl Strategy {
         }
     }
 
+    pub fn accepts_deposits(&self) -> bool {
+        matches!(self.state, StrategyState::Active)
+    }
+
+    /// Whether new locks can be allocated to the strategy
+    pub fn allocates(&self) -> bool {
+        matches!(
+            self.state,
+            StrategyState::Active | StrategyState::DepositClosed
+        )
+    }
+
+    pub fn accepts_withdrawals(&self) -> bool {
+        !matches!(self.state, StrategyState::Retired)
+    }
+
+    /// Moves the strategy through its lifecycle. Retiring is final and requires an empty strategy
+    /// with no withdraw requests left to claim
+    pub fn set_state(&mut self, state: StrategyState) -> Result<(), LibErrors> {
+        if matches!(self.state, StrategyState::Retired) {
+            return Err(LibErrors::InvalidStrategyState);
+        }
+
+        if matches!(state, StrategyState::Retired)
+            && (self.locked != Balances::default()
+                || !self.total_shares.is_zero()
+                || !self.withdraw_queue.is_empty())
+        {
+            return Err(LibErrors::StrategyNotEmpty);
+        }
+
+        self.state = state;
+        Ok(())
+    }
+
+    pub fn validate_risk_params(
+        collateral_ratio: Fraction,
+        liquidation_threshold: Fraction,
+    ) -> Result<(), LibErrors> {
+        if liquidation_threshold < collateral_ratio
+            || liquidation_threshold > Fraction::from_integer(1)
+        {
+            return Err(LibErrors::InvalidRiskParams);
+        }
+
+        Ok(())
+    }
+
+    pub fn set_risk_params(
+        &mut self,
+        collateral_ratio: Fraction,
+        liquidation_threshold: Fraction,
+    ) -> Result<(), LibErrors> {
+        Self::validate_risk_params(collateral_ratio, liquidation_threshold)?;
+
+        self.collateral_ratio = collateral_ratio;
+        self.liquidation_threshold = liquidation_threshold;
+
+        Ok(())
+    }
+
+    /// Enables or disables services, moving available liquidity of the strategy accordingly.
+    /// Only possible when nothing is locked in the strategy
+    pub fn set_services(
+        &mut self,
+        lend: bool,
+        swap: bool,
+        trade: bool,
+        services: &mut Services,
+    ) -> Result<(), LibErrors> {
+        if self.locked != Balances::default() {
+            return Err(LibErrors::StrategyHasLockedBalance);
+        }
+
+        // toggled services have to exist, checked before anything is moved
+        if lend != self.is_lending_enabled() {
+            services.lend_mut()?;
+        }
+        if swap != self.is_swapping_enabled() {
+            services.swap_mut()?;
+        }
+        if trade != self.is_trading_enabled() {
+            services.trade_mut()?;
+        }
+
+        let Balances { base, quote } = self.available;
+
+        if lend != self.is_lending_enabled() {
+            let lend_service = services.lend_mut()?;
+
+            if lend {
+                lend_service.add_available_base(base);
+                self.lent = Some(Quantity::default());
+            } else {
+                lend_service.remove_available_base(base);
+                self.lent = None;
+            }
+        }
+
+        if swap != self.is_swapping_enabled() {
+            let swap_service = services.swap_mut()?;
+
+            if swap {
+                swap_service.add_liquidity_base(base);
+                swap_service.add_liquidity_quote(quote);
+                self.sold = Some(Balances::default());
+                self.shared_swap = false;
+            } else {
+                swap_service.remove_liquidity_base(base);
+                swap_service.remove_liquidity_quote(quote);
+                self.sold = None;
+            }
+        }
+
+        if trade != self.is_trading_enabled() {
+            let trade_service = services.trade_mut()?;
+
+            if trade {
+                trade_service.add_available_base(base);
+                trade_service.add_available_quote(quote);
+                self.traded = Some(Balances::default());
+            } else {
+                trade_service.remove_available_base(base);
+                trade_service.remove_available_quote(quote);
+                self.traded = None;
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn new(
         lend: bool,
         swap: bool,
@@ -299,6 +503,7 @@ impl Strategy {
         *self.locked_in_mut(sub) += quantity;
         self.accrued_fee += quantity;
         self.locked.base += quantity;
+        self.yields.earned.base += quantity;
 
         if self.sold.is_some() {
             let swap = services.swap_mut().unwrap();
@@ -507,11 +712,249 @@ impl Strategy {
         Ok(())
     }
 
-    pub fn available_in(&self, _service: ServiceType) -> Quantity {
-        self.available()
+    pub fn earn_base(
+        &mut self,
+        quantity: Quantity,
+        _: ServiceType,
+        _: &mut Services,
+    ) -> Result<(), LibErrors> {
+        self.yields.earned.base += quantity;
+        Ok(())
+    }
+
+    pub fn earn_quote(
+        &mut self,
+        quantity: Quantity,
+        _: ServiceType,
+        _: &mut Services,
+    ) -> Result<(), LibErrors> {
+        self.yields.earned.quote += quantity;
+        Ok(())
+    }
+
+    pub fn lose_base(
+        &mut self,
+        quantity: Quantity,
+        _: ServiceType,
+        _: &mut Services,
+    ) -> Result<(), LibErrors> {
+        self.yields.lost.base += quantity;
+        Ok(())
+    }
+
+    pub fn lose_quote(
+        &mut self,
+        quantity: Quantity,
+        _: ServiceType,
+        _: &mut Services,
+    ) -> Result<(), LibErrors> {
+        self.yields.lost.quote += quantity;
+        Ok(())
+    }
+
+    /// Annualized earnings and losses of the strategy over the `window` of its `history`,
+    /// relative to its current balance
+    pub fn get_apy(
+        &self,
+        history: &YieldSnapshots,
+        window: Time,
+        now: Time,
+        oracle: &Oracle,
+        quote_oracle: &Oracle,
+    ) -> (Fraction, Fraction) {
+        let principal = oracle.calculate_value(self.balance())
+            + quote_oracle.calculate_value(self.balance_quote());
+
+        history.get_apy(&self.yields, principal, window, now, oracle, quote_oracle)
+    }
+
+    pub fn available_in(&self, service: ServiceType) -> Quantity {
+        match self.shares_with(service) {
+            true => self.withdrawable().base,
+            false => self.available(),
+        }
+    }
+
+    pub fn available_in_quote(&self, service: ServiceType) -> Quantity {
+        match self.shares_with(service) {
+            true => self.withdrawable().quote,
+            false => self.available_quote(),
+        }
+    }
+
+    /// Whether the strategy only lends its liquidity to the service, without using it itself
+    fn shares_with(&self, service: ServiceType) -> bool {
+        self.participates(service) && !self.uses(service)
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_lib::{user::UserStatement, Token, Vault};
+    use checked_decimal_macro::Decimal;
+
+    #[test]
+    fn risk_params() {
+        let mut strategy = Strategy::default();
+
+        assert_eq!(
+            strategy.set_risk_params(Fraction::from_scale(8, 1), Fraction::from_scale(7, 1)),
+            Err(LibErrors::InvalidRiskParams)
+        );
+        assert_eq!(
+            strategy.set_risk_params(Fraction::from_scale(8, 1), Fraction::from_scale(11, 1)),
+            Err(LibErrors::InvalidRiskParams)
+        );
+        assert_eq!(
+            strategy.set_risk_params(Fraction::from_scale(8, 1), Fraction::from_scale(9, 1)),
+            Ok(())
+        );
+        assert_eq!(strategy.collateral_ratio(), Fraction::from_scale(8, 1));
+        assert_eq!(strategy.liquidation_threshold(), Fraction::from_scale(9, 1));
+    }
+
+    #[test]
+    fn toggle_services() -> Result<(), LibErrors> {
+        let mut vault = Vault::new_vault_for_tests()?;
+        let user_statement = &mut UserStatement::default();
+
+        // lend only
+        vault.deposit(user_statement, Token::Base, Quantity::new(1000000), 2)?;
+
+        vault.set_strategy_services(2, false, true, true)?;
+
+        let strategy = vault.strategy(2)?;
+        assert!(!strategy.is_lending_enabled());
+        assert!(strategy.is_swapping_enabled());
+        assert!(strategy.is_trading_enabled());
+        assert_eq!(
+            vault.lend_service_not_mut()?.available().base,
+            Quantity::new(0)
+        );
+        assert_eq!(
+            vault.swap_service_not_mut()?.available(),
+            Balances {
+                base: Quantity::new(1000000),
+                quote: Quantity::new(2000000)
+            }
+        );
+        assert_eq!(
+            vault.trade_service_not_mut()?.available(),
+            Balances {
+                base: Quantity::new(1000000),
+                quote: Quantity::new(2000000)
+            }
+        );
+
+        vault.lock_base(
+            Quantity::new(1000),
+            Quantity::new(1000000),
+            ServiceType::Trade,
+        )?;
+
+        assert_eq!(
+            vault.set_strategy_services(2, true, true, true),
+            Err(LibErrors::StrategyHasLockedBalance)
+        );
+
+        vault.unlock_base(Quantity::new(1000), Quantity::new(1000), ServiceType::Trade)?;
+        vault.set_strategy_services(2, true, false, false)?;
+
+        assert_eq!(
+            vault.lend_service_not_mut()?.available().base,
+            Quantity::new(1000000)
+        );
+        assert_eq!(
+            vault.swap_service_not_mut()?.available(),
+            Balances::default()
+        );
 
-    pub fn available_in_quote(&self, _service: ServiceType) -> Quantity {
-        self.available_quote()
+        Ok(())
+    }
+
+    #[test]
+    fn wind_down() -> Result<(), LibErrors> {
+        let mut vault = Vault::new_vault_for_tests()?;
+        let user_statement = &mut UserStatement::default();
+
+        vault.deposit(user_statement, Token::Base, Quantity::new(1000000), 0)?;
+        vault.deposit(user_statement, Token::Base, Quantity::new(1000000), 2)?;
+
+        assert_eq!(
+            StrategyState::try_from(4),
+            Err(LibErrors::InvalidStrategyState)
+        );
+        vault
+            .strategy_mut(0)?
+            .set_state(StrategyState::WithdrawOnly)?;
+
+        assert_eq!(
+            vault.deposit(user_statement, Token::Base, Quantity::new(1000), 0),
+            Err(LibErrors::StrategyClosed)
+        );
+
+        // new locks go only to the strategy that is still active
+        let available = vault.lend_service_not_mut()?.available().base;
+        assert_eq!(available, Quantity::new(2000000));
+        vault.lock_base(Quantity::new(1000), available, ServiceType::Lend)?;
+
+        assert_eq!(vault.strategy(0)?.locked(), Quantity::new(0));
+        assert_eq!(vault.strategy(2)?.locked(), Quantity::new(1000));
+
+        let available = vault.lend_service_not_mut()?.available().base;
+        assert_eq!(
+            vault.lock_base(Quantity::new(1000000), available, ServiceType::Lend),
+            Err(LibErrors::NotEnoughAvailableLiquidity)
+        );
+
+        // cannot be retired while liquidity providers are still in
+        assert_eq!(
+            vault.strategy_mut(0)?.set_state(StrategyState::Retired),
+            Err(LibErrors::StrategyNotEmpty)
+        );
+
+        vault.withdraw(user_statement, Token::Base, Quantity::new(1000000), 0)?;
+
+        // rounding dust left without shares is swept into the insurance fund on retiring
+        let Vault {
+            strategies,
+            services,
+            ..
+        } = &mut vault;
+        strategies.get_strategy_mut(0)?.deposit(
+            Quantity::new(3),
+            Quantity::new(5),
+            Shares::new(0),
+            services,
+        );
+        let insured = vault.insurance.balance;
+
+        assert_eq!(
+            vault.set_strategy_state(0, StrategyState::Retired)?,
+            Balances {
+                base: Quantity::new(3),
+                quote: Quantity::new(5),
+            }
+        );
+        assert_eq!(vault.strategy(0)?.available, Balances::default());
+        assert_eq!(
+            vault.insurance.balance - insured,
+            Balances {
+                base: Quantity::new(3),
+                quote: Quantity::new(5),
+            }
+        );
+
+        assert_eq!(
+            vault.withdraw(user_statement, Token::Base, Quantity::new(1000), 0),
+            Err(LibErrors::StrategyClosed)
+        );
+        assert_eq!(
+            vault.set_strategy_state(0, StrategyState::Active),
+            Err(LibErrors::InvalidStrategyState)
+        );
+
+        Ok(())
     }
 }
@@ -0,0 +1,312 @@
+use crate::core_lib::{
+    decimal::{Balances, Fraction, Time, Value},
+    structs::{fee_curve::HOUR_DURATION, Oracle},
+};
+use checked_decimal_macro::{BetweenDecimals, Decimal, Factories};
+
+pub const YIELD_SNAPSHOTS: usize = 32;
+pub const SNAPSHOT_INTERVAL: Time = 24 * HOUR_DURATION;
+pub const YEAR_DURATION: Time = 365 * 24 * HOUR_DURATION;
+
+#[cfg(feature = "anchor")]
+mod zero {
+    use super::*;
+    use anchor_lang::prelude::*;
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct YieldSnapshot {
+        /// Time at which snapshot was taken
+        pub timestamp: Time,
+        /// Cumulative earnings at the time of snapshot
+        pub earned: Balances,
+        /// Cumulative losses at the time of snapshot
+        pub lost: Balances,
+    }
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct Yields {
+        /// Cumulative earnings (lend interest, swap fees, trading fees and losses of traders)
+        pub earned: Balances,
+        /// Cumulative losses (profits of traders)
+        pub lost: Balances,
+    }
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct YieldSnapshots {
+        /// Index of the newest snapshot
+        pub head: u8,
+        /// Number of taken snapshots
+        pub count: u8,
+        pub snapshots: [YieldSnapshot; YIELD_SNAPSHOTS],
+    }
+}
+
+#[cfg(not(feature = "anchor"))]
+mod non_zero {
+    use super::*;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct YieldSnapshot {
+        /// Time at which snapshot was taken
+        pub timestamp: Time,
+        /// Cumulative earnings at the time of snapshot
+        pub earned: Balances,
+        /// Cumulative losses at the time of snapshot
+        pub lost: Balances,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct Yields {
+        /// Cumulative earnings (lend interest, swap fees, trading fees and losses of traders)
+        pub earned: Balances,
+        /// Cumulative losses (profits of traders)
+        pub lost: Balances,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct YieldSnapshots {
+        /// Index of the newest snapshot
+        pub head: u8,
+        /// Number of taken snapshots
+        pub count: u8,
+        pub snapshots: [YieldSnapshot; YIELD_SNAPSHOTS],
+    }
+}
+
+#[cfg(feature = "anchor")]
+pub use zero::{YieldSnapshot, YieldSnapshots, Yields};
+
+#[cfg(not(feature = "anchor"))]
+pub use non_zero::{YieldSnapshot, YieldSnapshots, Yields};
+
+impl YieldSnapshot {
+    fn precedes(&self, yields: &Yields) -> bool {
+        self.earned.base <= yields.earned.base
+            && self.earned.quote <= yields.earned.quote
+            && self.lost.base <= yields.lost.base
+            && self.lost.quote <= yields.lost.quote
+    }
+}
+
+impl YieldSnapshots {
+    /// Takes a snapshot of cumulative values, at most once per `SNAPSHOT_INTERVAL`
+    pub fn snapshot(&mut self, yields: &Yields, now: Time) {
+        // cumulative values only go down when the strategy slot was taken by a new strategy
+        if self.count > 0 && !self.snapshots[self.head as usize].precedes(yields) {
+            *self = Self::default();
+        }
+
+        if self.count > 0 {
+            let newest = &self.snapshots[self.head as usize];
+
+            if now < newest.timestamp + SNAPSHOT_INTERVAL {
+                return;
+            }
+
+            self.head = ((self.head as usize + 1) % YIELD_SNAPSHOTS) as u8;
+        }
+
+        self.snapshots[self.head as usize] = YieldSnapshot {
+            timestamp: now,
+            earned: yields.earned,
+            lost: yields.lost,
+        };
+
+        if (self.count as usize) < YIELD_SNAPSHOTS {
+            self.count += 1;
+        }
+    }
+
+    /// Newest snapshot that is at least `window` old, or the oldest one if there is none
+    pub fn snapshot_before(&self, window: Time, now: Time) -> Option<&YieldSnapshot> {
+        let mut found = None;
+
+        for i in 0..self.count as usize {
+            let index = (self.head as usize + YIELD_SNAPSHOTS - i) % YIELD_SNAPSHOTS;
+            let snapshot = &self.snapshots[index];
+            found = Some(snapshot);
+
+            if snapshot.timestamp + window <= now {
+                break;
+            }
+        }
+
+        found
+    }
+
+    /// Annualized earnings and losses over the `window` relative to the `principal` value
+    pub fn get_apy(
+        &self,
+        yields: &Yields,
+        principal: Value,
+        window: Time,
+        now: Time,
+        oracle: &Oracle,
+        quote_oracle: &Oracle,
+    ) -> (Fraction, Fraction) {
+        let zero = (Fraction::new(0), Fraction::new(0));
+
+        let snapshot = match self.snapshot_before(window, now) {
+            Some(snapshot) => snapshot,
+            None => return zero,
+        };
+
+        if principal == Value::new(0) || now <= snapshot.timestamp || !snapshot.precedes(yields) {
+            return zero;
+        }
+
+        let annualize = |change: Balances| {
+            let value =
+                oracle.calculate_value(change.base) + quote_oracle.calculate_value(change.quote);
+
+            Fraction::from_decimal(
+                value * Value::from_integer(YEAR_DURATION)
+                    / Value::from_integer(now - snapshot.timestamp)
+                    / principal,
+            )
+        };
+
+        (
+            annualize(yields.earned - snapshot.earned),
+            annualize(yields.lost - snapshot.lost),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_lib::decimal::{DecimalPlaces, Price, Quantity};
+
+    #[test]
+    fn snapshots() {
+        let mut yields = Yields::default();
+        let mut history = YieldSnapshots::default();
+
+        assert!(history.snapshot_before(SNAPSHOT_INTERVAL, 0).is_none());
+
+        history.snapshot(&yields, 100);
+        yields.earned.base = Quantity::new(10);
+        // too early for the next snapshot
+        history.snapshot(&yields, 100 + SNAPSHOT_INTERVAL - 1);
+        assert_eq!(history.count, 1);
+
+        history.snapshot(&yields, 100 + SNAPSHOT_INTERVAL);
+        assert_eq!(history.count, 2);
+        assert_eq!(history.head, 1);
+        assert_eq!(history.snapshots[1].earned.base, Quantity::new(10));
+
+        // only one snapshot old enough
+        let now = 100 + 2 * SNAPSHOT_INTERVAL;
+        assert_eq!(
+            history.snapshot_before(SNAPSHOT_INTERVAL, now),
+            Some(&history.snapshots[1])
+        );
+        // falls back to the oldest
+        assert_eq!(
+            history.snapshot_before(7 * SNAPSHOT_INTERVAL, now),
+            Some(&history.snapshots[0])
+        );
+
+        // ring buffer wraps around
+        for i in 2..=YIELD_SNAPSHOTS as u32 {
+            history.snapshot(&yields, 100 + i * SNAPSHOT_INTERVAL);
+        }
+        assert_eq!(history.count as usize, YIELD_SNAPSHOTS);
+        assert_eq!(history.head, 0);
+        assert_eq!(
+            history.snapshots[0].timestamp,
+            100 + YIELD_SNAPSHOTS as u32 * SNAPSHOT_INTERVAL
+        );
+
+        // slot taken by a new strategy
+        history.snapshot(
+            &Yields::default(),
+            200 + YIELD_SNAPSHOTS as u32 * SNAPSHOT_INTERVAL,
+        );
+        assert_eq!(history.count, 1);
+        assert_eq!(history.head, 0);
+    }
+
+    #[test]
+    fn snapshot_strategies() -> Result<(), crate::core_lib::errors::LibErrors> {
+        let mut vault = crate::core_lib::Vault::new_vault_for_tests()?;
+        let mut history = [YieldSnapshots::default(); 6];
+
+        vault.strategy_mut(1)?.yields.earned.quote = Quantity::new(10);
+        vault.strategies.snapshot_yields(&mut history, 100);
+
+        let strategies = vault.strategies.head as usize;
+        assert!(history[..strategies].iter().all(|h| h.count == 1));
+        assert!(history[strategies..].iter().all(|h| h.count == 0));
+        assert_eq!(history[1].snapshots[0].earned.quote, Quantity::new(10));
+
+        Ok(())
+    }
+
+    #[test]
+    fn apy() {
+        let oracle = Oracle::new(
+            DecimalPlaces::Six,
+            Price::from_integer(2),
+            Price::from_integer(0),
+            Price::from_scale(1, 2),
+            0,
+            0,
+        );
+        let quote_oracle = Oracle::new(
+            DecimalPlaces::Six,
+            Price::from_integer(1),
+            Price::from_integer(0),
+            Price::from_scale(1, 2),
+            0,
+            0,
+        );
+
+        let mut yields = Yields::default();
+        let mut history = YieldSnapshots::default();
+        let principal = Value::from_integer(1000);
+
+        assert_eq!(
+            history.get_apy(
+                &yields,
+                principal,
+                SNAPSHOT_INTERVAL,
+                0,
+                &oracle,
+                &quote_oracle
+            ),
+            (Fraction::new(0), Fraction::new(0))
+        );
+
+        history.snapshot(&yields, 0);
+
+        // 1 base (2 value) and 1 quote earned, 1 quote lost over a day
+        yields.earned = Balances {
+            base: Quantity::new(1000000),
+            quote: Quantity::new(1000000),
+        };
+        yields.lost.quote = Quantity::new(1000000);
+
+        let (earned, lost) = history.get_apy(
+            &yields,
+            principal,
+            SNAPSHOT_INTERVAL,
+            SNAPSHOT_INTERVAL,
+            &oracle,
+            &quote_oracle,
+        );
+
+        assert_eq!(earned, Fraction::from_scale(1095, 3));
+        assert_eq!(lost, Fraction::from_scale(365, 3));
+    }
+}
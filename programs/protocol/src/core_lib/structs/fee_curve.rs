@@ -15,6 +15,12 @@ pub enum CurveSegment {
         a: Fraction,
         b: Fraction,
     },
+    /// linear segment starting at the kink: `a * (utilization - kink) + b`
+    Kinked {
+        a: Fraction,
+        b: Fraction,
+        kink: Fraction,
+    },
 }
 
 #[cfg(feature = "anchor")]
@@ -85,6 +91,15 @@ impl FeeCurve {
             CurveSegment::Linear { a, b } => {
                 (lower + upper).mul_up(a / Fraction::from_integer(2)) + b
             }
+            CurveSegment::Kinked { a, b, kink } => {
+                let doubled_kink = kink * Fraction::from_integer(2);
+
+                if lower + upper > doubled_kink {
+                    (lower + upper - doubled_kink).mul_up(a / Fraction::from_integer(2)) + b
+                } else {
+                    b
+                }
+            }
         }
     }
 
@@ -95,6 +110,13 @@ impl FeeCurve {
             CurveSegment::None => Fraction::from_integer(0),
             CurveSegment::Constant { c } => c,
             CurveSegment::Linear { a, b } => a.mul_up(utilization) + b,
+            CurveSegment::Kinked { a, b, kink } => {
+                if utilization > kink {
+                    a.mul_up(utilization - kink) + b
+                } else {
+                    b
+                }
+            }
         })
     }
 
@@ -157,6 +179,64 @@ impl FeeCurve {
         self
     }
 
+    /// Builds a whole curve from `(segment, upper bound)` pairs.
+    /// Bounds have to be strictly increasing and the last one has to be equal to 1.
+    pub fn from_segments(segments: &[(CurveSegment, Fraction)]) -> Result<Self, LibErrors> {
+        if segments.is_empty() || segments.len() > MAX_FEES {
+            return Err(LibErrors::InvalidFeeCurve);
+        }
+
+        let mut curve = Self::default();
+        let mut previous_bound = Fraction::from_integer(0);
+
+        for (index, (segment, bound)) in segments.iter().enumerate() {
+            if *segment == CurveSegment::None
+                || (index > 0 && *bound <= previous_bound)
+                || *bound > Fraction::from_integer(1)
+            {
+                return Err(LibErrors::InvalidFeeCurve);
+            }
+
+            curve.add_segment(*segment, *bound);
+            previous_bound = *bound;
+        }
+
+        if previous_bound != Fraction::from_integer(1) {
+            return Err(LibErrors::InvalidFeeCurve);
+        }
+
+        Ok(curve)
+    }
+
+    /// Jump rate model, `slope` grows the fee from `base` up to the `kink` utilization
+    /// and `jump_slope` is used above it
+    pub fn jump_rate(
+        base: Fraction,
+        slope: Fraction,
+        kink: Fraction,
+        jump_slope: Fraction,
+    ) -> Result<Self, LibErrors> {
+        if kink == Fraction::from_integer(0) || kink >= Fraction::from_integer(1) {
+            return Err(LibErrors::InvalidFeeCurve);
+        }
+
+        Self::from_segments(&[
+            (CurveSegment::Linear { a: slope, b: base }, kink),
+            (
+                CurveSegment::Kinked {
+                    a: jump_slope,
+                    b: slope.mul_up(kink) + base,
+                    kink,
+                },
+                Fraction::from_integer(1),
+            ),
+        ])
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.used as usize >= MAX_FEES
+    }
+
     fn add_segment(&mut self, curve: CurveSegment, bound: Fraction) {
         self.bounds[self.used as usize] = bound;
         self.values[self.used as usize] = curve;
@@ -282,4 +362,134 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn from_segments() -> Result<(), LibErrors> {
+        let fee = FeeCurve::from_segments(&[
+            (
+                CurveSegment::Constant {
+                    c: Fraction::from_scale(1, 2),
+                },
+                Fraction::from_scale(5, 1),
+            ),
+            (
+                CurveSegment::Linear {
+                    a: Fraction::from_scale(1, 1),
+                    b: Fraction::from_scale(1, 2),
+                },
+                Fraction::from_integer(1),
+            ),
+        ])?;
+
+        assert_eq!(fee.used, 2);
+        assert_eq!(
+            fee.get_point_fee(Fraction::from_scale(2, 1))?,
+            Fraction::from_scale(1, 2)
+        );
+        assert_eq!(
+            fee.get_point_fee(Fraction::from_scale(6, 1))?,
+            Fraction::from_scale(7, 2)
+        );
+
+        let constant = CurveSegment::Constant {
+            c: Fraction::from_scale(1, 2),
+        };
+
+        // empty
+        assert_eq!(
+            FeeCurve::from_segments(&[]),
+            Err(LibErrors::InvalidFeeCurve)
+        );
+        // not ending at 1
+        assert_eq!(
+            FeeCurve::from_segments(&[(constant, Fraction::from_scale(5, 1))]),
+            Err(LibErrors::InvalidFeeCurve)
+        );
+        // not monotonic
+        assert_eq!(
+            FeeCurve::from_segments(&[
+                (constant, Fraction::from_scale(5, 1)),
+                (constant, Fraction::from_scale(5, 1)),
+                (constant, Fraction::from_integer(1)),
+            ]),
+            Err(LibErrors::InvalidFeeCurve)
+        );
+        // above 1
+        assert_eq!(
+            FeeCurve::from_segments(&[
+                (constant, Fraction::from_integer(1)),
+                (constant, Fraction::from_integer(2)),
+            ]),
+            Err(LibErrors::InvalidFeeCurve)
+        );
+        // empty segment
+        assert_eq!(
+            FeeCurve::from_segments(&[(CurveSegment::None, Fraction::from_integer(1))]),
+            Err(LibErrors::InvalidFeeCurve)
+        );
+        // too many segments
+        assert_eq!(
+            FeeCurve::from_segments(&[
+                (constant, Fraction::from_scale(1, 1)),
+                (constant, Fraction::from_scale(2, 1)),
+                (constant, Fraction::from_scale(3, 1)),
+                (constant, Fraction::from_scale(4, 1)),
+                (constant, Fraction::from_scale(5, 1)),
+                (constant, Fraction::from_integer(1)),
+            ]),
+            Err(LibErrors::InvalidFeeCurve)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn jump_rate() -> Result<(), LibErrors> {
+        // 1% base, 5% at 80% utilization and 4.05 at full utilization
+        let fee = FeeCurve::jump_rate(
+            Fraction::from_scale(1, 2),
+            Fraction::from_scale(5, 2),
+            Fraction::from_scale(8, 1),
+            Fraction::from_integer(20),
+        )?;
+
+        assert_eq!(
+            fee.get_point_fee(Fraction::from_integer(0))?,
+            Fraction::from_scale(1, 2)
+        );
+        assert_eq!(
+            fee.get_point_fee(Fraction::from_scale(8, 1))?,
+            Fraction::from_scale(5, 2)
+        );
+        assert_eq!(
+            fee.get_point_fee(Fraction::from_scale(9, 1))?,
+            Fraction::from_scale(205, 2)
+        );
+        assert_eq!(
+            fee.get_point_fee(Fraction::from_integer(1))?,
+            Fraction::from_scale(405, 2)
+        );
+
+        // mean across the kink
+        assert_eq!(
+            fee.get_mean(Fraction::from_scale(8, 1), Fraction::from_integer(1))?,
+            Fraction::from_scale(205, 2)
+        );
+        assert_eq!(
+            fee.get_mean(Fraction::from_scale(6, 1), Fraction::from_integer(1))?,
+            Fraction::from_scale(10475, 4)
+        );
+
+        assert_eq!(
+            FeeCurve::jump_rate(
+                Fraction::from_scale(1, 2),
+                Fraction::from_scale(5, 2),
+                Fraction::from_integer(1),
+                Fraction::from_integer(20),
+            ),
+            Err(LibErrors::InvalidFeeCurve)
+        );
+
+        Ok(())
+    }
 }
@@ -0,0 +1,146 @@
+use crate::core_lib::{
+    decimal::{Balances, Fraction, Quantity},
+    errors::LibErrors,
+    Token,
+};
+use checked_decimal_macro::Factories;
+use std::cmp::min;
+
+#[cfg(feature = "anchor")]
+mod zero {
+    use super::*;
+    use anchor_lang::prelude::*;
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct InsuranceFund {
+        /// Tokens of the fund, held in vault reserves
+        pub balance: Balances,
+        /// Part of trading fees paid into the fund
+        pub trade_fee_part: Fraction,
+        /// Part of the unlocked liquidity that strategies pay out of trader profit before the fund steps in
+        pub profit_threshold: Fraction,
+        /// Total amount ever paid into the fund
+        pub total_deposited: Balances,
+        /// Total amount of losses ever covered by the fund
+        pub total_covered: Balances,
+    }
+}
+
+#[cfg(not(feature = "anchor"))]
+mod non_zero {
+    use super::*;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct InsuranceFund {
+        /// Tokens of the fund, held in vault reserves
+        pub balance: Balances,
+        /// Part of trading fees paid into the fund
+        pub trade_fee_part: Fraction,
+        /// Part of the unlocked liquidity that strategies pay out of trader profit before the fund steps in
+        pub profit_threshold: Fraction,
+        /// Total amount ever paid into the fund
+        pub total_deposited: Balances,
+        /// Total amount of losses ever covered by the fund
+        pub total_covered: Balances,
+    }
+}
+
+#[cfg(feature = "anchor")]
+pub use zero::InsuranceFund;
+
+#[cfg(not(feature = "anchor"))]
+pub use non_zero::InsuranceFund;
+
+impl InsuranceFund {
+    pub fn set_params(
+        &mut self,
+        trade_fee_part: Fraction,
+        profit_threshold: Fraction,
+    ) -> Result<(), LibErrors> {
+        if trade_fee_part > Fraction::from_integer(1)
+            || profit_threshold > Fraction::from_integer(1)
+        {
+            return Err(LibErrors::InvalidInsuranceParams);
+        }
+
+        self.trade_fee_part = trade_fee_part;
+        self.profit_threshold = profit_threshold;
+
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, quantity: Balances) {
+        self.balance += quantity;
+        self.total_deposited += quantity;
+    }
+
+    /// Covers as much of the loss as the balance allows, returns the covered quantity
+    pub fn cover(&mut self, loss: Quantity, token: Token) -> Quantity {
+        let (balance, total) = match token {
+            Token::Base => (&mut self.balance.base, &mut self.total_covered.base),
+            Token::Quote => (&mut self.balance.quote, &mut self.total_covered.quote),
+        };
+
+        let covered = min(loss, *balance);
+        *balance -= covered;
+        *total += covered;
+
+        covered
+    }
+
+    /// Part of the trading fee paid into the fund
+    pub fn trade_fee_cut(&self, fee: Quantity) -> Quantity {
+        fee * self.trade_fee_part
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use checked_decimal_macro::Decimal;
+
+    #[test]
+    fn cover() {
+        let mut fund = InsuranceFund::default();
+
+        assert_eq!(
+            fund.set_params(Fraction::from_scale(11, 1), Fraction::new(0)),
+            Err(LibErrors::InvalidInsuranceParams)
+        );
+        assert_eq!(
+            fund.set_params(Fraction::from_scale(1, 1), Fraction::from_scale(5, 2)),
+            Ok(())
+        );
+        assert_eq!(fund.trade_fee_cut(Quantity::new(1000)), Quantity::new(100));
+
+        fund.deposit(Balances {
+            base: Quantity::new(500),
+            quote: Quantity::new(100),
+        });
+
+        assert_eq!(
+            fund.cover(Quantity::new(300), Token::Base),
+            Quantity::new(300)
+        );
+        assert_eq!(
+            fund.cover(Quantity::new(300), Token::Base),
+            Quantity::new(200)
+        );
+        assert_eq!(
+            fund.cover(Quantity::new(300), Token::Quote),
+            Quantity::new(100)
+        );
+
+        assert_eq!(fund.balance, Balances::default());
+        assert_eq!(
+            fund.total_covered,
+            Balances {
+                base: Quantity::new(500),
+                quote: Quantity::new(100),
+            }
+        );
+    }
+}
@@ -1,8 +1,16 @@
 pub mod fee_curve;
 pub mod fixed_vector_tests;
+pub mod insurance;
 pub mod oracle;
+pub mod outflow;
+pub mod pause;
 pub mod receipt;
+pub mod timelock;
 
 pub use fee_curve::FeeCurve;
+pub use insurance::InsuranceFund;
 pub use oracle::{Oracle, OraclePriceType};
+pub use outflow::{Outflow, OutflowLimits};
+pub use pause::{Operation, PauseFlags};
 pub use receipt::{Receipt, Side};
+pub use timelock::{ChangeKind, PendingChange, Timelock};
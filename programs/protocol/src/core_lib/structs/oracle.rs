@@ -48,37 +48,7 @@ mod zero {
             let OracleUpdate { price, conf, exp } =
                 self.get_update_from_acc(acc, current_timestamp)?;
 
-            let (price, confidence) = if exp < 0 {
-                (
-                    Price::from_scale(
-                        price,
-                        exp.abs().try_into().map_err(|_| LibErrors::ParseError)?,
-                    ),
-                    Price::from_scale(
-                        conf,
-                        exp.abs().try_into().map_err(|_| LibErrors::ParseError)?,
-                    ),
-                )
-            } else {
-                (
-                    Price::from_integer(price).big_div(Price::from_scale(
-                        1,
-                        exp.try_into().map_err(|_| LibErrors::ParseError)?,
-                    )),
-                    Price::from_integer(conf).big_div(Price::from_scale(
-                        1,
-                        exp.try_into().map_err(|_| LibErrors::ParseError)?,
-                    )),
-                )
-            };
-
-            self.update(
-                price,
-                confidence,
-                current_timestamp
-                    .try_into()
-                    .map_err(|_| LibErrors::ParseError)?,
-            )
+            self.update_scaled(price, conf, exp, current_timestamp)
         }
     }
 }
@@ -149,6 +119,47 @@ impl Oracle {
         Ok(())
     }
 
+    /// Updates the oracle with a price and confidence scaled by `10^exp`, as stored by price feeds.
+    pub fn update_scaled(
+        &mut self,
+        price: i64,
+        conf: u64,
+        exp: i32,
+        current_timestamp: i64,
+    ) -> Result<(), LibErrors> {
+        let (price, confidence) = if exp < 0 {
+            (
+                Price::from_scale(
+                    price,
+                    exp.abs().try_into().map_err(|_| LibErrors::ParseError)?,
+                ),
+                Price::from_scale(
+                    conf,
+                    exp.abs().try_into().map_err(|_| LibErrors::ParseError)?,
+                ),
+            )
+        } else {
+            (
+                Price::from_integer(price).big_div(Price::from_scale(
+                    1,
+                    exp.try_into().map_err(|_| LibErrors::ParseError)?,
+                )),
+                Price::from_integer(conf).big_div(Price::from_scale(
+                    1,
+                    exp.try_into().map_err(|_| LibErrors::ParseError)?,
+                )),
+            )
+        };
+
+        self.update(
+            price,
+            confidence,
+            current_timestamp
+                .try_into()
+                .map_err(|_| LibErrors::ParseError)?,
+        )
+    }
+
     // /// Checks if the oracle has been updated in the last `max_update_interval` seconds.
     // pub fn check_if_updated(&self, now: Time) -> Result<(), LibErrors> {
     //     if now - self.last_update <= self.max_update_interval {
@@ -0,0 +1,185 @@
+use crate::core_lib::{
+    decimal::{Time, Value},
+    errors::LibErrors,
+};
+use checked_decimal_macro::Decimal;
+
+#[cfg(feature = "anchor")]
+mod zero {
+    use super::*;
+    use anchor_lang::prelude::*;
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct Outflow {
+        /// Value that left within the window, decays linearly over it
+        pub used: Value,
+        pub last_update: Time,
+    }
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct OutflowLimits {
+        /// Outflows of the whole vault
+        pub vault: Outflow,
+        /// Maximal value leaving the vault within the window, zero for no limit
+        pub vault_limit: Value,
+        /// Maximal value leaving the vault to a single statement within the window, zero for no limit
+        pub statement_limit: Value,
+        /// Length of the rolling window, zero disables the limits
+        pub window: Time,
+    }
+}
+
+#[cfg(not(feature = "anchor"))]
+mod non_zero {
+    use super::*;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct Outflow {
+        /// Value that left within the window, decays linearly over it
+        pub used: Value,
+        pub last_update: Time,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct OutflowLimits {
+        /// Outflows of the whole vault
+        pub vault: Outflow,
+        /// Maximal value leaving the vault within the window, zero for no limit
+        pub vault_limit: Value,
+        /// Maximal value leaving the vault to a single statement within the window, zero for no limit
+        pub statement_limit: Value,
+        /// Length of the rolling window, zero disables the limits
+        pub window: Time,
+    }
+}
+
+#[cfg(feature = "anchor")]
+pub use zero::{Outflow, OutflowLimits};
+
+#[cfg(not(feature = "anchor"))]
+pub use non_zero::{Outflow, OutflowLimits};
+
+impl Outflow {
+    /// Used value left after the decay since the last update
+    pub fn current(&self, window: Time, now: Time) -> Value {
+        let elapsed = now.saturating_sub(self.last_update);
+
+        if elapsed >= window {
+            return Value::new(0);
+        }
+
+        Value::new(self.used.get() * (window - elapsed) as u128 / window as u128)
+    }
+
+    /// Room left under the limit
+    pub fn available(&self, window: Time, limit: Value, now: Time) -> Value {
+        let current = self.current(window, now);
+
+        match current < limit {
+            true => limit - current,
+            false => Value::new(0),
+        }
+    }
+
+    fn add(
+        &mut self,
+        value: Value,
+        window: Time,
+        limit: Value,
+        now: Time,
+        error: LibErrors,
+    ) -> Result<(), LibErrors> {
+        let used = self.current(window, now) + value;
+
+        if !limit.is_zero() && used > limit {
+            return Err(error);
+        }
+
+        self.used = used;
+        self.last_update = now;
+
+        Ok(())
+    }
+}
+
+impl OutflowLimits {
+    pub fn set(&mut self, window: Time, vault_limit: Value, statement_limit: Value) {
+        self.window = window;
+        self.vault_limit = vault_limit;
+        self.statement_limit = statement_limit;
+    }
+
+    /// Records value leaving the vault, fails if it goes over the vault or the statement limit
+    pub fn record(
+        &mut self,
+        statement: &mut Outflow,
+        value: Value,
+        now: Time,
+    ) -> Result<(), LibErrors> {
+        if self.window == 0 {
+            return Ok(());
+        }
+
+        self.vault.add(
+            value,
+            self.window,
+            self.vault_limit,
+            now,
+            LibErrors::VaultOutflowLimitExceeded,
+        )?;
+        statement.add(
+            value,
+            self.window,
+            self.statement_limit,
+            now,
+            LibErrors::StatementOutflowLimitExceeded,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use checked_decimal_macro::Factories;
+
+    #[test]
+    fn rolling_window() -> Result<(), LibErrors> {
+        let mut limits = OutflowLimits::default();
+        let (first, second) = (&mut Outflow::default(), &mut Outflow::default());
+
+        // disabled
+        limits.record(first, Value::from_integer(1000), 0)?;
+        assert_eq!(limits.vault, Outflow::default());
+
+        limits.set(100, Value::from_integer(150), Value::from_integer(100));
+
+        limits.record(first, Value::from_integer(80), 0)?;
+        assert_eq!(
+            limits.record(first, Value::from_integer(30), 10),
+            Err(LibErrors::StatementOutflowLimitExceeded)
+        );
+        assert_eq!(
+            limits.record(second, Value::from_integer(90), 10),
+            Err(LibErrors::VaultOutflowLimitExceeded)
+        );
+
+        // half of the window later, half of the usage is gone
+        limits.record(first, Value::from_integer(50), 50)?;
+        assert_eq!(first.used, Value::from_integer(90));
+        assert_eq!(
+            first.available(100, limits.statement_limit, 50),
+            Value::from_integer(10)
+        );
+
+        limits.record(second, Value::from_integer(100), 150)?;
+        assert_eq!(limits.vault.used, Value::from_integer(100));
+
+        Ok(())
+    }
+}
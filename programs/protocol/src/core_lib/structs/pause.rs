@@ -0,0 +1,101 @@
+use crate::core_lib::errors::LibErrors;
+
+/// Operations that can be stopped in an emergency, exits such as repay and close stay allowed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Operation {
+    Lend = 0,
+    Swap = 1,
+    Trade = 2,
+    Deposit = 3,
+    Withdraw = 4,
+}
+
+impl TryFrom<u8> for Operation {
+    type Error = LibErrors;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Lend),
+            1 => Ok(Self::Swap),
+            2 => Ok(Self::Trade),
+            3 => Ok(Self::Deposit),
+            4 => Ok(Self::Withdraw),
+            _ => Err(LibErrors::InvalidOperation),
+        }
+    }
+}
+
+#[cfg(feature = "anchor")]
+mod zero {
+    use anchor_lang::prelude::*;
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct PauseFlags {
+        /// Bit set for every paused `Operation`
+        pub flags: u8,
+    }
+}
+
+#[cfg(not(feature = "anchor"))]
+mod non_zero {
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct PauseFlags {
+        /// Bit set for every paused `Operation`
+        pub flags: u8,
+    }
+}
+
+#[cfg(feature = "anchor")]
+pub use zero::PauseFlags;
+
+#[cfg(not(feature = "anchor"))]
+pub use non_zero::PauseFlags;
+
+impl PauseFlags {
+    pub fn is_paused(&self, operation: Operation) -> bool {
+        self.flags & (1 << operation as u8) != 0
+    }
+
+    pub fn set(&mut self, operation: Operation, paused: bool) {
+        match paused {
+            true => self.flags |= 1 << operation as u8,
+            false => self.flags &= !(1 << operation as u8),
+        }
+    }
+
+    pub fn check(&self, operation: Operation) -> Result<(), LibErrors> {
+        match self.is_paused(operation) {
+            true => Err(LibErrors::OperationPaused),
+            false => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pause() {
+        let mut pause = PauseFlags::default();
+
+        pause.set(Operation::Swap, true);
+        pause.set(Operation::Withdraw, true);
+        assert_eq!(
+            pause.check(Operation::Swap),
+            Err(LibErrors::OperationPaused)
+        );
+        assert_eq!(pause.check(Operation::Lend), Ok(()));
+        assert!(pause.is_paused(Operation::Withdraw));
+
+        pause.set(Operation::Swap, false);
+        assert_eq!(pause.check(Operation::Swap), Ok(()));
+        assert_eq!(pause.flags, 1 << 4);
+
+        assert_eq!(Operation::try_from(5), Err(LibErrors::InvalidOperation));
+    }
+}
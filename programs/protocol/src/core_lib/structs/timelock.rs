@@ -0,0 +1,277 @@
+use crate::core_lib::{
+    decimal::Time,
+    errors::LibErrors,
+    structs::{fee_curve::HOUR_DURATION, FeeCurve},
+};
+
+pub const TIMELOCK_SIZE: usize = 8;
+/// Changes never execute sooner than this, whatever the configured delay
+pub const MIN_TIMELOCK_DELAY: Time = HOUR_DURATION;
+
+/// Parameter changes that have to wait out the delay, meaning of `values` is given for each kind
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum ChangeKind {
+    /// service, base, bound, b, c - appends a segment to the fee curve
+    #[default]
+    FeeCurveSegment = 0,
+    /// service, base, base fee, slope, kink, jump slope - replaces the fee curve
+    JumpRateCurve = 1,
+    /// open fee, max leverage, collateral ratio, liquidation threshold
+    TradingParams = 2,
+    /// collateral ratio, liquidation threshold of the strategy
+    StrategyRiskParams = 3,
+    /// enabled, debt ceiling
+    DebtCeiling = 4,
+    /// delay of the timelock itself, not tied to a vault
+    Delay = 5,
+    /// service, base - replaces the fee curve with the one carried by the change
+    FeeCurve = 6,
+    /// target, factor of the swap inventory skew
+    InventorySkew = 7,
+    /// lending, swapping, trading flags of the strategy
+    StrategyServices = 8,
+}
+
+impl TryFrom<u8> for ChangeKind {
+    type Error = LibErrors;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::FeeCurveSegment),
+            1 => Ok(Self::JumpRateCurve),
+            2 => Ok(Self::TradingParams),
+            3 => Ok(Self::StrategyRiskParams),
+            4 => Ok(Self::DebtCeiling),
+            5 => Ok(Self::Delay),
+            6 => Ok(Self::FeeCurve),
+            7 => Ok(Self::InventorySkew),
+            8 => Ok(Self::StrategyServices),
+            _ => Err(LibErrors::InvalidChangeKind),
+        }
+    }
+}
+
+#[cfg(feature = "anchor")]
+mod zero {
+    use super::*;
+    use anchor_lang::prelude::*;
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct PendingChange {
+        /// Identifier of the change, zero marks an empty slot
+        pub id: u64,
+        /// Raw arguments of the change
+        pub values: [u64; 6],
+        /// New fee curve, only used by `FeeCurve` changes
+        pub curve: FeeCurve,
+        /// Time after which the change can be executed
+        pub eta: Time,
+        pub vault: u8,
+        pub strategy: u8,
+        pub kind: ChangeKind,
+    }
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct Timelock {
+        /// Identifier of the last scheduled change
+        pub last_id: u64,
+        pub changes: [PendingChange; TIMELOCK_SIZE],
+        /// Time between scheduling a change and being able to execute it, never below `MIN_TIMELOCK_DELAY`
+        pub delay: Time,
+    }
+}
+
+#[cfg(not(feature = "anchor"))]
+mod non_zero {
+    use super::*;
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct PendingChange {
+        /// Identifier of the change, zero marks an empty slot
+        pub id: u64,
+        /// Raw arguments of the change
+        pub values: [u64; 6],
+        /// New fee curve, only used by `FeeCurve` changes
+        pub curve: FeeCurve,
+        /// Time after which the change can be executed
+        pub eta: Time,
+        pub vault: u8,
+        pub strategy: u8,
+        pub kind: ChangeKind,
+    }
+
+    #[repr(C)]
+    #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+    pub struct Timelock {
+        /// Identifier of the last scheduled change
+        pub last_id: u64,
+        pub changes: [PendingChange; TIMELOCK_SIZE],
+        /// Time between scheduling a change and being able to execute it, never below `MIN_TIMELOCK_DELAY`
+        pub delay: Time,
+    }
+}
+
+#[cfg(feature = "anchor")]
+pub use zero::{PendingChange, Timelock};
+
+#[cfg(not(feature = "anchor"))]
+pub use non_zero::{PendingChange, Timelock};
+
+impl Timelock {
+    pub fn new() -> Self {
+        Self {
+            delay: MIN_TIMELOCK_DELAY,
+            ..Default::default()
+        }
+    }
+
+    /// Queues the change to be executable after the delay, returns its id
+    pub fn schedule(
+        &mut self,
+        kind: ChangeKind,
+        vault: u8,
+        strategy: u8,
+        values: [u64; 6],
+        now: Time,
+    ) -> Result<u64, LibErrors> {
+        self.schedule_with_curve(kind, vault, strategy, values, FeeCurve::default(), now)
+    }
+
+    /// Queues the change together with a fee curve, returns its id
+    pub fn schedule_with_curve(
+        &mut self,
+        kind: ChangeKind,
+        vault: u8,
+        strategy: u8,
+        values: [u64; 6],
+        curve: FeeCurve,
+        now: Time,
+    ) -> Result<u64, LibErrors> {
+        if kind == ChangeKind::Delay && (values[0] as Time) < MIN_TIMELOCK_DELAY {
+            return Err(LibErrors::TimelockDelayTooShort);
+        }
+
+        let slot = self
+            .changes
+            .iter_mut()
+            .find(|change| change.id == 0)
+            .ok_or(LibErrors::TimelockFull)?;
+
+        self.last_id += 1;
+        *slot = PendingChange {
+            id: self.last_id,
+            values,
+            curve,
+            eta: now.saturating_add(self.delay.max(MIN_TIMELOCK_DELAY)),
+            vault,
+            strategy,
+            kind,
+        };
+
+        Ok(self.last_id)
+    }
+
+    pub fn set_delay(&mut self, delay: Time) -> Result<(), LibErrors> {
+        if delay < MIN_TIMELOCK_DELAY {
+            return Err(LibErrors::TimelockDelayTooShort);
+        }
+
+        self.delay = delay;
+        Ok(())
+    }
+
+    pub fn get(&self, id: u64) -> Result<&PendingChange, LibErrors> {
+        self.changes
+            .iter()
+            .find(|change| id != 0 && change.id == id)
+            .ok_or(LibErrors::ChangeNotFound)
+    }
+
+    /// Removes the change from the queue if its delay passed
+    pub fn take(&mut self, id: u64, now: Time) -> Result<PendingChange, LibErrors> {
+        if self.get(id)?.eta > now {
+            return Err(LibErrors::ChangeNotReady);
+        }
+
+        self.cancel(id)
+    }
+
+    /// Removes the change from the queue regardless of its delay
+    pub fn cancel(&mut self, id: u64) -> Result<PendingChange, LibErrors> {
+        let change = self
+            .changes
+            .iter_mut()
+            .find(|change| id != 0 && change.id == id)
+            .ok_or(LibErrors::ChangeNotFound)?;
+
+        let taken = *change;
+        *change = PendingChange::default();
+
+        Ok(taken)
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &PendingChange> {
+        self.changes.iter().filter(|change| change.id != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timelock() -> Result<(), LibErrors> {
+        let mut timelock = Timelock {
+            delay: 100,
+            ..Default::default()
+        };
+
+        // delay below the minimum is never used
+        let id = timelock.schedule(ChangeKind::DebtCeiling, 1, 0, [1, 500, 0, 0, 0, 0], 1000)?;
+        assert_eq!(timelock.get(id)?.eta, 1000 + MIN_TIMELOCK_DELAY);
+        assert_eq!(
+            timelock.take(id, 999 + MIN_TIMELOCK_DELAY),
+            Err(LibErrors::ChangeNotReady)
+        );
+
+        let change = timelock.take(id, 1000 + MIN_TIMELOCK_DELAY)?;
+        assert_eq!(change.kind, ChangeKind::DebtCeiling);
+        assert_eq!(change.values[1], 500);
+        assert_eq!(
+            timelock.take(id, 1000 + MIN_TIMELOCK_DELAY),
+            Err(LibErrors::ChangeNotFound)
+        );
+
+        assert_eq!(
+            timelock.schedule(ChangeKind::Delay, 0, 0, [50, 0, 0, 0, 0, 0], 1000),
+            Err(LibErrors::TimelockDelayTooShort)
+        );
+        assert_eq!(
+            timelock.set_delay(50),
+            Err(LibErrors::TimelockDelayTooShort)
+        );
+
+        let id = timelock.schedule(ChangeKind::Delay, 0, 0, [7200, 0, 0, 0, 0, 0], 1000)?;
+        assert_eq!(id, 2);
+        assert_eq!(timelock.pending().count(), 1);
+        timelock.cancel(id)?;
+        assert_eq!(timelock.pending().count(), 0);
+        assert_eq!(timelock.cancel(0), Err(LibErrors::ChangeNotFound));
+
+        for _ in 0..TIMELOCK_SIZE {
+            timelock.schedule(ChangeKind::DebtCeiling, 0, 0, [0; 6], 0)?;
+        }
+        assert_eq!(
+            timelock.schedule(ChangeKind::DebtCeiling, 0, 0, [0; 6], 0),
+            Err(LibErrors::TimelockFull)
+        );
+
+        Ok(())
+    }
+}
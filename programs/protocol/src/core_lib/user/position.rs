@@ -96,6 +96,16 @@ impl Position {
         }
     }
 
+    /// whether nothing is owed by or to the owner anymore
+    pub fn is_settled(&self) -> bool {
+        match self {
+            Position::LiquidityProvide { shares, .. } => shares.is_zero(),
+            Position::Borrow { shares, amount, .. } => shares.is_zero() && amount.is_zero(),
+            Position::Trading { .. } => false,
+            Position::Empty => true,
+        }
+    }
+
     fn shares_mut(&mut self) -> &mut Shares {
         match self {
             Position::Borrow { shares, .. } => shares,
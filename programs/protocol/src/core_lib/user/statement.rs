@@ -1,11 +1,12 @@
-use crate::core_lib::{decimal::Fraction, errors::LibErrors};
+use crate::core_lib::{decimal::Fraction, errors::LibErrors, structs::Outflow, vault::MAX_VAULTS};
 
 use super::{utils::CollateralValues, *};
 
 use checked_decimal_macro::Decimal;
 use checked_decimal_macro::{num_traits::ToPrimitive, BigOps};
 use std::{
-    collections::HashSet,
+    cmp::min,
+    collections::{BTreeMap, HashSet},
     ops::Range,
     slice::{Iter, IterMut},
 };
@@ -30,6 +31,8 @@ mod zero {
     pub struct UserTemporaryValues {
         pub collateral: CollateralValues,
         pub liabilities: Value,
+        /// collateral value of isolated vaults above their debt ceilings
+        pub isolated_excess: Value,
         // pub trades: Trades,
     }
 
@@ -39,6 +42,10 @@ mod zero {
     pub struct UserStatement {
         pub positions: Positions,
         pub values: UserTemporaryValues,
+        /// value withdrawn from each vault within its outflow window, indexed by vault id
+        pub outflows: [Outflow; MAX_VAULTS],
+        /// debt value backed by collateral of each isolated vault, indexed by vault id
+        pub isolated_debt: [Value; MAX_VAULTS],
     }
 }
 
@@ -58,6 +65,8 @@ mod non_zero {
     pub struct UserTemporaryValues {
         pub collateral: CollateralValues,
         pub liabilities: Value,
+        /// collateral value of isolated vaults above their debt ceilings
+        pub isolated_excess: Value,
         // pub trades: Trades,
     }
 
@@ -66,6 +75,10 @@ mod non_zero {
     pub struct UserStatement {
         pub positions: Positions,
         pub values: UserTemporaryValues,
+        /// value withdrawn from each vault within its outflow window, indexed by vault id
+        pub outflows: [Outflow; MAX_VAULTS],
+        /// debt value backed by collateral of each isolated vault, indexed by vault id
+        pub isolated_debt: [Value; MAX_VAULTS],
     }
 }
 
@@ -121,6 +134,13 @@ impl UserStatement {
             .ok_or(LibErrors::PositionNotFound)?)
     }
 
+    /// Outflow of the statement from the vault with the given id
+    pub fn outflow_mut(&mut self, vault: u8) -> Result<&mut Outflow, LibErrors> {
+        self.outflows
+            .get_mut(vault as usize)
+            .ok_or(LibErrors::NoVaultOnIndex)
+    }
+
     pub fn delete_position(&mut self, id: usize) {
         self.positions.delete(id)
     }
@@ -129,11 +149,43 @@ impl UserStatement {
         self.values.collateral.with_collateral_ratio >= self.values.liabilities
     }
 
+    /// liabilities exceed the collateral even when valued 1:1
+    pub fn insolvent(&self) -> bool {
+        self.values.liabilities > self.values.collateral.exact
+    }
+
+    /// whether every position is empty or settled, so the statement can be closed
+    pub fn is_settled(&self) -> bool {
+        match self.positions.iter() {
+            Some(mut iter) => iter.all(Position::is_settled),
+            None => true,
+        }
+    }
+
+    /// whether any liquidity or trade could still be used to cover the debt
+    pub fn has_collateral(&self) -> bool {
+        match self.positions.iter() {
+            Some(mut iter) => iter.any(|pos| !pos.is_liability()),
+            None => false,
+        }
+    }
+
     /// calculate value that user can borrow
     pub fn permitted_debt(&self) -> Value {
         self.values.collateral.with_collateral_ratio - self.values.liabilities
     }
 
+    /// calculate value that user can borrow, isolated collateral is capped at its debt ceiling
+    pub fn permitted_borrow(&self) -> Value {
+        let permitted_debt = self.permitted_debt();
+
+        if self.values.isolated_excess >= permitted_debt {
+            Value::new(0)
+        } else {
+            permitted_debt - self.values.isolated_excess
+        }
+    }
+
     pub fn permitted_withdraw(&self, collateral_ratio: Fraction) -> Value {
         self.permitted_debt().big_div(collateral_ratio)
     }
@@ -149,7 +201,8 @@ impl UserStatement {
         }
     }
 
-    /// Vault's oracles should be refreshed before using this function
+    /// Vault's oracles should be refreshed before using this function.
+    /// Tokenized shares are not part of the statement, so they are never counted as collateral
     fn collaterals_values(&self, vaults: &[Vault]) -> Result<CollateralValues, LibErrors> {
         if let Some(iter) = self.positions.iter() {
             iter.filter(|&pos| pos.is_collateral())
@@ -161,6 +214,131 @@ impl UserStatement {
         }
     }
 
+    /// Collateral value of LP positions in isolated vaults, by vault id
+    fn isolated_backing(&self, vaults: &[Vault]) -> Result<BTreeMap<u8, Value>, LibErrors> {
+        let mut backing = BTreeMap::new();
+
+        if let Some(iter) = self.positions.iter() {
+            for position in iter.filter(|&pos| pos.is_collateral()) {
+                let vault_index = *position.vault_index();
+
+                if vaults[vault_index as usize].debt_ceiling().is_none() {
+                    continue;
+                }
+
+                let with_collateral_ratio =
+                    position.collateral_values(vaults)?.with_collateral_ratio;
+                *backing.entry(vault_index).or_insert(Value::new(0)) += with_collateral_ratio;
+            }
+        }
+
+        Ok(backing)
+    }
+
+    /// Collateral value of LP positions in isolated vaults above what their debt ceilings
+    /// still let them back, debt already backed by the statement counts as room
+    fn isolated_excess(&self, vaults: &[Vault]) -> Result<Value, LibErrors> {
+        Ok(self.isolated_backing(vaults)?.iter().fold(
+            Value::new(0),
+            |excess, (vault_index, backing_value)| {
+                let room = vaults[*vault_index as usize]
+                    .isolated_room()
+                    .unwrap_or_default()
+                    + self.isolated_debt[*vault_index as usize];
+
+                if *backing_value > room {
+                    excess + (*backing_value - room)
+                } else {
+                    excess
+                }
+            },
+        ))
+    }
+
+    /// Part of the liabilities that only isolated collateral can back, other collateral is used first
+    fn needed_isolated(&self, backing: &BTreeMap<u8, Value>) -> Value {
+        let isolated = backing
+            .values()
+            .fold(Value::new(0), |sum, value| sum + *value);
+        let other = match self.values.collateral.with_collateral_ratio > isolated {
+            true => self.values.collateral.with_collateral_ratio - isolated,
+            false => Value::new(0),
+        };
+
+        match self.values.liabilities > other {
+            true => self.values.liabilities - other,
+            false => Value::new(0),
+        }
+    }
+
+    fn attributed_isolated(&self) -> Value {
+        self.isolated_debt
+            .iter()
+            .fold(Value::new(0), |sum, value| sum + *value)
+    }
+
+    /// Charges debt that needs isolated collateral to the debt ceilings of isolated vaults,
+    /// should be called after borrowing
+    pub fn back_isolated_debt(&mut self, vaults: &mut [Vault]) -> Result<(), LibErrors> {
+        self.refresh(vaults)?;
+
+        let backing = self.isolated_backing(vaults)?;
+        let needed = self.needed_isolated(&backing);
+        let attributed = self.attributed_isolated();
+
+        if needed <= attributed {
+            return Ok(());
+        }
+
+        let mut missing = needed - attributed;
+
+        for (vault_index, backing_value) in backing {
+            let index = vault_index as usize;
+            let unused = match backing_value > self.isolated_debt[index] {
+                true => backing_value - self.isolated_debt[index],
+                false => Value::new(0),
+            };
+            let room = vaults[index].isolated_room().unwrap_or_default();
+            let charged = min(missing, min(unused, room));
+
+            self.isolated_debt[index] += charged;
+            vaults[index].isolated_debt += charged;
+            missing -= charged;
+        }
+
+        match missing.is_zero() {
+            true => Ok(()),
+            false => Err(LibErrors::DebtCeilingExceeded),
+        }
+    }
+
+    /// Frees debt ceilings of isolated vaults no longer needed to back the debt,
+    /// should be called after repaying
+    pub fn release_isolated_debt(&mut self, vaults: &mut [Vault]) -> Result<(), LibErrors> {
+        self.refresh(vaults)?;
+
+        let needed = self.needed_isolated(&self.isolated_backing(vaults)?);
+        let attributed = self.attributed_isolated();
+
+        if attributed <= needed {
+            return Ok(());
+        }
+
+        let mut released = attributed - needed;
+
+        for (index, debt) in self.isolated_debt.iter_mut().enumerate() {
+            let freed = min(released, *debt);
+
+            if let Some(vault) = vaults.get_mut(index) {
+                vault.isolated_debt -= min(freed, vault.isolated_debt);
+            }
+            *debt -= freed;
+            released -= freed;
+        }
+
+        Ok(())
+    }
+
     fn trades_values(&self, vaults: &[Vault]) -> Result<(Value, CollateralValues), LibErrors> {
         if let Some(iter) = self.positions.iter() {
             iter.filter(|&pos| pos.is_trade()).fold(
@@ -183,6 +361,7 @@ impl UserStatement {
 
         self.values.liabilities = self.liabilities_value(vaults)? + loss;
         self.values.collateral = self.collaterals_values(vaults)? + profit;
+        self.values.isolated_excess = self.isolated_excess(vaults)?;
 
         Ok(())
     }
@@ -200,6 +379,31 @@ mod position_management {
 
     use super::*;
 
+    #[test]
+    fn settled_positions() -> Result<(), LibErrors> {
+        let mut user_statement = UserStatement::default();
+        assert!(user_statement.is_settled());
+
+        user_statement.add_position(Position::Empty)?;
+        user_statement.add_position(Position::LiquidityProvide {
+            vault_index: 0,
+            strategy_index: 0,
+            shares: Shares::new(0),
+            amount: Quantity::new(0),
+            quote_amount: Quantity::new(0),
+        })?;
+        assert!(user_statement.is_settled());
+
+        user_statement.add_position(Position::Borrow {
+            vault_index: 0,
+            shares: Shares::new(10),
+            amount: Quantity::new(10),
+        })?;
+        assert!(!user_statement.is_settled());
+
+        Ok(())
+    }
+
     #[test]
     fn default_positions() -> Result<(), LibErrors> {
         let mut user_statement = UserStatement::default();
@@ -575,4 +779,94 @@ mod position_management {
 
         Ok(())
     }
+
+    #[test]
+    fn isolated_debt_ceiling() -> Result<(), LibErrors> {
+        let mut user_statement = UserStatement::default();
+        let mut vaults = [Vault::new_vault_for_tests()?, Vault::new_vault_for_tests()?];
+        vaults[1].id = 1;
+
+        vaults[0].set_debt_ceiling(Some(Value::from_integer(10)));
+
+        vaults[0].deposit(&mut user_statement, Token::Base, Quantity::new(10000000), 2)?;
+        vaults[1].deposit(&mut user_statement, Token::Base, Quantity::new(2000000), 2)?;
+
+        user_statement.refresh(&vaults)?;
+
+        assert_eq!(user_statement.permitted_debt(), Value::from_integer(48));
+        assert_eq!(
+            user_statement.values.isolated_excess,
+            Value::from_integer(30)
+        );
+        assert_eq!(user_statement.permitted_borrow(), Value::from_integer(18));
+
+        // fits permitted debt, but not with isolated collateral capped
+        assert_eq!(
+            vaults[0].borrow(&mut user_statement, Quantity::new(9500000)),
+            Err(LibErrors::DebtCeilingExceeded)
+        );
+
+        vaults[0].borrow(&mut user_statement, Quantity::new(8000000))?;
+        user_statement.back_isolated_debt(&mut vaults)?;
+
+        // other collateral backs 8 of 16, the rest counts against the ceiling
+        assert_eq!(vaults[0].isolated_debt, Value::from_integer(8));
+        assert_eq!(user_statement.permitted_borrow(), Value::from_integer(2));
+        assert_eq!(
+            vaults[0].borrow(&mut user_statement, Quantity::new(1500000)),
+            Err(LibErrors::DebtCeilingExceeded)
+        );
+
+        // withdrawing the other collateral would leave debt above the ceiling to isolated shares,
+        // a failed instruction reverts, so it runs on copies
+        let (mut failed, mut statement) = (vaults, user_statement);
+        failed[1].withdraw(&mut statement, Token::Base, Quantity::new(2000000), 2)?;
+        statement.refresh(&failed)?;
+
+        assert!(statement.collateralized());
+        assert_eq!(
+            statement.back_isolated_debt(&mut failed),
+            Err(LibErrors::DebtCeilingExceeded)
+        );
+
+        // the ceiling is shared, another statement only gets what is left of it
+        let mut other_statement = UserStatement::default();
+        vaults[0].deposit(
+            &mut other_statement,
+            Token::Base,
+            Quantity::new(10000000),
+            2,
+        )?;
+        other_statement.refresh(&vaults)?;
+
+        assert_eq!(other_statement.permitted_borrow(), Value::from_integer(2));
+        assert_eq!(
+            vaults[0].borrow(&mut other_statement, Quantity::new(2500000)),
+            Err(LibErrors::DebtCeilingExceeded)
+        );
+
+        vaults[0].borrow(&mut other_statement, Quantity::new(1000000))?;
+        other_statement.back_isolated_debt(&mut vaults)?;
+        assert_eq!(vaults[0].isolated_debt, Value::from_integer(10));
+
+        // repaying frees the ceiling for others
+        vaults[0].repay(&mut user_statement, Quantity::new(2000000))?;
+        user_statement.release_isolated_debt(&mut vaults)?;
+
+        assert_eq!(vaults[0].isolated_debt, Value::from_integer(6));
+        assert_eq!(user_statement.isolated_debt[0], Value::from_integer(4));
+        other_statement.refresh(&vaults)?;
+        assert_eq!(other_statement.permitted_borrow(), Value::from_integer(4));
+
+        vaults[0].set_debt_ceiling(None);
+        user_statement.refresh(&vaults)?;
+
+        assert_eq!(
+            user_statement.permitted_borrow(),
+            user_statement.permitted_debt()
+        );
+        vaults[0].borrow(&mut user_statement, Quantity::new(1500000))?;
+
+        Ok(())
+    }
 }
@@ -1,6 +1,6 @@
 use super::*;
 use crate::core_lib::{
-    decimal::BothQuantities,
+    decimal::Balances,
     user::{Position, UserStatement},
 };
 use checked_decimal_macro::Decimal;
@@ -13,6 +13,51 @@ pub enum Token {
     Quote,
 }
 
+/// Quantities and shares moved by a deposit or a withdrawal of liquidity
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct LiquidityChange {
+    pub base: Quantity,
+    pub quote: Quantity,
+    pub shares: Shares,
+}
+
+impl LiquidityChange {
+    /// Quantity of the token opposite to the given one
+    pub fn other(&self, token: Token) -> Quantity {
+        match token {
+            Token::Base => self.quote,
+            Token::Quote => self.base,
+        }
+    }
+}
+
+/// Result of a single sided deposit, input is the deposited token and other is the opposite one
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ZapDeposit {
+    /// Part of the input swapped for the other token
+    pub swapped: Quantity,
+    /// Quantity of the other token received from the swap
+    pub received: Quantity,
+    /// Quantity of the input token deposited into the strategy
+    pub deposited: Quantity,
+    /// Quantity of the other token deposited into the strategy
+    pub deposited_other: Quantity,
+    /// Shares of the strategy received for the deposit
+    pub shares: Shares,
+}
+
+impl ZapDeposit {
+    /// Total quantity of the input token taken from the user
+    pub fn taken(&self) -> Quantity {
+        self.swapped + self.deposited
+    }
+
+    /// Quantity of the other token left after the deposit, returned to the user
+    pub fn refund(&self) -> Quantity {
+        self.received - self.deposited_other
+    }
+}
+
 impl Vault {
     pub fn other_quantity_in_ratio(
         &self,
@@ -69,11 +114,15 @@ impl Vault {
         withdraw_token: Token,
         amount: Quantity,
         strategy_index: u8,
-    ) -> Result<BothQuantities, LibErrors> {
+    ) -> Result<LiquidityChange, LibErrors> {
         if amount == Quantity::new(0) {
             return Err(LibErrors::ZeroAmountInput);
         }
 
+        if !self.strategy(strategy_index)?.accepts_withdrawals() {
+            return Err(LibErrors::StrategyClosed);
+        }
+
         let position_temp = Position::LiquidityProvide {
             vault_index: self.id,
             strategy_index,
@@ -89,8 +138,10 @@ impl Vault {
             Token::Base => strategy.balance(),
             Token::Quote => strategy.balance_quote(),
         };
-        let base_available = strategy.available();
-        let quote_available = strategy.available_quote();
+        let Balances {
+            base: base_available,
+            quote: quote_available,
+        } = strategy.withdrawable();
 
         let shares = min(
             strategy.total_shares().get_change_up(amount, balance),
@@ -117,19 +168,143 @@ impl Vault {
             user_statement.delete_position(id)
         }
 
-        Ok(BothQuantities {
+        Ok(LiquidityChange {
             base: base_quantity,
             quote: quote_quantity,
+            shares,
         })
     }
 
+    /// Withdraws liquidity entirely in one token, the other leg is swapped through the swap service.
+    /// Value of the withdrawal is bounded by what the statement permits,
+    /// returns total quantity out and the withdrawn liquidity
+    pub fn withdraw_single(
+        &mut self,
+        user_statement: &mut UserStatement,
+        withdraw_token: Token,
+        amount: Quantity,
+        strategy_index: u8,
+        min_expected: Quantity,
+    ) -> Result<(Quantity, LiquidityChange), LibErrors> {
+        let strategy = self.strategy(strategy_index)?;
+        let collateral_ratio = strategy.collateral_ratio();
+        let other = self.other_quantity_in_ratio(amount, withdraw_token, strategy);
+
+        if !collateral_ratio.is_zero() {
+            let (base, quote) = match withdraw_token {
+                Token::Base => (amount, other),
+                Token::Quote => (other, amount),
+            };
+            let value =
+                self.oracle()?.calculate_value(base) + self.quote_oracle()?.calculate_value(quote);
+
+            if value > user_statement.permitted_withdraw(collateral_ratio) {
+                return Err(LibErrors::UserNotCollateralized);
+            }
+        }
+
+        let change = self.withdraw(user_statement, withdraw_token, amount, strategy_index)?;
+        let LiquidityChange { base, quote, .. } = change;
+
+        let total = match withdraw_token {
+            Token::Base if !quote.is_zero() => base + self.buy(quote)?,
+            Token::Quote if !base.is_zero() => quote + self.sell(base)?,
+            Token::Base => base,
+            Token::Quote => quote,
+        };
+
+        if total < min_expected {
+            return Err(LibErrors::NoMinAmountOut);
+        }
+
+        Ok((total, change))
+    }
+
+    /// Moves shares out of the statement so they can be tokenized, they stop counting as collateral
+    pub fn wrap_shares(
+        &mut self,
+        user_statement: &mut UserStatement,
+        strategy_index: u8,
+        shares: Shares,
+    ) -> Result<(), LibErrors> {
+        if shares.is_zero() {
+            return Err(LibErrors::ZeroAmountInput);
+        }
+
+        let position_temp = Position::LiquidityProvide {
+            vault_index: self.id,
+            strategy_index,
+            shares: Shares::new(0),
+            amount: Quantity::new(0),
+            quote_amount: Quantity::new(0),
+        };
+
+        let strategy = self.strategy(strategy_index)?;
+        let (id, position) = user_statement.search_mut_id(&position_temp)?;
+
+        if shares > *position.shares() {
+            return Err(LibErrors::NotEnoughShares);
+        }
+
+        if shares.lt(position.shares()) {
+            let (base_quantity, quote_quantity) = strategy.get_earned_double(&shares);
+
+            position.decrease_amount(min(*position.amount(), base_quantity));
+            position.decrease_quote_amount(min(*position.quote_amount(), quote_quantity));
+            position.decrease_shares(shares);
+        } else {
+            user_statement.delete_position(id)
+        }
+
+        Ok(())
+    }
+
+    /// Moves tokenized shares back into the statement
+    pub fn unwrap_shares(
+        &mut self,
+        user_statement: &mut UserStatement,
+        strategy_index: u8,
+        shares: Shares,
+    ) -> Result<(), LibErrors> {
+        if shares.is_zero() {
+            return Err(LibErrors::ZeroAmountInput);
+        }
+
+        let strategy = self.strategy(strategy_index)?;
+
+        if shares > strategy.total_shares() {
+            return Err(LibErrors::NotEnoughShares);
+        }
+
+        let (base_quantity, quote_quantity) = strategy.get_earned_double(&shares);
+
+        let temp_position = Position::LiquidityProvide {
+            vault_index: self.id,
+            strategy_index,
+            shares,
+            amount: base_quantity,
+            quote_amount: quote_quantity,
+        };
+
+        match user_statement.search_mut(&temp_position) {
+            Ok(position) => {
+                position.increase_amount(base_quantity);
+                position.increase_quote_amount(quote_quantity);
+                position.increase_shares(shares);
+            }
+            Err(..) => user_statement.add_position(temp_position)?,
+        }
+
+        Ok(())
+    }
+
     pub fn deposit(
         &mut self,
         user_statement: &mut UserStatement,
         deposit_token: Token,
         amount: Quantity,
         strategy_index: u8,
-    ) -> Result<Quantity, LibErrors> {
+    ) -> Result<LiquidityChange, LibErrors> {
         if amount == Quantity::new(0) {
             return Err(LibErrors::ZeroAmountInput);
         }
@@ -138,6 +313,10 @@ impl Vault {
         let quote_oracle = self.quote_oracle()?;
 
         let strategy = self.strategy(strategy_index)?;
+        if !strategy.accepts_deposits() {
+            return Err(LibErrors::StrategyClosed);
+        }
+
         // get other quantity in ratio based on base token and quote token balances and given input,
         // if neither of them are greaten than zero returns 0 as marker for further calculations
         let opposite_quantity = self.other_quantity_in_ratio(amount, deposit_token, strategy);
@@ -189,18 +368,109 @@ impl Vault {
             Err(..) => user_statement.add_position(temp_position)?,
         }
 
-        if deposit_token == Token::Base {
-            Ok(quote_quantity)
+        Ok(LiquidityChange {
+            base: base_quantity,
+            quote: quote_quantity,
+            shares,
+        })
+    }
+    /// Quantity of the other token needed to deposit `amount` of the given token
+    fn needed_for_deposit(
+        &self,
+        amount: Quantity,
+        token: Token,
+        strategy_index: u8,
+    ) -> Result<Quantity, LibErrors> {
+        let base_oracle = self.oracle()?;
+        let quote_oracle = self.quote_oracle()?;
+
+        let strategy = self.strategy(strategy_index)?;
+        let opposite_quantity = self.other_quantity_in_ratio(amount, token, strategy);
+
+        let (_, needed) = match token {
+            Token::Base => {
+                self.get_opposite_quantity(quote_oracle, opposite_quantity, base_oracle, amount)
+            }
+            Token::Quote => {
+                self.get_opposite_quantity(base_oracle, opposite_quantity, quote_oracle, amount)
+            }
+        };
+
+        Ok(needed)
+    }
+
+    /// Deposits a single token, swapping the part of it needed to match the ratio of the strategy.
+    /// Leftover of the swapped token is reported in the result for the user to receive back,
+    /// fails if fewer than `min_shares` are minted
+    pub fn zap_deposit(
+        &mut self,
+        user_statement: &mut UserStatement,
+        deposit_token: Token,
+        amount: Quantity,
+        strategy_index: u8,
+        min_shares: Shares,
+    ) -> Result<ZapDeposit, LibErrors> {
+        if amount == Quantity::new(0) {
+            return Err(LibErrors::ZeroAmountInput);
+        }
+
+        if !self.strategy(strategy_index)?.accepts_deposits() {
+            return Err(LibErrors::StrategyClosed);
+        }
+
+        let other_token = match deposit_token {
+            Token::Base => Token::Quote,
+            Token::Quote => Token::Base,
+        };
+
+        // part to swap is estimated from the average rate of swapping the whole amount
+        let needed = self.needed_for_deposit(amount, deposit_token, strategy_index)?;
+        let swap = self.swap_with_shared()?;
+        let quote = match deposit_token {
+            Token::Base => swap.quote_sell(amount, self.oracle()?, self.quote_oracle()?)?,
+            Token::Quote => swap.quote_buy(amount, self.oracle()?, self.quote_oracle()?)?,
+        };
+
+        if (needed + quote.output()).is_zero() {
+            return Err(LibErrors::ZeroAmountInput);
+        }
+
+        let swapped = amount.big_mul_div(needed, needed + quote.output());
+
+        let received = match deposit_token {
+            Token::Base => self.sell(swapped)?,
+            Token::Quote => self.buy(swapped)?,
+        };
+
+        let remaining = amount - swapped;
+        let needed = self.needed_for_deposit(remaining, deposit_token, strategy_index)?;
+
+        let (deposited, deposited_other, shares) = if needed <= received {
+            let change = self.deposit(user_statement, deposit_token, remaining, strategy_index)?;
+            (remaining, change.other(deposit_token), change.shares)
         } else {
-            Ok(base_quantity)
+            let change = self.deposit(user_statement, other_token, received, strategy_index)?;
+            (change.other(other_token), received, change.shares)
+        };
+
+        if shares < min_shares {
+            return Err(LibErrors::NoMinAmountOut);
         }
+
+        Ok(ZapDeposit {
+            swapped,
+            received,
+            deposited,
+            deposited_other,
+            shares,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::core_lib::Vault;
+    use crate::core_lib::{strategy::StrategyState, Vault};
 
     #[test]
     fn deposit() -> Result<(), LibErrors> {
@@ -208,11 +478,15 @@ mod tests {
         let user_statement = &mut UserStatement::default();
 
         assert_eq!(
-            vault.deposit(user_statement, Token::Base, Quantity::new(2000000), 0)?,
+            vault
+                .deposit(user_statement, Token::Base, Quantity::new(2000000), 0)?
+                .quote,
             Quantity::new(4000000)
         );
         assert_eq!(
-            vault.deposit(user_statement, Token::Quote, Quantity::new(4000000), 0)?,
+            vault
+                .deposit(user_statement, Token::Quote, Quantity::new(4000000), 0)?
+                .base,
             Quantity::new(2000000)
         );
 
@@ -234,12 +508,14 @@ mod tests {
         let second_user_statement = &mut UserStatement::default();
 
         assert_eq!(
-            vault.deposit(
-                second_user_statement,
-                Token::Base,
-                Quantity::new(4000000),
-                0
-            )?,
+            vault
+                .deposit(
+                    second_user_statement,
+                    Token::Base,
+                    Quantity::new(4000000),
+                    0
+                )?
+                .quote,
             Quantity::new(8000000)
         );
 
@@ -272,7 +548,7 @@ mod tests {
             0,
         )?;
 
-        let BothQuantities { base, quote } = vault.withdraw(
+        let LiquidityChange { base, quote, .. } = vault.withdraw(
             second_user_statement,
             Token::Base,
             Quantity::new(4000005),
@@ -293,7 +569,7 @@ mod tests {
             .withdraw(second_user_statement, Token::Base, Quantity::new(0), 0)
             .is_err());
 
-        let BothQuantities { base, quote } = vault.withdraw(
+        let LiquidityChange { base, quote, .. } = vault.withdraw(
             second_user_statement,
             Token::Base,
             Quantity::new(2000000),
@@ -305,4 +581,187 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn zap_deposit() -> Result<(), LibErrors> {
+        let mut vault = Vault::new_vault_for_tests()?;
+        vault
+            .swap_service()?
+            .fee_curve_sell()
+            .add_constant_fee(Fraction::new(0), Fraction::from_integer(1));
+
+        let provider = &mut UserStatement::default();
+        vault.deposit(provider, Token::Base, Quantity::new(10000000), 0)?;
+        vault.deposit(provider, Token::Base, Quantity::new(1000000), 2)?;
+
+        let user_statement = &mut UserStatement::default();
+
+        // a failed instruction reverts the swap made before the check, so it runs on copies
+        let (mut failed, mut statement) = (vault, *user_statement);
+        assert_eq!(
+            failed.zap_deposit(
+                &mut statement,
+                Token::Base,
+                Quantity::new(1000000),
+                2,
+                Shares::new(500001)
+            ),
+            Err(LibErrors::NoMinAmountOut)
+        );
+
+        let zap = vault.zap_deposit(
+            user_statement,
+            Token::Base,
+            Quantity::new(1000000),
+            2,
+            Shares::new(500000),
+        )?;
+
+        // half is swapped at price of 2 to match 1:2 ratio of the strategy
+        assert_eq!(
+            zap,
+            ZapDeposit {
+                swapped: Quantity::new(500000),
+                received: Quantity::new(1000000),
+                deposited: Quantity::new(500000),
+                deposited_other: Quantity::new(1000000),
+                shares: Shares::new(500000),
+            }
+        );
+        assert_eq!(zap.taken(), Quantity::new(1000000));
+        assert_eq!(zap.refund(), Quantity::new(0));
+
+        let position = user_statement.search(&Position::LiquidityProvide {
+            vault_index: 0,
+            strategy_index: 2,
+            shares: Shares::new(0),
+            amount: Quantity::new(0),
+            quote_amount: Quantity::new(0),
+        })?;
+        assert_eq!(*position.shares(), Shares::new(500000));
+
+        vault
+            .strategy_mut(2)?
+            .set_state(StrategyState::DepositClosed)?;
+        assert_eq!(
+            vault.zap_deposit(
+                user_statement,
+                Token::Quote,
+                Quantity::new(1000000),
+                2,
+                Shares::new(0)
+            ),
+            Err(LibErrors::StrategyClosed)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn withdraw_single() -> Result<(), LibErrors> {
+        let mut vaults = [Vault::new_vault_for_tests()?];
+        vaults[0]
+            .swap_service()?
+            .fee_curve_buy()
+            .add_constant_fee(Fraction::new(0), Fraction::from_integer(1));
+
+        let provider = &mut UserStatement::default();
+        vaults[0].deposit(provider, Token::Base, Quantity::new(10000000), 1)?;
+
+        let user_statement = &mut UserStatement::default();
+        vaults[0].deposit(user_statement, Token::Base, Quantity::new(10000000), 0)?;
+        user_statement.refresh(&vaults)?;
+        vaults[0].borrow(user_statement, Quantity::new(2000000))?;
+        user_statement.refresh(&vaults)?;
+
+        // 40 of collateral value, 4 borrowed, so at most 36 can be withdrawn
+        assert_eq!(
+            vaults[0].withdraw_single(
+                user_statement,
+                Token::Base,
+                Quantity::new(9500000),
+                0,
+                Quantity::new(0)
+            ),
+            Err(LibErrors::UserNotCollateralized)
+        );
+
+        // quote leg is swapped for base at price of 2
+        assert_eq!(
+            vaults[0]
+                .withdraw_single(
+                    user_statement,
+                    Token::Base,
+                    Quantity::new(1000000),
+                    0,
+                    Quantity::new(2000000)
+                )?
+                .0,
+            Quantity::new(2000000)
+        );
+
+        let position = user_statement.search(&Position::LiquidityProvide {
+            vault_index: 0,
+            strategy_index: 0,
+            shares: Shares::new(0),
+            amount: Quantity::new(0),
+            quote_amount: Quantity::new(0),
+        })?;
+        assert_eq!(*position.shares(), Shares::new(9000000));
+
+        Ok(())
+    }
+
+    #[test]
+    fn wrap_shares() -> Result<(), LibErrors> {
+        let mut vaults = [Vault::new_vault_for_tests()?];
+        let user_statement = &mut UserStatement::default();
+
+        vaults[0].deposit(user_statement, Token::Base, Quantity::new(2000000), 0)?;
+        user_statement.refresh(&vaults)?;
+        assert_eq!(
+            user_statement.values.collateral.exact,
+            Value::from_integer(8)
+        );
+
+        assert_eq!(
+            vaults[0].wrap_shares(user_statement, 0, Shares::new(2000001)),
+            Err(LibErrors::NotEnoughShares)
+        );
+
+        // wrapped shares no longer count as collateral
+        vaults[0].wrap_shares(user_statement, 0, Shares::new(1000000))?;
+        user_statement.refresh(&vaults)?;
+        assert_eq!(
+            user_statement.values.collateral.exact,
+            Value::from_integer(4)
+        );
+
+        vaults[0].unwrap_shares(user_statement, 0, Shares::new(1000000))?;
+        user_statement.refresh(&vaults)?;
+        assert_eq!(
+            user_statement.values.collateral.exact,
+            Value::from_integer(8)
+        );
+
+        let pos = Position::LiquidityProvide {
+            vault_index: 0,
+            strategy_index: 0,
+            shares: Shares::new(0),
+            amount: Quantity::new(0),
+            quote_amount: Quantity::new(0),
+        };
+        assert_eq!(
+            *user_statement.search(&pos)?.amount(),
+            Quantity::new(2000000)
+        );
+
+        vaults[0].wrap_shares(user_statement, 0, Shares::new(2000000))?;
+        assert_eq!(
+            user_statement.search(&pos),
+            Err(LibErrors::PositionNotFound)
+        );
+
+        Ok(())
+    }
 }
@@ -6,7 +6,9 @@ use crate::core_lib::{
 };
 use checked_decimal_macro::Decimal;
 
-use super::Vault;
+use super::{Token, Vault};
+
+type PartFn = fn(&Strategy, ServiceType) -> Quantity;
 
 type ActionFn = fn(
     &mut Strategy,
@@ -21,8 +23,53 @@ impl Vault {
         quantity: Quantity,
         total: Quantity,
         service: ServiceType,
-        part: fn(&Strategy, ServiceType) -> Quantity,
+        part: PartFn,
+        action: ActionFn,
+    ) -> Result<(), LibErrors> {
+        self.split_where(quantity, total, service, part, action, |_| true)
+    }
+
+    /// Splits new locks only between strategies that still allocate liquidity,
+    /// so the ones being wound down release capital as their positions close
+    fn allocate(
+        &mut self,
+        quantity: Quantity,
+        total: Quantity,
+        service: ServiceType,
+        part: PartFn,
+        action: ActionFn,
+    ) -> Result<(), LibErrors> {
+        let mut closed = Quantity::new(0);
+
+        for i in self.strategies.indexes() {
+            let strategy = self.strategies.get_strategy(i as u8)?;
+
+            if strategy.participates(service) && !strategy.allocates() {
+                closed += part(strategy, service);
+            }
+        }
+
+        if closed.is_zero() {
+            return self.split(quantity, total, service, part, action);
+        }
+
+        let total = total - closed;
+
+        if quantity > total {
+            return Err(LibErrors::NotEnoughAvailableLiquidity);
+        }
+
+        self.split_where(quantity, total, service, part, action, Strategy::allocates)
+    }
+
+    fn split_where(
+        &mut self,
+        quantity: Quantity,
+        total: Quantity,
+        service: ServiceType,
+        part: PartFn,
         action: ActionFn,
+        filter: fn(&Strategy) -> bool,
     ) -> Result<(), LibErrors> {
         let mut processed = Quantity::new(0);
         let mut last_index = 0;
@@ -30,7 +77,7 @@ impl Vault {
         for i in self.strategies.indexes() {
             let strategy = self.strategies.get_strategy_mut(i as u8)?;
 
-            if strategy.uses(service) {
+            if strategy.participates(service) && filter(strategy) {
                 last_index = i;
                 let to_lock = quantity.big_mul_div(part(&strategy, service), total);
                 processed += to_lock;
@@ -51,7 +98,7 @@ impl Vault {
         quantity_b: Quantity,
         total: Quantity,
         service: ServiceType,
-        part: fn(&Strategy, ServiceType) -> Quantity,
+        part: PartFn,
         action_a: ActionFn,
         action_b: ActionFn,
     ) -> Result<(), LibErrors> {
@@ -61,7 +108,7 @@ impl Vault {
 
         for i in self.strategies.indexes() {
             let strategy = self.strategies.get_strategy_mut(i as u8)?;
-            if strategy.uses(service) {
+            if strategy.participates(service) {
                 last_index = i;
                 let to_lock_a = quantity_a.big_mul_div(part(&strategy, service), total);
                 let to_lock_b = quantity_b.big_mul_div(part(&strategy, service), total);
@@ -102,7 +149,7 @@ impl Vault {
         total_available: Quantity,
         service: ServiceType,
     ) -> Result<(), LibErrors> {
-        self.split(
+        self.allocate(
             quantity,
             total_available,
             service,
@@ -117,7 +164,7 @@ impl Vault {
         total_available: Quantity,
         service: ServiceType,
     ) -> Result<(), LibErrors> {
-        self.split(
+        self.allocate(
             quantity,
             total_available,
             service,
@@ -153,7 +200,10 @@ impl Vault {
             service,
             Strategy::locked_in,
             Strategy::unlock_base,
-        )
+        )?;
+
+        // returned liquidity goes to queued withdrawals first
+        self.fill_withdraw_queues()
     }
 
     pub fn unlock_quote(
@@ -168,7 +218,67 @@ impl Vault {
             service,
             Strategy::locked_in_quote,
             Strategy::unlock_quote,
-        )
+        )?;
+
+        self.fill_withdraw_queues()
+    }
+
+    /// Splits earnings (swap fees, trading fees and losses of traders) for yield tracking
+    pub fn record_earned_base(
+        &mut self,
+        quantity: Quantity,
+        total: Quantity,
+        service: ServiceType,
+        part: PartFn,
+    ) -> Result<(), LibErrors> {
+        if quantity.is_zero() {
+            return Ok(());
+        }
+
+        self.split(quantity, total, service, part, Strategy::earn_base)
+    }
+
+    pub fn record_earned_quote(
+        &mut self,
+        quantity: Quantity,
+        total: Quantity,
+        service: ServiceType,
+        part: PartFn,
+    ) -> Result<(), LibErrors> {
+        if quantity.is_zero() {
+            return Ok(());
+        }
+
+        self.split(quantity, total, service, part, Strategy::earn_quote)
+    }
+
+    /// Splits losses (profits of traders) for yield tracking
+    pub fn record_lost_base(
+        &mut self,
+        quantity: Quantity,
+        total: Quantity,
+        service: ServiceType,
+        part: PartFn,
+    ) -> Result<(), LibErrors> {
+        if quantity.is_zero() {
+            return Ok(());
+        }
+
+        self.split(quantity, total, service, part, Strategy::lose_base)
+    }
+
+    pub fn record_lost_quote(
+        &mut self,
+        quantity: Quantity,
+        total: Quantity,
+        service: ServiceType,
+        part: PartFn,
+    ) -> Result<(), LibErrors> {
+        if quantity.is_zero() {
+            return Ok(());
+        }
+
+        self.split(quantity, total, service, part, Strategy::lose_quote)
     }
 
     pub fn exchange_to_quote(
@@ -222,7 +332,9 @@ impl Vault {
             Strategy::locked_in,
             Strategy::unlock_base,
             Strategy::decrease_balance_base,
-        )
+        )?;
+
+        self.fill_withdraw_queues()
     }
 
     pub fn unlock_with_loss_quote(
@@ -240,7 +352,9 @@ impl Vault {
             Strategy::locked_in_quote,
             Strategy::unlock_quote,
             Strategy::decrease_balance_quote,
-        )
+        )?;
+
+        self.fill_withdraw_queues()
     }
 
     pub fn unlock_with_profit_base(
@@ -258,7 +372,9 @@ impl Vault {
             Strategy::locked_in,
             Strategy::unlock_base,
             Strategy::increase_balance_base,
-        )
+        )?;
+
+        self.fill_withdraw_queues()
     }
 
     pub fn unlock_with_profit_quote(
@@ -276,7 +392,41 @@ impl Vault {
             Strategy::locked_in_quote,
             Strategy::unlock_quote,
             Strategy::increase_balance_quote,
-        )
+        )?;
+
+        self.fill_withdraw_queues()
+    }
+
+    /// Unlocks quote of a short whose loss is paid in base
+    pub fn unlock_quote_with_profit_base(
+        &mut self,
+        unlock: Quantity,
+        profit: Quantity,
+        total_locked: Quantity,
+        service: ServiceType,
+    ) -> Result<(), LibErrors> {
+        self.double_split(
+            unlock,
+            profit,
+            total_locked,
+            service,
+            Strategy::locked_in_quote,
+            Strategy::unlock_quote,
+            Strategy::increase_balance_base,
+        )?;
+
+        self.fill_withdraw_queues()
+    }
+
+    /// Waterfall of a loss coming to strategies: they bear it up to the `threshold`,
+    /// the insurance fund covers the excess as far as its balance goes and anything left
+    /// falls back on strategies. Returns the part of the loss left for strategies
+    pub fn insure_loss(&mut self, loss: Quantity, threshold: Quantity, token: Token) -> Quantity {
+        if loss <= threshold {
+            return loss;
+        }
+
+        loss - self.insurance.cover(loss - threshold, token)
     }
 }
 
@@ -1,10 +1,25 @@
 use super::*;
 use crate::core_lib::{
     errors::LibErrors,
-    services::lending::Borrowable,
+    services::lending::{Borrowable, Repayment},
     user::{Position, UserStatement},
+    Token,
 };
-use checked_decimal_macro::Decimal;
+use checked_decimal_macro::{BetweenDecimals, BigOps, Decimal};
+use std::cmp::min;
+
+/// Outcome of a write-off, `debt` = `covered` + `socialized`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct WriteOff {
+    /// quantity of debt cleared from the position and lending service
+    pub debt: Quantity,
+    /// part of the debt covered by the insurance fund
+    pub covered: Quantity,
+    /// part of the debt lost by lending strategies
+    pub socialized: Quantity,
+    /// amount of borrow shares burned
+    pub shares: Shares,
+}
 
 impl Vault {
     fn lend_and_oracle(&mut self) -> Result<(&mut Lend, &Oracle), LibErrors> {
@@ -24,7 +39,7 @@ impl Vault {
         &mut self,
         user_statement: &mut UserStatement,
         amount: Quantity,
-    ) -> Result<Quantity, LibErrors> {
+    ) -> Result<(Quantity, Shares), LibErrors> {
         if amount == Quantity::new(0) {
             return Err(LibErrors::ZeroAmountInput);
         }
@@ -36,31 +51,77 @@ impl Vault {
         let borrow_quantity =
             lend.calculate_borrow_quantity(oracle, amount, user_allowed_borrow)?;
 
+        // collateral from isolated vaults backs debt only up to their ceilings
+        if oracle.calculate_value(borrow_quantity) > user_statement.permitted_borrow() {
+            return Err(LibErrors::DebtCeilingExceeded);
+        }
+
         let shares = lend.borrow(borrow_quantity)?;
         self.lock_base(borrow_quantity, total_available, ServiceType::Lend)?;
+        self.add_debt(user_statement, borrow_quantity, shares)?;
+
+        Ok((borrow_quantity, shares))
+    }
+
+    /// Turns a loss the trader did not pay into debt of the statement, as far as lent liquidity allows.
+    /// Returns the quantity booked as debt
+    pub fn book_debt(
+        &mut self,
+        user_statement: &mut UserStatement,
+        quantity: Quantity,
+    ) -> Result<Quantity, LibErrors> {
+        let debt = min(quantity, self.debt_capacity());
+
+        if debt.is_zero() {
+            return Ok(debt);
+        }
+
+        let lend = self.lend_service()?;
+        let total_available = lend.available().base;
+
+        let shares = lend.book_debt(debt);
+        self.lock_base(debt, total_available, ServiceType::Lend)?;
+        self.add_debt(user_statement, debt, shares)?;
+
+        Ok(debt)
+    }
+
+    /// Base that can still be booked as debt, zero without lending
+    pub fn debt_capacity(&self) -> Quantity {
+        self.services
+            .lend()
+            .map_or(Quantity::new(0), |lend| lend.available().base)
+    }
 
+    fn add_debt(
+        &self,
+        user_statement: &mut UserStatement,
+        amount: Quantity,
+        shares: Shares,
+    ) -> Result<(), LibErrors> {
         let position_temp = Position::Borrow {
             vault_index: self.id,
             shares,
-            amount: borrow_quantity,
+            amount,
         };
 
         match user_statement.search_mut(&position_temp) {
             Ok(position) => {
-                position.increase_amount(borrow_quantity);
+                position.increase_amount(amount);
                 position.increase_shares(shares);
             }
             Err(..) => user_statement.add_position(position_temp)?,
         }
 
-        Ok(borrow_quantity)
+        Ok(())
     }
 
+    /// Repays debt of the user, `repaid` is the quantity to be taken from user, which never exceeds the debt
     pub fn repay(
         &mut self,
         user_statement: &mut UserStatement,
         repay_quantity: Quantity,
-    ) -> Result<Quantity, LibErrors> {
+    ) -> Result<Repayment, LibErrors> {
         if repay_quantity == Quantity::new(0) {
             return Err(LibErrors::ZeroAmountInput);
         }
@@ -76,18 +137,272 @@ impl Vault {
         let lend = self.lend_service()?;
         let total_locked = lend.locked().base;
 
-        let (unlock_quantity, burned_shares, position_decrease) =
-            lend.repay(repay_quantity, *position.amount(), *position.shares())?;
+        let repayment = lend.repay(repay_quantity, *position.amount(), *position.shares())?;
 
-        self.unlock_base(unlock_quantity, total_locked, ServiceType::Lend)?;
+        self.unlock_base(repayment.repaid, total_locked, ServiceType::Lend)?;
 
-        if burned_shares.ge(&position.shares()) {
+        if repayment.shares.ge(position.shares()) {
             user_statement.delete_position(id)
         } else {
-            position.decrease_amount(position_decrease);
-            position.decrease_shares(burned_shares);
+            position.decrease_amount(repayment.principal);
+            position.decrease_shares(repayment.shares);
+        }
+
+        Ok(repayment)
+    }
+
+    /// Clears residual debt of a statement that is insolvent and has no collateral left.
+    /// The insurance fund covers the loss first, the rest is split between lending strategies
+    /// in proportion to their lent liquidity. Statement should be refreshed before
+    pub fn write_off(&mut self, user_statement: &mut UserStatement) -> Result<WriteOff, LibErrors> {
+        if !user_statement.insolvent() {
+            return Err(LibErrors::StatementSolvent);
+        }
+
+        if user_statement.has_collateral() {
+            return Err(LibErrors::CollateralRemaining);
+        }
+
+        let position_temp = Position::Borrow {
+            vault_index: self.id,
+            shares: Shares::new(0),
+            amount: Quantity::new(0),
+        };
+
+        let (id, position) = user_statement.search_mut_id(&position_temp)?;
+
+        let lend = self.lend_service()?;
+        let total_locked = lend.locked().base;
+
+        let repayment = lend.write_off(*position.amount(), *position.shares())?;
+        user_statement.delete_position(id);
+
+        let debt = repayment.repaid;
+        let socialized = self.insure_loss(debt, Quantity::new(0), Token::Base);
+
+        self.record_lost_base(
+            socialized,
+            total_locked,
+            ServiceType::Lend,
+            Strategy::locked_in,
+        )?;
+        self.unlock_with_loss_base(debt, socialized, total_locked, ServiceType::Lend)?;
+
+        Ok(WriteOff {
+            debt,
+            covered: debt - socialized,
+            socialized,
+            shares: repayment.shares,
+        })
+    }
+
+    /// Withdraws liquidity of the user from the strategy to repay their debt in this vault,
+    /// quote part of the withdrawal is sold for base. Fails if less than `min_base_out` is collected.
+    /// Returns the repayment and leftover base quantity
+    pub fn repay_with_collateral(
+        &mut self,
+        user_statement: &mut UserStatement,
+        strategy_index: u8,
+        amount: Quantity,
+        min_base_out: Quantity,
+    ) -> Result<(Repayment, Quantity), LibErrors> {
+        if amount == Quantity::new(0) {
+            return Err(LibErrors::ZeroAmountInput);
+        }
+
+        let position = user_statement.search(&Position::Borrow {
+            vault_index: self.id,
+            shares: Shares::new(0),
+            amount: Quantity::new(0),
+        })?;
+        let amount = min(amount, position.get_owed_single(position.shares(), self)?);
+
+        let oracle = self.oracle()?;
+        let quote_oracle = self.quote_oracle()?;
+        let strategy = self.strategy(strategy_index)?;
+
+        let strategy_value = oracle.calculate_value(strategy.balance())
+            + quote_oracle.calculate_value(strategy.balance_quote());
+
+        if strategy_value == Value::new(0) {
+            return Err(LibErrors::EmptyStrategy);
+        }
+
+        // part of the strategy worth the debt, withdrawn in its current ratio
+        let part = Fraction::from_decimal_up(
+            oracle
+                .calculate_needed_value(amount)
+                .big_div_up(strategy_value),
+        );
+
+        let (withdraw_token, balance) = if strategy.balance() != Quantity::new(0) {
+            (Token::Base, strategy.balance())
+        } else {
+            (Token::Quote, strategy.balance_quote())
+        };
+
+        let LiquidityChange { base, quote, .. } = self.withdraw(
+            user_statement,
+            withdraw_token,
+            balance.big_mul_up(part),
+            strategy_index,
+        )?;
+
+        let base = if quote != Quantity::new(0) {
+            base + self.buy(quote)?
+        } else {
+            base
+        };
+
+        if base < min_base_out {
+            return Err(LibErrors::NoMinAmountOut);
         }
 
-        Ok(unlock_quantity)
+        let repayment = self.repay(user_statement, min(base, amount))?;
+
+        Ok((repayment, base - repayment.repaid))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_lib::decimal::Balances;
+
+    #[test]
+    fn repay_with_collateral() -> Result<(), LibErrors> {
+        let mut vaults = [Vault::new_vault_for_tests()?];
+        vaults[0]
+            .swap_service()?
+            .fee_curve_buy()
+            .add_constant_fee(Fraction::new(0), Fraction::from_integer(1));
+        let user_statement = &mut UserStatement::default();
+
+        vaults[0].deposit(user_statement, Token::Base, Quantity::new(10000000), 0)?;
+        user_statement.refresh(&vaults)?;
+        vaults[0].borrow(user_statement, Quantity::new(2000000))?;
+
+        assert_eq!(
+            vaults[0].repay_with_collateral(
+                user_statement,
+                1,
+                Quantity::new(1000000),
+                Quantity::new(0)
+            ),
+            Err(LibErrors::EmptyStrategy)
+        );
+        // a failed instruction reverts, so it runs on copies
+        let (mut vault, mut statement) = (vaults[0], *user_statement);
+        assert_eq!(
+            vault.repay_with_collateral(
+                &mut statement,
+                0,
+                Quantity::new(1000000),
+                Quantity::new(1000001)
+            ),
+            Err(LibErrors::NoMinAmountOut)
+        );
+
+        // half of the withdrawal is quote, sold for base
+        let (repayment, leftover) = vaults[0].repay_with_collateral(
+            user_statement,
+            0,
+            Quantity::new(1000000),
+            Quantity::new(1000000),
+        )?;
+        assert_eq!(
+            (repayment.repaid, leftover),
+            (Quantity::new(1000000), Quantity::new(0))
+        );
+
+        let borrow = Position::Borrow {
+            vault_index: 0,
+            shares: Shares::new(0),
+            amount: Quantity::new(0),
+        };
+        assert_eq!(
+            *user_statement.search(&borrow)?.amount(),
+            Quantity::new(1000000)
+        );
+
+        // clamped to the owed quantity
+        let (repayment, leftover) = vaults[0].repay_with_collateral(
+            user_statement,
+            0,
+            Quantity::new(5000000),
+            Quantity::new(0),
+        )?;
+        assert_eq!(
+            (repayment.repaid, leftover),
+            (Quantity::new(1000000), Quantity::new(8))
+        );
+        assert_eq!(
+            user_statement.search(&borrow),
+            Err(LibErrors::PositionNotFound)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn write_off() -> Result<(), LibErrors> {
+        let mut vaults = [Vault::new_vault_for_tests()?];
+        let lender = &mut UserStatement::default();
+        let borrower = &mut UserStatement::default();
+
+        vaults[0].deposit(lender, Token::Base, Quantity::new(6000000), 0)?;
+        vaults[0].deposit(lender, Token::Base, Quantity::new(2000000), 2)?;
+        vaults[0].deposit(borrower, Token::Base, Quantity::new(2000000), 2)?;
+        borrower.refresh(&vaults)?;
+        let (debt, _) = vaults[0].borrow(borrower, Quantity::new(2000000))?;
+
+        borrower.refresh(&vaults)?;
+        assert_eq!(
+            vaults[0].write_off(borrower),
+            Err(LibErrors::StatementSolvent)
+        );
+
+        // collateral gone, debt stays
+        vaults[0].withdraw(borrower, Token::Base, Quantity::new(2000000), 2)?;
+        borrower.refresh(&vaults)?;
+
+        vaults[0].insurance.deposit(Balances {
+            base: Quantity::new(500000),
+            quote: Quantity::new(0),
+        });
+
+        assert_eq!(
+            vaults[0].write_off(borrower)?,
+            WriteOff {
+                debt,
+                covered: Quantity::new(500000),
+                socialized: Quantity::new(1500000),
+                shares: Shares::new(2000000),
+            }
+        );
+        assert_eq!(
+            borrower.search(&Position::Borrow {
+                vault_index: 0,
+                shares: Shares::new(0),
+                amount: Quantity::new(0),
+            }),
+            Err(LibErrors::PositionNotFound)
+        );
+
+        let lend = vaults[0].lend_service()?;
+        assert_eq!(lend.borrowed, Quantity::new(0));
+        assert_eq!(lend.borrow_shares, Shares::new(0));
+        assert_eq!(lend.written_off, debt);
+        assert_eq!(lend.available().base, Quantity::new(6500000));
+
+        // loss split as the debt was lent, 6:4
+        assert_eq!(vaults[0].strategy(0)?.balance(), Quantity::new(5100000));
+        assert_eq!(vaults[0].strategy(2)?.balance(), Quantity::new(1400000));
+        assert_eq!(
+            vaults[0].strategy(2)?.yields.lost.base,
+            Quantity::new(600000)
+        );
+
+        Ok(())
     }
 }
@@ -0,0 +1,150 @@
+use super::*;
+use crate::core_lib::{
+    decimal::Balances, services::ServiceUpdate, strategy::StrategyState, structs::Operation,
+};
+
+/// Stages of a vault, a delisted vault only lets liquidity out until it is closed
+#[derive(Debug, Clone, Default, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum VaultStatus {
+    #[default]
+    Active = 0,
+    /// oracle prices are frozen, positions can only be closed and liquidity withdrawn
+    Delisting = 1,
+    /// reserves are closed and the slot can be taken by a new vault
+    Closed = 2,
+}
+
+impl Vault {
+    /// Stops new activity in the vault, its oracles keep the last price as the final one
+    pub fn delist(&mut self) -> Result<(), LibErrors> {
+        if self.status != VaultStatus::Active {
+            return Err(LibErrors::VaultDelisted);
+        }
+
+        self.status = VaultStatus::Delisting;
+        Ok(())
+    }
+
+    /// Fails for operations other than withdrawals once the vault is delisted
+    pub fn check_listed(&self, operation: Operation) -> Result<(), LibErrors> {
+        match self.status == VaultStatus::Active || operation == Operation::Withdraw {
+            true => Ok(()),
+            false => Err(LibErrors::VaultDelisted),
+        }
+    }
+
+    /// Checks that nothing is owed to LPs, borrowers or traders anymore, so the reserves can be closed
+    pub fn check_closable(&self) -> Result<(), LibErrors> {
+        if self.status != VaultStatus::Delisting {
+            return Err(LibErrors::VaultNotDelisting);
+        }
+
+        let strategies_empty = match self.strategies.iter() {
+            Some(mut strategies) => strategies.all(|strategy| {
+                strategy.total_shares.is_zero() && strategy.withdraw_queue.is_empty()
+            }),
+            None => true,
+        };
+        let lend_empty = self
+            .services
+            .lend()
+            .map_or(true, |lend| lend.locked().base.is_zero());
+        let trade_empty = self.services.trade().map_or(true, |trade| {
+            trade.locked().base.is_zero() && trade.locked().quote.is_zero()
+        });
+
+        match strategies_empty && lend_empty && trade_empty {
+            true => Ok(()),
+            false => Err(LibErrors::VaultNotEmpty),
+        }
+    }
+
+    /// Moves the strategy through its lifecycle. Dust left available by rounding once all shares
+    /// are out is swept into the insurance fund on retiring and returned
+    pub fn set_strategy_state(
+        &mut self,
+        strategy_index: u8,
+        state: StrategyState,
+    ) -> Result<Balances, LibErrors> {
+        if state == StrategyState::Retired {
+            // queued requests get their liquidity set aside before anything is swept
+            self.fill_withdraw_queue(strategy_index)?;
+        }
+
+        let Self {
+            strategies,
+            services,
+            insurance,
+            ..
+        } = self;
+
+        let strategy = strategies.get_strategy_mut(strategy_index)?;
+        strategy.set_state(state)?;
+
+        if state != StrategyState::Retired {
+            return Ok(Balances::default());
+        }
+
+        let dust = strategy.withdrawable();
+        strategy.withdraw(dust.base, dust.quote, Shares::new(0), services);
+        insurance.deposit(dust);
+
+        Ok(dust)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_lib::user::UserStatement;
+
+    #[test]
+    fn lifecycle() -> Result<(), LibErrors> {
+        let mut vault = Vault::default();
+
+        assert_eq!(vault.check_closable(), Err(LibErrors::VaultNotDelisting));
+        vault.delist()?;
+        assert_eq!(vault.delist(), Err(LibErrors::VaultDelisted));
+
+        assert_eq!(
+            vault.check_listed(Operation::Deposit),
+            Err(LibErrors::VaultDelisted)
+        );
+        assert_eq!(vault.check_listed(Operation::Withdraw), Ok(()));
+        assert_eq!(vault.check_closable(), Ok(()));
+
+        let mut vault = Vault::new_vault_for_tests()?;
+        let mut user = UserStatement::default();
+        vault.deposit(&mut user, Token::Base, Quantity::new(1000000), 0)?;
+        vault.delist()?;
+        assert_eq!(vault.check_closable(), Err(LibErrors::VaultNotEmpty));
+
+        // wrapped shares are brought back and withdrawn after delisting
+        let mut vault = Vault::new_vault_for_tests()?;
+        let mut user = UserStatement::default();
+        let change = vault.deposit(&mut user, Token::Base, Quantity::new(1000000), 0)?;
+        vault.wrap_shares(&mut user, 0, change.shares)?;
+        vault.delist()?;
+        assert_eq!(vault.check_closable(), Err(LibErrors::VaultNotEmpty));
+
+        vault.check_listed(Operation::Withdraw)?;
+        vault.unwrap_shares(&mut user, 0, change.shares)?;
+        vault.withdraw(&mut user, Token::Base, Quantity::new(1000000), 0)?;
+        assert_eq!(vault.check_closable(), Ok(()));
+
+        // outflow limits stop applying once the vault is winding down
+        let mut vault = Vault::new_vault_for_tests()?;
+        vault
+            .outflow
+            .set(100, Value::from_integer(100), Value::from_integer(1));
+        assert_eq!(
+            vault.record_outflow(&mut user, Quantity::new(1000000), Quantity::new(0), 0),
+            Err(LibErrors::StatementOutflowLimitExceeded)
+        );
+        vault.delist()?;
+        vault.record_outflow(&mut user, Quantity::new(1000000), Quantity::new(0), 0)?;
+
+        Ok(())
+    }
+}
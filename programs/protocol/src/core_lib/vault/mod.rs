@@ -1,21 +1,28 @@
 pub mod deposit;
 pub mod general;
 pub mod lend;
+pub mod lifecycle;
+pub mod params;
+pub mod queue;
 pub mod swap;
 pub mod trade;
 
 use crate::core_lib::{
-    decimal::{DecimalPlaces, Fraction, Price, Quantity, Shares, Time, Utilization},
+    decimal::{DecimalPlaces, Fraction, Price, Quantity, Shares, Time, Utilization, Value},
     services::{lending::Lend, swapping::Swap, ServiceType, ServiceUpdate, Services},
     strategy::{Strategies, Strategy},
-    structs::{FeeCurve, Oracle},
+    structs::{FeeCurve, InsuranceFund, Oracle, OutflowLimits, PauseFlags},
 };
 use checked_decimal_macro::Decimal;
 
 #[cfg(test)]
 use checked_decimal_macro::Factories;
 
-pub use self::deposit::Token;
+pub use self::deposit::{LiquidityChange, Token};
+pub use self::lifecycle::VaultStatus;
+
+/// Number of vault slots, statements keep per vault data for each of them
+pub const MAX_VAULTS: usize = 10;
 
 #[cfg(feature = "anchor")]
 mod zero {
@@ -30,6 +37,17 @@ mod zero {
         pub strategies: Strategies,
         pub oracle: Option<Oracle>,
         pub quote_oracle: Option<Oracle>,
+        /// isolation mode, max debt value LP shares of this vault can back across all statements
+        pub debt_ceiling: Option<Value>,
+        /// debt value currently backed by LP shares of this vault, never above the debt ceiling
+        pub isolated_debt: Value,
+        /// covers losses of strategies beyond what they are expected to bear
+        pub insurance: InsuranceFund,
+        /// operations stopped in this vault only
+        pub pause: PauseFlags,
+        /// rolling-window limits on value leaving the vault
+        pub outflow: OutflowLimits,
+        pub status: VaultStatus,
         pub id: u8,
     }
 }
@@ -44,6 +62,17 @@ mod non_zero {
         pub strategies: Strategies,
         pub oracle: Option<Oracle>,
         pub quote_oracle: Option<Oracle>,
+        /// isolation mode, max debt value LP shares of this vault can back across all statements
+        pub debt_ceiling: Option<Value>,
+        /// debt value currently backed by LP shares of this vault, never above the debt ceiling
+        pub isolated_debt: Value,
+        /// covers losses of strategies beyond what they are expected to bear
+        pub insurance: InsuranceFund,
+        /// operations stopped in this vault only
+        pub pause: PauseFlags,
+        /// rolling-window limits on value leaving the vault
+        pub outflow: OutflowLimits,
+        pub status: VaultStatus,
         pub id: u8,
     }
 }
@@ -54,7 +83,7 @@ pub use zero::Vault;
 #[cfg(not(feature = "anchor"))]
 pub use non_zero::Vault;
 
-use super::{errors::LibErrors, services::trading::Trade};
+use super::{errors::LibErrors, services::trading::Trade, user::UserStatement};
 
 impl Vault {
     pub fn add_strategy(
@@ -76,6 +105,8 @@ impl Vault {
             return Err(LibErrors::TradeServiceNone);
         }
 
+        Strategy::validate_risk_params(collateral_ratio, liquidation_threshold)?;
+
         self.strategies
             .add(Strategy::new(
                 has_lend,
@@ -87,6 +118,24 @@ impl Vault {
             .map_err(|_| LibErrors::CannotAddStrategy)
     }
 
+    pub fn set_strategy_services(
+        &mut self,
+        index: u8,
+        has_lend: bool,
+        has_swap: bool,
+        has_trade: bool,
+    ) -> Result<(), LibErrors> {
+        let Self {
+            strategies,
+            services,
+            ..
+        } = self;
+
+        strategies
+            .get_strategy_mut(index)?
+            .set_services(has_lend, has_swap, has_trade, services)
+    }
+
     pub fn enable_oracle(
         &mut self,
         decimal_places: DecimalPlaces,
@@ -217,6 +266,44 @@ impl Vault {
         Ok(())
     }
 
+    /// enables isolation mode for the vault collateral, `None` disables it
+    pub fn set_debt_ceiling(&mut self, debt_ceiling: Option<Value>) {
+        self.debt_ceiling = debt_ceiling;
+    }
+
+    pub fn debt_ceiling(&self) -> Option<Value> {
+        self.debt_ceiling
+    }
+
+    /// Debt value LP shares of this vault can still back, `None` outside isolation mode
+    pub fn isolated_room(&self) -> Option<Value> {
+        self.debt_ceiling
+            .map(|ceiling| match ceiling > self.isolated_debt {
+                true => ceiling - self.isolated_debt,
+                false => Value::new(0),
+            })
+    }
+
+    /// Records tokens leaving the vault to the statement owner, valued at oracle prices.
+    /// A delisted vault is wound down without limits, so it can always be emptied and closed
+    pub fn record_outflow(
+        &mut self,
+        user_statement: &mut UserStatement,
+        base: Quantity,
+        quote: Quantity,
+        now: Time,
+    ) -> Result<(), LibErrors> {
+        if self.outflow.window == 0 || self.status == VaultStatus::Delisting {
+            return Ok(());
+        }
+
+        let value =
+            self.oracle()?.calculate_value(base) + self.quote_oracle()?.calculate_value(quote);
+        let usage = user_statement.outflow_mut(self.id)?;
+
+        self.outflow.record(usage, value, now)
+    }
+
     pub fn trade_service(&mut self) -> Result<&mut Trade, LibErrors> {
         self.services.trade_mut()
     }
@@ -253,7 +340,8 @@ impl Vault {
         self.services.trade()
     }
 
-    pub fn refresh(&mut self, current_time: Time) -> Result<(), LibErrors> {
+    /// Accrues lending fees and settles them to strategies, returns the settled quantity
+    pub fn refresh(&mut self, current_time: Time) -> Result<Quantity, LibErrors> {
         if let Ok(lend) = self.lend_service() {
             lend.accrue_interest_rate(current_time);
 
@@ -267,7 +355,7 @@ impl Vault {
                 let locked_without_current_fees = locked - accrued_fees;
 
                 if accrued_fees.is_zero() {
-                    return Ok(());
+                    return Ok(accrued_fees);
                 }
 
                 self.settle_lend_fees(
@@ -275,10 +363,12 @@ impl Vault {
                     locked_without_current_fees,
                     ServiceType::Lend,
                 )?;
+
+                return Ok(accrued_fees);
             }
         }
 
-        Ok(())
+        Ok(Quantity::new(0))
     }
 }
 
@@ -0,0 +1,200 @@
+use super::*;
+use crate::core_lib::structs::{ChangeKind, PendingChange};
+
+impl Vault {
+    /// Fee curve of the service: 1 - lend, 2 - swap, 3 - trade, `base` picks the side where there are two
+    pub fn fee_curve_mut(&mut self, service: u8, base: bool) -> Result<&mut FeeCurve, LibErrors> {
+        Ok(match (service, base) {
+            (1, true) => self.lend_service()?.fee_curve(),
+            (2, true) => self.swap_service()?.fee_curve_sell(),
+            (2, false) => self.swap_service()?.fee_curve_buy(),
+            (3, true) => self.trade_service()?.fee_curve_base(),
+            (3, false) => self.trade_service()?.fee_curve_quote(),
+            _ => return Err(LibErrors::InvalidService),
+        })
+    }
+
+    /// Parameters can be set directly only until liquidity comes in, later changes go through the timelock
+    pub fn check_setup(&self) -> Result<(), LibErrors> {
+        let has_users = match self.strategies.iter() {
+            Some(mut strategies) => strategies.any(|strategy| {
+                !strategy.total_shares.is_zero() || !strategy.withdraw_queue.is_empty()
+            }),
+            None => false,
+        };
+
+        match has_users {
+            true => Err(LibErrors::TimelockRequired),
+            false => Ok(()),
+        }
+    }
+
+    /// Applies a change that waited out the timelock, changes of the delay are applied to the timelock itself
+    pub fn apply_change(&mut self, change: &PendingChange, now: Time) -> Result<(), LibErrors> {
+        let [v0, v1, v2, v3, v4, v5] = change.values;
+        let service = v0 as u8;
+        let base = v1 != 0;
+
+        match change.kind {
+            ChangeKind::FeeCurveSegment => {
+                let curve = self.fee_curve_mut(service, base)?;
+
+                if curve.is_full() {
+                    return Err(LibErrors::InvalidFeeCurve);
+                }
+
+                let bound = Fraction::new(v2);
+                match v3 {
+                    0 => curve.add_constant_fee(Fraction::new(v4), bound),
+                    b => curve.add_linear_fee(Fraction::new(b), Fraction::new(v4), bound),
+                };
+            }
+            ChangeKind::JumpRateCurve => {
+                let new_curve = FeeCurve::jump_rate(
+                    Fraction::new(v2),
+                    Fraction::new(v3),
+                    Fraction::new(v4),
+                    Fraction::new(v5),
+                )?;
+
+                // fees accrued so far are settled with the previous curve
+                self.refresh(now)?;
+
+                *self.fee_curve_mut(service, base)? = new_curve;
+            }
+            ChangeKind::TradingParams => self.trade_service()?.set_params(
+                Fraction::new(v0),
+                Fraction::new(v1),
+                Fraction::new(v2),
+                Fraction::new(v3),
+            )?,
+            ChangeKind::FeeCurve => {
+                self.refresh(now)?;
+
+                *self.fee_curve_mut(service, base)? = change.curve;
+            }
+            ChangeKind::StrategyRiskParams => self
+                .strategy_mut(change.strategy)?
+                .set_risk_params(Fraction::new(v0), Fraction::new(v1))?,
+            ChangeKind::DebtCeiling => {
+                self.set_debt_ceiling((v0 != 0).then(|| Value::new(v1 as u128)))
+            }
+            ChangeKind::InventorySkew => self
+                .swap_service()?
+                .set_inventory_skew(Fraction::new(v0), Fraction::new(v1))?,
+            ChangeKind::StrategyServices => {
+                self.set_strategy_services(change.strategy, v0 != 0, v1 != 0, v2 != 0)?
+            }
+            ChangeKind::Delay => return Err(LibErrors::InvalidChangeKind),
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_lib::{
+        structs::{timelock::MIN_TIMELOCK_DELAY, Timelock},
+        user::UserStatement,
+    };
+
+    #[test]
+    fn apply_change() -> Result<(), LibErrors> {
+        let mut vault = Vault::new_vault_for_tests()?;
+        let mut timelock = Timelock::new();
+        let ready = MIN_TIMELOCK_DELAY;
+
+        let id = timelock.schedule(
+            ChangeKind::TradingParams,
+            0,
+            0,
+            [1000, 20000000, 500000, 900000, 0, 0],
+            0,
+        )?;
+        let debt_ceiling =
+            timelock.schedule(ChangeKind::DebtCeiling, 0, 0, [1, 5000, 0, 0, 0, 0], 0)?;
+
+        assert_eq!(timelock.take(id, ready - 1), Err(LibErrors::ChangeNotReady));
+        vault.apply_change(&timelock.take(id, ready)?, ready)?;
+
+        let trade = vault.trade_service_not_mut()?;
+        assert_eq!(trade.open_fee, Fraction::new(1000));
+        assert_eq!(trade.max_leverage, Fraction::new(20000000));
+        assert_eq!(trade.collateral_ratio(), Fraction::new(500000));
+        assert_eq!(trade.liquidation_threshold(), Fraction::new(900000));
+
+        timelock.cancel(debt_ceiling)?;
+        assert_eq!(vault.debt_ceiling(), None);
+
+        // liquidation threshold below the collateral ratio
+        let id = timelock.schedule(
+            ChangeKind::TradingParams,
+            0,
+            0,
+            [1000, 20000000, 900000, 500000, 0, 0],
+            ready,
+        )?;
+        assert_eq!(
+            vault.apply_change(&timelock.take(id, 2 * ready)?, 2 * ready),
+            Err(LibErrors::InvalidRiskParams)
+        );
+
+        let id = timelock.schedule(
+            ChangeKind::StrategyRiskParams,
+            0,
+            7,
+            [500000, 900000, 0, 0, 0, 0],
+            ready,
+        )?;
+        assert_eq!(
+            vault.apply_change(&timelock.take(id, 2 * ready)?, 2 * ready),
+            Err(LibErrors::StrategyMissing)
+        );
+
+        let id = timelock.schedule(
+            ChangeKind::InventorySkew,
+            0,
+            0,
+            [600000, 500000, 0, 0, 0, 0],
+            ready,
+        )?;
+        vault.apply_change(&timelock.take(id, 2 * ready)?, 2 * ready)?;
+
+        let swap = vault.swap_service_not_mut()?;
+        assert_eq!(swap.skew_target, Fraction::new(600000));
+        assert_eq!(swap.skew_factor, Fraction::new(500000));
+
+        let id = timelock.schedule(
+            ChangeKind::StrategyServices,
+            0,
+            2,
+            [1, 1, 0, 0, 0, 0],
+            ready,
+        )?;
+        vault.apply_change(&timelock.take(id, 2 * ready)?, 2 * ready)?;
+
+        let strategy = vault.strategy(2)?;
+        assert!(strategy.uses(ServiceType::Lend) && strategy.uses(ServiceType::Swap));
+        assert!(!strategy.uses(ServiceType::Trade));
+
+        Ok(())
+    }
+
+    #[test]
+    fn setup_ends_with_liquidity() -> Result<(), LibErrors> {
+        let mut vault = Vault::new_vault_for_tests()?;
+        vault.check_setup()?;
+
+        vault.deposit(
+            &mut UserStatement::default(),
+            Token::Base,
+            Quantity::new(1000000),
+            0,
+        )?;
+        assert_eq!(vault.check_setup(), Err(LibErrors::TimelockRequired));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,383 @@
+use super::*;
+use crate::core_lib::{
+    decimal::Balances,
+    strategy::WithdrawQueue,
+    user::{Position, UserStatement},
+};
+use std::cmp::min;
+
+impl Vault {
+    /// Queues withdrawal of liquidity that is currently locked, value of the shares is fixed now
+    /// and paid out in order of requests as liquidity returns to the strategy. Returns the ticket
+    /// and the owed quantities
+    pub fn request_withdraw(
+        &mut self,
+        user_statement: &mut UserStatement,
+        owner: [u8; 32],
+        withdraw_token: Token,
+        amount: Quantity,
+        strategy_index: u8,
+    ) -> Result<(u64, Balances), LibErrors> {
+        if amount == Quantity::new(0) {
+            return Err(LibErrors::ZeroAmountInput);
+        }
+
+        if !self.strategy(strategy_index)?.accepts_withdrawals() {
+            return Err(LibErrors::StrategyClosed);
+        }
+
+        let position_temp = Position::LiquidityProvide {
+            vault_index: self.id,
+            strategy_index,
+            shares: Shares::new(0),
+            amount: Quantity::new(0),
+            quote_amount: Quantity::new(0),
+        };
+
+        let (id, position) = user_statement.search_mut_id(&position_temp)?;
+
+        let strategy = self.strategies.get_strategy_mut(strategy_index)?;
+        let balance = match withdraw_token {
+            Token::Base => strategy.balance(),
+            Token::Quote => strategy.balance_quote(),
+        };
+
+        let shares = min(
+            strategy.total_shares().get_change_up(amount, balance),
+            *position.shares(),
+        );
+
+        let (base_quantity, quote_quantity) = strategy.get_earned_double(&shares);
+        let owed = Balances {
+            base: base_quantity,
+            quote: quote_quantity,
+        };
+
+        let ticket = strategy.withdraw_queue.push(owner, owed, shares)?;
+        strategy.total_shares -= shares;
+
+        if shares.lt(position.shares()) {
+            position.decrease_amount(min(*position.amount(), base_quantity));
+            position.decrease_quote_amount(min(*position.quote_amount(), quote_quantity));
+            position.decrease_shares(shares);
+        } else {
+            user_statement.delete_position(id)
+        }
+
+        self.fill_withdraw_queue(strategy_index)?;
+
+        Ok((ticket, owed))
+    }
+
+    /// Sets aside available liquidity for queued requests, strictly in order of the queue.
+    /// Liquidity set aside is taken out of the strategy, so services can't lend or trade it
+    pub fn fill_withdraw_queue(&mut self, strategy_index: u8) -> Result<(), LibErrors> {
+        let Self {
+            strategies,
+            services,
+            ..
+        } = self;
+
+        let strategy = strategies.get_strategy_mut(strategy_index)?;
+
+        while let Some(index) = strategy.withdraw_queue.next_unfilled() {
+            let taken = strategy.withdraw_queue.reserve(index, strategy.available);
+            strategy.withdraw(taken.base, taken.quote, Shares::new(0), services);
+
+            if !strategy.withdraw_queue.requests[index].filled {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn fill_withdraw_queues(&mut self) -> Result<(), LibErrors> {
+        for i in self.strategies.indexes() {
+            self.fill_withdraw_queue(i as u8)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes filled request from the queue, returns quantities to be sent to the owner
+    pub fn claim_withdraw(
+        &mut self,
+        owner: [u8; 32],
+        strategy_index: u8,
+        ticket: u64,
+    ) -> Result<Balances, LibErrors> {
+        let request = self
+            .strategy_mut(strategy_index)?
+            .withdraw_queue
+            .take(&owner, ticket, true)?;
+
+        Ok(request.owed)
+    }
+
+    /// Removes request that is not filled yet, liquidity set aside for it goes back to the strategy.
+    /// Burned shares are restored, but never worth more than the owed quantities
+    pub fn cancel_withdraw(
+        &mut self,
+        user_statement: &mut UserStatement,
+        owner: [u8; 32],
+        strategy_index: u8,
+        ticket: u64,
+    ) -> Result<Shares, LibErrors> {
+        let Self {
+            strategies,
+            services,
+            ..
+        } = self;
+
+        let strategy = strategies.get_strategy_mut(strategy_index)?;
+        let request = strategy.withdraw_queue.take(&owner, ticket, false)?;
+        let Balances { base, quote } = request.owed;
+
+        strategy.deposit(
+            request.reserved.base,
+            request.reserved.quote,
+            Shares::new(0),
+            services,
+        );
+
+        // value of shares may have grown while they were out of the strategy,
+        // they are priced against the liquidity of the other holders
+        let others = (
+            WithdrawQueue::exclude_pending(strategy.balance(), base),
+            WithdrawQueue::exclude_pending(strategy.balance_quote(), quote),
+        );
+        let shares = match others {
+            (balance, _) if !balance.is_zero() => {
+                strategy.total_shares().get_change_down(base, balance)
+            }
+            (_, balance) if !balance.is_zero() => {
+                strategy.total_shares().get_change_down(quote, balance)
+            }
+            _ => request.shares,
+        };
+        let shares = min(shares, request.shares);
+        strategy.total_shares += shares;
+
+        let temp_position = Position::LiquidityProvide {
+            vault_index: self.id,
+            strategy_index,
+            shares,
+            amount: base,
+            quote_amount: quote,
+        };
+
+        match user_statement.search_mut(&temp_position) {
+            Ok(position) => {
+                position.increase_amount(base);
+                position.increase_quote_amount(quote);
+                position.increase_shares(shares);
+            }
+            Err(..) => user_statement.add_position(temp_position)?,
+        }
+
+        self.fill_withdraw_queue(strategy_index)?;
+
+        Ok(shares)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_lib::{strategy::StrategyState, user::UserStatement};
+
+    #[test]
+    fn retire_with_queue() -> Result<(), LibErrors> {
+        let mut vault = Vault::new_vault_for_tests()?;
+        let user_statement = &mut UserStatement::default();
+        let owner = [1; 32];
+
+        vault.deposit(user_statement, Token::Base, Quantity::new(1000000), 2)?;
+        vault.lock_base(
+            Quantity::new(800000),
+            Quantity::new(1000000),
+            ServiceType::Lend,
+        )?;
+        let (ticket, owed) = vault.request_withdraw(
+            user_statement,
+            owner,
+            Token::Base,
+            Quantity::new(1000000),
+            2,
+        )?;
+        vault.unlock_base(
+            Quantity::new(800000),
+            Quantity::new(800000),
+            ServiceType::Lend,
+        )?;
+
+        // no shares nor locks are left, but the request is still to be claimed
+        assert_eq!(vault.strategy(2)?.total_shares(), Shares::new(0));
+        assert_eq!(
+            vault.set_strategy_state(2, StrategyState::Retired),
+            Err(LibErrors::StrategyNotEmpty)
+        );
+
+        assert_eq!(vault.claim_withdraw(owner, 2, ticket)?, owed);
+        assert_eq!(
+            vault.set_strategy_state(2, StrategyState::Retired)?,
+            Balances::default()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn withdraw_queue() -> Result<(), LibErrors> {
+        let mut vault = Vault::new_vault_for_tests()?;
+        let user_statement = &mut UserStatement::default();
+        let owner = [1; 32];
+
+        // lend only
+        vault.deposit(user_statement, Token::Base, Quantity::new(1000000), 2)?;
+        vault.lock_base(
+            Quantity::new(800000),
+            Quantity::new(1000000),
+            ServiceType::Lend,
+        )?;
+
+        assert_eq!(
+            vault.withdraw(user_statement, Token::Base, Quantity::new(500000), 2),
+            Err(LibErrors::NotEnoughBaseQuantity)
+        );
+
+        let (ticket, _) =
+            vault.request_withdraw(user_statement, owner, Token::Base, Quantity::new(500000), 2)?;
+
+        let strategy = vault.strategy(2)?;
+        assert_eq!(strategy.total_shares(), Shares::new(500000));
+        assert_eq!(strategy.balance(), Quantity::new(500000));
+        // available liquidity is set aside right away, out of reach of lending
+        assert_eq!(
+            strategy.withdraw_queue.pending(),
+            Balances {
+                base: Quantity::new(300000),
+                quote: Quantity::new(0),
+            }
+        );
+        assert_eq!(
+            vault.lend_service_not_mut()?.available().base,
+            Quantity::new(0)
+        );
+
+        assert_eq!(
+            vault.withdraw(user_statement, Token::Base, Quantity::new(100000), 2),
+            Err(LibErrors::NotEnoughBaseQuantity)
+        );
+        assert_eq!(
+            vault.claim_withdraw(owner, 2, ticket),
+            Err(LibErrors::WithdrawRequestNotFilled)
+        );
+        assert_eq!(
+            vault.claim_withdraw([2; 32], 2, ticket),
+            Err(LibErrors::WithdrawRequestNotOwned)
+        );
+
+        vault.unlock_base(
+            Quantity::new(400000),
+            Quantity::new(800000),
+            ServiceType::Lend,
+        )?;
+
+        let strategy = vault.strategy(2)?;
+        assert_eq!(strategy.withdraw_queue.pending(), Balances::default());
+        assert_eq!(strategy.available(), Quantity::new(100000));
+        assert_eq!(strategy.balance(), Quantity::new(500000));
+
+        assert_eq!(
+            vault.claim_withdraw(owner, 2, ticket)?,
+            Balances {
+                base: Quantity::new(500000),
+                quote: Quantity::new(1000000),
+            }
+        );
+        assert_eq!(
+            vault.claim_withdraw(owner, 2, ticket),
+            Err(LibErrors::WithdrawRequestNotFound)
+        );
+
+        // cancelled request is turned back into shares
+        let (ticket, _) =
+            vault.request_withdraw(user_statement, owner, Token::Base, Quantity::new(200000), 2)?;
+        assert_eq!(ticket, 2);
+        assert_eq!(
+            *user_statement
+                .search(&Position::LiquidityProvide {
+                    vault_index: 0,
+                    strategy_index: 2,
+                    shares: Shares::new(0),
+                    amount: Quantity::new(0),
+                    quote_amount: Quantity::new(0),
+                })?
+                .shares(),
+            Shares::new(300000)
+        );
+
+        assert_eq!(
+            vault.cancel_withdraw(user_statement, owner, 2, ticket)?,
+            Shares::new(200000)
+        );
+        assert_eq!(vault.strategy(2)?.total_shares(), Shares::new(500000));
+        assert_eq!(
+            vault.strategy(2)?.withdraw_queue.pending(),
+            Balances::default()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn cancel_partially_reserved() -> Result<(), LibErrors> {
+        let mut vault = Vault::new_vault_for_tests()?;
+        let user_statement = &mut UserStatement::default();
+        let owner = [1; 32];
+
+        vault.deposit(user_statement, Token::Base, Quantity::new(1000000), 2)?;
+        vault.lock_base(
+            Quantity::new(1000000),
+            Quantity::new(1000000),
+            ServiceType::Lend,
+        )?;
+
+        // whole strategy queued, nothing left to value the shares against
+        let (ticket, _) = vault.request_withdraw(
+            user_statement,
+            owner,
+            Token::Base,
+            Quantity::new(1000000),
+            2,
+        )?;
+        assert_eq!(vault.strategy(2)?.balance(), Quantity::new(0));
+
+        vault.unlock_base(
+            Quantity::new(400000),
+            Quantity::new(1000000),
+            ServiceType::Lend,
+        )?;
+        assert_eq!(
+            vault.strategy(2)?.withdraw_queue.requests[0].reserved.base,
+            Quantity::new(400000)
+        );
+
+        // burned shares are restored and the reserved part is back in the strategy
+        assert_eq!(
+            vault.cancel_withdraw(user_statement, owner, 2, ticket)?,
+            Shares::new(1000000)
+        );
+        let strategy = vault.strategy(2)?;
+        assert_eq!(strategy.available(), Quantity::new(400000));
+        assert_eq!(strategy.balance(), Quantity::new(1000000));
+        assert_eq!(
+            vault.lend_service_not_mut()?.available().base,
+            Quantity::new(400000)
+        );
+
+        Ok(())
+    }
+}
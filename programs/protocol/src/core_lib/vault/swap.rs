@@ -1,7 +1,11 @@
 use crate::core_lib::{
-    decimal::Quantity,
+    decimal::{Balances, Quantity},
     errors::LibErrors,
-    services::{swapping::Swap, ServiceType, ServiceUpdate, Services},
+    services::{
+        swapping::{KeptFeeBucket, Swap},
+        ServiceType, ServiceUpdate, Services,
+    },
+    strategy::Strategy,
     structs::Oracle,
 };
 
@@ -23,15 +27,82 @@ impl Vault {
         Ok((swap, oracle, quote_oracle))
     }
 
+    /// Available liquidity of strategies which share it with swap service without swapping themselves,
+    /// quantities owed to their queued withdrawals are left out
+    pub fn shared_swap_liquidity(&self) -> Balances {
+        let mut shared = Balances::default();
+
+        for i in self.strategies.indexes() {
+            if let Some(strategy) = self.strategies.get_checked(i) {
+                if strategy.shared_swap && !strategy.uses(ServiceType::Swap) {
+                    shared += strategy.withdrawable();
+                }
+            }
+        }
+
+        shared
+    }
+
+    /// Opts strategy in or out of sharing its available liquidity with swap service
+    pub fn set_shared_swap(&mut self, strategy_index: u8, shared: bool) -> Result<(), LibErrors> {
+        self.swap_service_not_mut()?;
+
+        let strategy = self.strategy_mut(strategy_index)?;
+
+        if strategy.uses(ServiceType::Swap) {
+            return Err(LibErrors::SharedSwapNotAllowed);
+        }
+
+        strategy.shared_swap = shared;
+
+        Ok(())
+    }
+
+    /// Swap service with shared liquidity added to its available balances
+    pub fn swap_with_shared(&self) -> Result<Swap, LibErrors> {
+        let mut swap = *self.swap_service_not_mut()?;
+        let shared = self.shared_swap_liquidity();
+
+        swap.add_available_base(shared.base);
+        swap.add_available_quote(shared.quote);
+
+        Ok(swap)
+    }
+
     pub fn sell(&mut self, quantity: Quantity) -> Result<Quantity, LibErrors> {
+        let shared = self.shared_swap_liquidity();
         let (swap, oracle, quote_oracle) = self.swap_and_oracles()?;
 
-        let quote_quantity = swap.sell(quantity, oracle, quote_oracle)?;
+        // shared liquidity is drawn only for the time of the swap
+        swap.add_available_base(shared.base);
+        swap.add_available_quote(shared.quote);
+
+        let earned_before = swap.total_earned_fee.quote;
+        let kept_before = swap.total_kept_fee.quote;
+        let quote_quantity = swap.sell(quantity, oracle, quote_oracle);
+        let earned = swap.total_earned_fee.quote - earned_before;
+        let kept = swap.total_kept_fee.quote - kept_before;
         let total_available = swap.available();
-        self.exchange_to_quote(
+
+        swap.remove_available_base(shared.base);
+        swap.remove_available_quote(shared.quote);
+        let quote_quantity = quote_quantity?;
+
+        // kept insurance part of the fee feeds the fund right away
+        let insured = swap.kept.sweep(KeptFeeBucket::Insurance);
+        self.insurance.deposit(insured);
+
+        self.record_earned_quote(
+            earned,
+            total_available.quote,
+            ServiceType::Swap,
+            Strategy::available_in_quote,
+        )?;
+        // strategies take the sold base and pay the output, kept fee leaves them for the buckets
+        self.exchange_to_base(
+            quote_quantity + kept,
             quantity,
-            quote_quantity,
-            total_available.base,
+            total_available.quote,
             ServiceType::Swap,
         )?;
 
@@ -39,14 +110,37 @@ impl Vault {
     }
 
     pub fn buy(&mut self, quantity: Quantity) -> Result<Quantity, LibErrors> {
+        let shared = self.shared_swap_liquidity();
         let (swap, oracle, quote_oracle) = self.swap_and_oracles()?;
 
-        let base_quantity = swap.buy(quantity, oracle, quote_oracle)?;
+        // shared liquidity is drawn only for the time of the swap
+        swap.add_available_base(shared.base);
+        swap.add_available_quote(shared.quote);
+
+        let earned_before = swap.total_earned_fee.base;
+        let kept_before = swap.total_kept_fee.base;
+        let base_quantity = swap.buy(quantity, oracle, quote_oracle);
+        let earned = swap.total_earned_fee.base - earned_before;
+        let kept = swap.total_kept_fee.base - kept_before;
         let total_available = swap.available();
-        self.exchange_to_base(
+
+        swap.remove_available_base(shared.base);
+        swap.remove_available_quote(shared.quote);
+        let base_quantity = base_quantity?;
+
+        let insured = swap.kept.sweep(KeptFeeBucket::Insurance);
+        self.insurance.deposit(insured);
+
+        self.record_earned_base(
+            earned,
+            total_available.base,
+            ServiceType::Swap,
+            Strategy::available_in,
+        )?;
+        self.exchange_to_quote(
+            base_quantity + kept,
             quantity,
-            base_quantity,
-            total_available.quote,
+            total_available.base,
             ServiceType::Swap,
         )?;
 
@@ -58,7 +152,8 @@ impl Vault {
 mod tests {
     use crate::core_lib::{
         decimal::{Balances, Fraction, Shares},
-        strategy::Strategy,
+        services::swapping::KeptFees,
+        strategy::{StrategyState, WithdrawQueue, Yields},
         user::UserStatement,
         Token,
     };
@@ -114,9 +209,12 @@ mod tests {
                     base: Quantity::new(0),
                     quote: Quantity::new(0)
                 },
+                kept: KeptFees::default(),
                 selling_fee,
                 buying_fee,
                 kept_fee: Fraction::from_scale(1, 1),
+                skew_target: Fraction::new(0),
+                skew_factor: Fraction::new(0),
             }
         );
 
@@ -141,6 +239,10 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields::default(),
             }
         );
         assert_eq!(
@@ -167,6 +269,10 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields::default(),
             }
         );
         assert_eq!(
@@ -187,6 +293,10 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields::default(),
             }
         );
 
@@ -222,9 +332,12 @@ mod tests {
                     base: Quantity::new(0),
                     quote: Quantity::new(0)
                 },
+                kept: KeptFees::default(),
                 selling_fee,
                 buying_fee,
                 kept_fee: Fraction::from_scale(1, 1),
+                skew_target: Fraction::new(0),
+                skew_factor: Fraction::new(0),
             }
         );
 
@@ -258,9 +371,12 @@ mod tests {
                     base: Quantity::new(0),
                     quote: Quantity::new(0)
                 },
+                kept: KeptFees::default(),
                 selling_fee,
                 buying_fee,
                 kept_fee: Fraction::from_scale(1, 1),
+                skew_target: Fraction::new(0),
+                skew_factor: Fraction::new(0),
             }
         );
 
@@ -294,9 +410,12 @@ mod tests {
                     base: Quantity::new(0),
                     quote: Quantity::new(0)
                 },
+                kept: KeptFees::default(),
                 selling_fee,
                 buying_fee,
                 kept_fee: Fraction::from_scale(1, 1),
+                skew_target: Fraction::new(0),
+                skew_factor: Fraction::new(0),
             }
         );
 
@@ -321,6 +440,10 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields::default(),
             }
         );
         assert_eq!(
@@ -347,6 +470,10 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields::default(),
             }
         );
         assert_eq!(
@@ -367,6 +494,10 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields::default(),
             }
         );
 
@@ -378,12 +509,12 @@ mod tests {
             *vault.swap_service()?,
             Swap {
                 available: Balances {
-                    base: Quantity::new(1900),
-                    quote: Quantity::new(4199)
+                    base: Quantity::new(2100),
+                    quote: Quantity::new(3801)
                 },
                 balances: Balances {
-                    base: Quantity::new(1900),
-                    quote: Quantity::new(4199)
+                    base: Quantity::new(2100),
+                    quote: Quantity::new(3801)
                 },
                 total_earned_fee: Balances {
                     base: Quantity::new(0),
@@ -397,9 +528,12 @@ mod tests {
                     base: Quantity::new(0),
                     quote: Quantity::new(0)
                 },
+                kept: KeptFees::default(),
                 selling_fee,
                 buying_fee,
                 kept_fee: Fraction::from_scale(1, 1),
+                skew_target: Fraction::new(0),
+                skew_factor: Fraction::new(0),
             }
         );
 
@@ -413,8 +547,8 @@ mod tests {
                 }),
                 traded: None,
                 available: Balances {
-                    base: Quantity::new(950),
-                    quote: Quantity::new(2099)
+                    base: Quantity::new(1050),
+                    quote: Quantity::new(1901)
                 },
                 locked: Balances {
                     base: Quantity::new(0),
@@ -424,6 +558,10 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields::default(),
             }
         );
         assert_eq!(
@@ -439,8 +577,8 @@ mod tests {
                     quote: Quantity::new(0)
                 }),
                 available: Balances {
-                    base: Quantity::new(950),
-                    quote: Quantity::new(2100)
+                    base: Quantity::new(1050),
+                    quote: Quantity::new(1900)
                 },
                 locked: Balances {
                     base: Quantity::new(0),
@@ -450,6 +588,16 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields {
+                    earned: Balances {
+                        base: Quantity::new(0),
+                        quote: Quantity::new(1)
+                    },
+                    ..Yields::default()
+                },
             }
         );
         assert_eq!(
@@ -470,6 +618,10 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields::default(),
             }
         );
 
@@ -480,12 +632,12 @@ mod tests {
             *vault.swap_service()?,
             Swap {
                 available: Balances {
-                    base: Quantity::new(2099),
-                    quote: Quantity::new(3799)
+                    base: Quantity::new(1901),
+                    quote: Quantity::new(4201)
                 },
                 balances: Balances {
-                    base: Quantity::new(2099),
-                    quote: Quantity::new(3799)
+                    base: Quantity::new(1901),
+                    quote: Quantity::new(4201)
                 },
                 total_earned_fee: Balances {
                     base: Quantity::new(1),
@@ -499,9 +651,12 @@ mod tests {
                     base: Quantity::new(0),
                     quote: Quantity::new(0)
                 },
+                kept: KeptFees::default(),
                 selling_fee,
                 buying_fee,
                 kept_fee: Fraction::from_scale(1, 1),
+                skew_target: Fraction::new(0),
+                skew_factor: Fraction::new(0),
             }
         );
 
@@ -515,8 +670,8 @@ mod tests {
                 }),
                 traded: None,
                 available: Balances {
-                    base: Quantity::new(1049),
-                    quote: Quantity::new(1900)
+                    base: Quantity::new(951),
+                    quote: Quantity::new(2101)
                 },
                 locked: Balances {
                     base: Quantity::new(0),
@@ -526,6 +681,10 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields::default(),
             }
         );
         assert_eq!(
@@ -541,8 +700,8 @@ mod tests {
                     quote: Quantity::new(0)
                 }),
                 available: Balances {
-                    base: Quantity::new(1050),
-                    quote: Quantity::new(1899)
+                    base: Quantity::new(950),
+                    quote: Quantity::new(2100)
                 },
                 locked: Balances {
                     base: Quantity::new(0),
@@ -552,6 +711,16 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields {
+                    earned: Balances {
+                        base: Quantity::new(1),
+                        quote: Quantity::new(1)
+                    },
+                    ..Yields::default()
+                },
             }
         );
         assert_eq!(
@@ -572,8 +741,141 @@ mod tests {
                 collateral_ratio: Fraction::from_integer(1),
                 accrued_fee: Quantity::new(0),
                 liquidation_threshold: Fraction::from_integer(1),
+                shared_swap: false,
+                state: StrategyState::Active,
+                withdraw_queue: WithdrawQueue::default(),
+                yields: Yields::default(),
+            }
+        );
+
+        Ok(())
+    }
+
+    /// Tokens the vault accounts for: strategies, kept fee buckets and insurance fund
+    fn accounted(vault: &Vault) -> Result<Balances, LibErrors> {
+        let mut total = vault.insurance.balance;
+
+        for strategy in vault.strategies.iter().unwrap() {
+            total += strategy.available + strategy.locked;
+        }
+
+        let kept = &vault.swap_service_not_mut()?.kept;
+        for bucket in [
+            KeptFeeBucket::Insurance,
+            KeptFeeBucket::Pol,
+            KeptFeeBucket::Burn,
+        ] {
+            total += kept.balance(bucket);
+        }
+
+        Ok(total)
+    }
+
+    #[test]
+    fn kept_fee_backed_by_reserves() -> Result<(), LibErrors> {
+        let mut vault = Vault::new_vault_for_tests()?;
+        let swap = vault.swap_service()?;
+        swap.fee_curve_sell()
+            .add_constant_fee(Fraction::from_scale(1, 2), Fraction::from_integer(1));
+        swap.fee_curve_buy()
+            .add_constant_fee(Fraction::from_scale(1, 2), Fraction::from_integer(1));
+        swap.kept
+            .set_split(Fraction::from_scale(3, 1), Fraction::from_scale(2, 1))?;
+
+        let user_statement = &mut UserStatement::default();
+        let deposited = vault.deposit(user_statement, Token::Base, Quantity::new(10_000000), 1)?;
+        let mut reserves = Balances {
+            base: deposited.base,
+            quote: deposited.quote,
+        };
+
+        let out = vault.sell(Quantity::new(1_000000))?;
+        reserves.base += Quantity::new(1_000000);
+        reserves.quote -= out;
+
+        let out = vault.buy(Quantity::new(3_000000))?;
+        reserves.quote += Quantity::new(3_000000);
+        reserves.base -= out;
+
+        let kept = vault.swap_service_not_mut()?.total_kept_fee;
+        assert!(!kept.base.is_zero() && !kept.quote.is_zero());
+        assert_eq!(accounted(&vault)?, reserves);
+
+        // sweeping a bucket moves out tokens no strategy claims
+        let swept = vault.swap_service()?.kept.sweep(KeptFeeBucket::Pol);
+        reserves -= swept;
+        assert_eq!(accounted(&vault)?, reserves);
+
+        Ok(())
+    }
+
+    #[test]
+    fn shared_liquidity() -> Result<(), LibErrors> {
+        let mut vault = Vault::new_vault_for_tests()?;
+        vault
+            .swap_service()?
+            .fee_curve_sell()
+            .add_constant_fee(Fraction::new(0), Fraction::from_integer(1));
+
+        let user_statement = &mut UserStatement::default();
+        vault.deposit(user_statement, Token::Base, Quantity::new(1000000), 0)?;
+        vault.deposit(user_statement, Token::Base, Quantity::new(10000000), 2)?;
+
+        assert_eq!(
+            vault.sell(Quantity::new(2000000)),
+            Err(LibErrors::NotEnoughQuoteQuantity)
+        );
+        assert_eq!(
+            vault.set_shared_swap(0, true),
+            Err(LibErrors::SharedSwapNotAllowed)
+        );
+
+        vault.set_shared_swap(2, true)?;
+        assert_eq!(
+            vault.swap_with_shared()?.available(),
+            Balances {
+                base: Quantity::new(11000000),
+                quote: Quantity::new(22000000),
+            }
+        );
+
+        assert_eq!(vault.sell(Quantity::new(2000000))?, Quantity::new(4000000));
+
+        // exchange is split between both strategies by their available quote
+        let shared = vault.strategy(2)?.available;
+        assert_eq!(
+            shared,
+            Balances {
+                base: Quantity::new(11818182),
+                quote: Quantity::new(16363636),
             }
         );
+        assert_eq!(
+            vault.swap_service_not_mut()?.available(),
+            vault.strategy(0)?.available
+        );
+        assert_eq!(
+            vault.lend_service_not_mut()?.available().base,
+            vault.strategy(0)?.available.base + shared.base
+        );
+
+        // second request waits behind the first one, its quote stays available but is not shared
+        let lend_available = vault.lend_service_not_mut()?.available().base;
+        vault.lock_base(lend_available, lend_available, ServiceType::Lend)?;
+        for _ in 0..2 {
+            vault.request_withdraw(
+                user_statement,
+                [1; 32],
+                Token::Base,
+                Quantity::new(1000000),
+                2,
+            )?;
+        }
+
+        let strategy = vault.strategy(2)?;
+        assert!(!strategy.withdraw_queue.pending().quote.is_zero());
+        assert!(strategy.withdrawable().quote < strategy.available.quote);
+        assert_eq!(vault.shared_swap_liquidity(), strategy.withdrawable());
 
         Ok(())
     }
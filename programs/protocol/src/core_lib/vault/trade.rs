@@ -1,13 +1,15 @@
 use crate::core_lib::{
-    decimal::{BalanceChange, Quantity, Time},
+    decimal::{BalanceChange, Balances, Quantity, Time},
     errors::LibErrors,
     services::{ServiceType, ServiceUpdate},
+    strategy::Strategy,
     structs::{Receipt, Side},
     user::{Position, UserStatement},
 };
 use checked_decimal_macro::Decimal;
+use std::cmp::min;
 
-use super::Vault;
+use super::{Token, Vault, VaultStatus};
 
 impl Vault {
     pub fn open_position(
@@ -15,12 +17,13 @@ impl Vault {
         user_statement: &mut UserStatement,
         quantity: Quantity,
         side: Side,
-    ) -> Result<(), LibErrors> {
+    ) -> Result<Receipt, LibErrors> {
         if quantity == Quantity::new(0) {
             return Err(LibErrors::ZeroAmountInput);
         }
 
-        let collateral = user_statement.permitted_debt();
+        // isolated collateral above its debt ceiling cannot back the position
+        let collateral = user_statement.permitted_borrow();
 
         let position_temp = Position::Trading {
             vault_index: self.id,
@@ -57,7 +60,7 @@ impl Vault {
 
         user_statement.add_position(position)?;
 
-        Ok(())
+        Ok(receipt)
     }
 
     pub fn close_position(
@@ -65,6 +68,29 @@ impl Vault {
         user_statement: &mut UserStatement,
         now: Time,
     ) -> Result<(BalanceChange, Side), LibErrors> {
+        let settlement = self.settle_position(user_statement, false)?;
+
+        Ok((settlement.change, settlement.side))
+    }
+
+    /// Settles the position of a delisted vault at the final price. A loss of the trader becomes
+    /// debt of the statement in base, the part that lent liquidity can't cover is forgiven
+    pub fn force_close_position(
+        &mut self,
+        user_statement: &mut UserStatement,
+    ) -> Result<Settlement, LibErrors> {
+        if self.status != VaultStatus::Delisting {
+            return Err(LibErrors::VaultNotDelisting);
+        }
+
+        self.settle_position(user_statement, true)
+    }
+
+    fn settle_position(
+        &mut self,
+        user_statement: &mut UserStatement,
+        forced: bool,
+    ) -> Result<Settlement, LibErrors> {
         let temp_position = Position::Trading {
             vault_index: self.id,
             receipt: Receipt::default(),
@@ -72,24 +98,63 @@ impl Vault {
 
         let (trade, oracle, quote_oracle) = self.trade_mut_and_oracles()?;
 
-        let (position_id, found_position) = user_statement.search_mut_id(&temp_position)?;
+        let (_, found_position) = user_statement.search_mut_id(&temp_position)?;
         let receipt = found_position.receipt();
 
-        let change = match receipt.side {
+        let mut debt = Quantity::new(0);
+        let mut forgiven = Quantity::new(0);
+
+        let (change, side) = match receipt.side {
             Side::Long => {
                 let total_locked = trade.locked().base;
+                let open_fee = receipt.locked * trade.open_fee;
                 let (change, unlock) = trade.close_long(receipt, oracle)?;
                 match change {
-                    BalanceChange::Profit(profit) => self.unlock_with_loss_base(
-                        unlock,
-                        profit,
-                        total_locked,
-                        ServiceType::Trade,
-                    )?,
+                    BalanceChange::Profit(profit) => {
+                        let threshold = unlock * self.insurance.profit_threshold;
+                        let loss = self.insure_loss(profit, threshold, Token::Base);
+
+                        self.record_lost_base(
+                            loss,
+                            total_locked,
+                            ServiceType::Trade,
+                            Strategy::locked_in,
+                        )?;
+                        self.unlock_with_loss_base(unlock, loss, total_locked, ServiceType::Trade)?
+                    }
                     BalanceChange::Loss(loss) => {
+                        let collected = match forced {
+                            true => {
+                                debt = self.book_debt(user_statement, loss)?;
+                                debt
+                            }
+                            false => loss,
+                        };
+                        forgiven = loss - collected;
+
+                        let cut = self.insurance.trade_fee_cut(min(collected, open_fee));
+                        self.insurance.deposit(Balances {
+                            base: cut,
+                            quote: Quantity::new(0),
+                        });
+                        let earned = collected - cut;
+
+                        // forgiven part is reported as earned and lost at once
+                        self.record_earned_base(
+                            earned + forgiven,
+                            total_locked,
+                            ServiceType::Trade,
+                            Strategy::locked_in,
+                        )?;
+                        self.record_lost_base(
+                            forgiven,
+                            total_locked,
+                            ServiceType::Trade,
+                            Strategy::locked_in,
+                        )?;
                         self.unlock_with_profit_base(
                             unlock,
-                            loss,
+                            earned,
                             total_locked,
                             ServiceType::Trade,
                         )?;
@@ -100,18 +165,88 @@ impl Vault {
             }
             Side::Short => {
                 let total_locked = trade.locked().quote;
+                let open_fee = receipt.locked * trade.open_fee;
                 let (change, unlock) = trade.close_short(&receipt, oracle, &quote_oracle)?;
                 match change {
-                    BalanceChange::Profit(profit) => self.unlock_with_loss_quote(
-                        unlock,
-                        profit,
-                        total_locked,
-                        ServiceType::Trade,
-                    )?,
+                    BalanceChange::Profit(profit) => {
+                        let threshold = unlock * self.insurance.profit_threshold;
+                        let loss = self.insure_loss(profit, threshold, Token::Quote);
+
+                        self.record_lost_quote(
+                            loss,
+                            total_locked,
+                            ServiceType::Trade,
+                            Strategy::locked_in_quote,
+                        )?;
+                        self.unlock_with_loss_quote(unlock, loss, total_locked, ServiceType::Trade)?
+                    }
+                    BalanceChange::Loss(loss) if forced => {
+                        // debt can only be taken in base, the loss is converted at the final price
+                        let owed = oracle
+                            .calculate_needed_quantity(quote_oracle.calculate_needed_value(loss));
+                        debt = self.book_debt(user_statement, owed)?;
+
+                        let collected = match debt == owed {
+                            true => loss,
+                            false => loss.big_mul_div(debt, owed),
+                        };
+                        forgiven = loss - collected;
+
+                        let cut = match collected.is_zero() {
+                            true => Quantity::new(0),
+                            false => debt.big_mul_div(
+                                self.insurance.trade_fee_cut(min(collected, open_fee)),
+                                collected,
+                            ),
+                        };
+                        self.insurance.deposit(Balances {
+                            base: cut,
+                            quote: Quantity::new(0),
+                        });
+                        let earned = debt - cut;
+
+                        self.record_earned_base(
+                            earned,
+                            total_locked,
+                            ServiceType::Trade,
+                            Strategy::locked_in_quote,
+                        )?;
+                        self.record_earned_quote(
+                            forgiven,
+                            total_locked,
+                            ServiceType::Trade,
+                            Strategy::locked_in_quote,
+                        )?;
+                        self.record_lost_quote(
+                            forgiven,
+                            total_locked,
+                            ServiceType::Trade,
+                            Strategy::locked_in_quote,
+                        )?;
+                        self.unlock_quote_with_profit_base(
+                            unlock,
+                            earned,
+                            total_locked,
+                            ServiceType::Trade,
+                        )?;
+                    }
                     BalanceChange::Loss(loss) => {
+                        let cut = self.insurance.trade_fee_cut(min(loss, open_fee));
+                        self.insurance.deposit(Balances {
+                            quote: cut,
+                            base: Quantity::new(0),
+                        });
+                        let earned = loss - cut;
+
+                        self.record_earned_quote(
+                            earned,
+                            total_locked,
+                            ServiceType::Trade,
+                            Strategy::locked_in_quote,
+                        )?;
                         self.unlock_with_profit_quote(
                             unlock,
-                            loss,
+                            earned,
                             total_locked,
                             ServiceType::Trade,
                         )?;
@@ -121,17 +256,36 @@ impl Vault {
             }
         };
 
+        // booked debt may have moved the position
+        let (position_id, _) = user_statement.search_mut_id(&temp_position)?;
         user_statement.delete_position(position_id);
 
-        Ok(change)
+        Ok(Settlement {
+            change,
+            side,
+            debt,
+            forgiven,
+        })
     }
 }
 
+/// Outcome of settling a trading position
+#[derive(Debug, PartialEq)]
+pub struct Settlement {
+    pub change: BalanceChange,
+    pub side: Side,
+    /// base booked as debt of the statement for a loss of a force closed trader
+    pub debt: Quantity,
+    /// part of the loss that was not collected, in the token of the position
+    pub forgiven: Quantity,
+}
+
 #[cfg(test)]
 mod vault_trade {
     use super::*;
     use crate::core_lib::{
-        decimal::{DecimalPlaces, Fraction, Price, Utilization},
+        decimal::{DecimalPlaces, Fraction, Price, Shares, Utilization},
+        services::swapping::KeptFeeBucket,
         structs::FeeCurve,
         Token,
     };
@@ -244,6 +398,138 @@ mod vault_trade {
         Ok(())
     }
 
+    fn accounted(vault: &Vault) -> Result<Balances, LibErrors> {
+        let mut total = vault.insurance.balance;
+
+        for strategy in vault.strategies.iter().unwrap() {
+            total += strategy.available + strategy.locked;
+        }
+
+        let kept = &vault.swap_service_not_mut()?.kept;
+        for bucket in [
+            KeptFeeBucket::Insurance,
+            KeptFeeBucket::Pol,
+            KeptFeeBucket::Burn,
+        ] {
+            total += kept.balance(bucket);
+        }
+
+        Ok(total)
+    }
+
+    fn assert_backed(vault: &Vault, reserves: Balances) -> Result<(), LibErrors> {
+        let accounted = accounted(vault)?;
+        assert!(accounted.base <= reserves.base, "base not backed");
+        assert!(accounted.quote <= reserves.quote, "quote not backed");
+        Ok(())
+    }
+
+    #[test]
+    fn insurance_backed_by_reserves() -> Result<(), LibErrors> {
+        let mut user = UserStatement::default();
+        let mut vault = test_vault(&mut user)?;
+        user.refresh(&[vault])?;
+        let mut reserves = accounted(&vault)?;
+
+        vault
+            .insurance
+            .set_params(Fraction::from_scale(5, 1), Fraction::from_scale(1, 2))?;
+        let swap = vault.swap_service()?;
+        swap.fee_curve_sell()
+            .add_constant_fee(Fraction::from_scale(1, 2), Fraction::from_integer(1));
+        swap.fee_curve_buy()
+            .add_constant_fee(Fraction::from_scale(1, 2), Fraction::from_integer(1));
+
+        // kept insurance part of swap fees feeds the fund
+        let out = vault.buy(Quantity::new(100_000000))?;
+        reserves.quote += Quantity::new(100_000000);
+        reserves.base -= out;
+        let out = vault.sell(Quantity::new(50_000000))?;
+        reserves.base += Quantity::new(50_000000);
+        reserves.quote -= out;
+        assert!(!vault.insurance.balance.base.is_zero());
+        assert_backed(&vault, reserves)?;
+
+        // fund covers the trader profit above the threshold
+        let covered_before = vault.insurance.total_covered.base;
+        vault.open_position(&mut user, Quantity::new(2000000), Side::Long)?;
+        vault
+            .oracle_mut()?
+            .update(Price::new(2100000000), Price::new(2000000), 0)?;
+        let (balance_change, _) = vault.close_position(&mut user, 0)?;
+        reserves.base -= balance_change.quantity();
+        assert!(vault.insurance.total_covered.base > covered_before);
+        assert_backed(&vault, reserves)?;
+
+        // trader loss is paid in, part of it feeds the fund
+        vault.open_position(&mut user, Quantity::new(2000000), Side::Long)?;
+        vault
+            .oracle_mut()?
+            .update(Price::new(1900000000), Price::new(2000000), 0)?;
+        let (balance_change, _) = vault.close_position(&mut user, 0)?;
+        reserves.base += balance_change.quantity();
+        assert_backed(&vault, reserves)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn insured_long() -> Result<(), LibErrors> {
+        let mut user = UserStatement::default();
+        let mut vault = test_vault(&mut user)?;
+        user.refresh(&[vault])?;
+
+        vault
+            .insurance
+            .set_params(Fraction::from_scale(5, 1), Fraction::from_scale(1, 2))?;
+        vault.insurance.deposit(Balances {
+            base: Quantity::new(50000),
+            quote: Quantity::new(0),
+        });
+
+        let mut sum_before = Quantity::new(0);
+        for strategy in vault.strategies.iter().unwrap() {
+            sum_before += strategy.available();
+        }
+
+        vault.open_position(&mut user, Quantity::new(2000000), Side::Long)?;
+        vault
+            .oracle_mut()?
+            .update(Price::new(2100000000), Price::new(2000000), 0)?;
+        let (balance_change, _) = vault.close_position(&mut user, 0)?;
+
+        let mut sum = Quantity::new(0);
+        for strategy in vault.strategies.iter().unwrap() {
+            sum += strategy.available();
+        }
+
+        // strategies bear 1% of the unlocked 2000000, fund covers the excess up to its balance
+        assert_eq!(balance_change, BalanceChange::Profit(Quantity::new(95038)));
+        assert_eq!(sum, sum_before - Quantity::new(95038 - 50000));
+        assert_eq!(vault.insurance.balance.base, Quantity::new(0));
+        assert_eq!(vault.insurance.total_covered.base, Quantity::new(50000));
+
+        // trader loses, half of the open fee goes to the fund
+        vault.open_position(&mut user, Quantity::new(2000000), Side::Long)?;
+        vault
+            .oracle_mut()?
+            .update(Price::new(1900000000), Price::new(2000000), 0)?;
+        let (balance_change, _) = vault.close_position(&mut user, 0)?;
+
+        let mut sum_after = Quantity::new(0);
+        for strategy in vault.strategies.iter().unwrap() {
+            sum_after += strategy.available();
+        }
+
+        assert_eq!(vault.insurance.balance.base, Quantity::new(100));
+        assert_eq!(
+            sum_after,
+            sum + balance_change.quantity() - Quantity::new(100)
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn check_unlock_loss_long() -> Result<(), LibErrors> {
         let mut user = UserStatement::default();
@@ -264,17 +550,126 @@ mod vault_trade {
         let (balance_change, side) = vault.close_position(&mut user, 0)?;
 
         let mut sum = Quantity::new(0);
+        let mut earned = Quantity::new(0);
         for strategy in vault.strategies.iter().unwrap() {
             sum += strategy.available();
+            earned += strategy.yields.earned.base;
         }
 
         assert_eq!(balance_change.quantity(), Quantity::new(105464));
         assert_eq!(sum, sum_before + balance_change.quantity());
+        assert_eq!(earned, balance_change.quantity());
         assert_eq!(side, Side::Long);
 
         Ok(())
     }
 
+    #[test]
+    fn force_close_loss_long() -> Result<(), LibErrors> {
+        let mut user = UserStatement::default();
+        let mut vault = test_vault(&mut user)?;
+        user.refresh(&[vault])?;
+        let reserves = accounted(&vault)?;
+
+        vault.open_position(&mut user, Quantity::new(2000000), Side::Long)?;
+        vault
+            .oracle_mut()?
+            .update(Price::new(1900000000), Price::new(2000000), 0)?;
+
+        assert_eq!(
+            vault.force_close_position(&mut user),
+            Err(LibErrors::VaultNotDelisting)
+        );
+        vault.delist()?;
+        let settlement = vault.force_close_position(&mut user)?;
+
+        // the loss is owed by the statement, strategies hold it as lent liquidity
+        assert_eq!(
+            settlement.change,
+            BalanceChange::Loss(Quantity::new(105464))
+        );
+        assert_eq!(settlement.side, Side::Long);
+        assert_eq!(settlement.debt, Quantity::new(105464));
+        assert_eq!(settlement.forgiven, Quantity::new(0));
+        assert_eq!(
+            *user
+                .search(&Position::Borrow {
+                    vault_index: vault.id,
+                    shares: Shares::new(0),
+                    amount: Quantity::new(0),
+                })?
+                .amount(),
+            settlement.debt
+        );
+        let lent = vault.lend_service_not_mut()?.borrowed();
+        assert_eq!(accounted(&vault)?.base, reserves.base + lent);
+        assert_eq!(
+            vault.force_close_position(&mut user),
+            Err(LibErrors::PositionNotFound)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn force_close_loss_short() -> Result<(), LibErrors> {
+        let mut user = UserStatement::default();
+        let mut vault = test_vault(&mut user)?;
+        user.refresh(&[vault])?;
+        let reserves = accounted(&vault)?;
+
+        vault.open_position(&mut user, Quantity::new(2000000), Side::Short)?;
+        vault
+            .oracle_mut()?
+            .update(Price::new(2100000000), Price::new(2000000), 0)?;
+        vault.delist()?;
+        let settlement = vault.force_close_position(&mut user)?;
+
+        // quote loss is converted to base debt at the final price
+        assert_eq!(
+            settlement.change,
+            BalanceChange::Loss(Quantity::new(200400))
+        );
+        assert_eq!(settlement.debt, Quantity::new(95429));
+        assert_eq!(settlement.forgiven, Quantity::new(0));
+        let lent = vault.lend_service_not_mut()?.borrowed();
+        assert_eq!(accounted(&vault)?.base, reserves.base + lent);
+        assert_eq!(accounted(&vault)?.quote, reserves.quote);
+
+        Ok(())
+    }
+
+    #[test]
+    fn force_close_without_lending_forgives() -> Result<(), LibErrors> {
+        let mut user = UserStatement::default();
+        let mut vault = test_vault(&mut user)?;
+        user.refresh(&[vault])?;
+        let reserves = accounted(&vault)?;
+
+        vault.open_position(&mut user, Quantity::new(2000000), Side::Long)?;
+        vault
+            .oracle_mut()?
+            .update(Price::new(1900000000), Price::new(2000000), 0)?;
+        vault.delist()?;
+
+        // nothing left to lend, the loss can't become debt
+        let available = vault.lend_service_not_mut()?.available().base;
+        vault.lend_service()?.remove_available_base(available);
+
+        let settlement = vault.force_close_position(&mut user)?;
+        assert_eq!(settlement.debt, Quantity::new(0));
+        assert_eq!(settlement.forgiven, Quantity::new(105464));
+        assert_eq!(accounted(&vault)?, reserves);
+
+        let mut lost = Quantity::new(0);
+        for strategy in vault.strategies.iter().unwrap() {
+            lost += strategy.yields.lost.base;
+        }
+        assert_eq!(lost, settlement.forgiven);
+
+        Ok(())
+    }
+
     #[test]
     fn unlock_with_short_profit() -> Result<(), LibErrors> {
         let mut user = UserStatement::default();
@@ -295,12 +690,15 @@ mod vault_trade {
         let (balance_change, side) = vault.close_position(&mut user, 0)?;
 
         let mut sum = Quantity::new(0);
+        let mut lost = Quantity::new(0);
         for strategy in vault.strategies.iter().unwrap() {
             sum += strategy.available_quote();
+            lost += strategy.yields.lost.quote;
         }
 
         assert_eq!(balance_change.quantity(), Quantity::new(199600));
         assert_eq!(sum, sum_before - balance_change.quantity());
+        assert_eq!(lost, balance_change.quantity());
         assert_eq!(side, Side::Short);
 
         Ok(())
@@ -0,0 +1,322 @@
+use crate::core_lib::{decimal::Balances, Vault};
+use anchor_lang::prelude::*;
+use checked_decimal_macro::Decimal;
+
+#[event]
+pub struct DepositEvent {
+    pub vault: u8,
+    pub strategy: u8,
+    pub owner: Pubkey,
+    pub base: u64,
+    pub quote: u64,
+    pub shares: u128,
+    pub base_price: u64,
+    pub quote_price: u64,
+    pub utilization: u128,
+}
+
+#[event]
+pub struct WithdrawEvent {
+    pub vault: u8,
+    pub strategy: u8,
+    pub owner: Pubkey,
+    pub base: u64,
+    pub quote: u64,
+    pub shares: u128,
+    pub base_price: u64,
+    pub quote_price: u64,
+    pub utilization: u128,
+}
+
+#[event]
+pub struct WithdrawRequestedEvent {
+    pub vault: u8,
+    pub strategy: u8,
+    pub owner: Pubkey,
+    pub ticket: u64,
+    /// quantities owed by the ticket, they leave the statement right away
+    pub base: u64,
+    pub quote: u64,
+}
+
+#[event]
+pub struct WithdrawClaimedEvent {
+    pub vault: u8,
+    pub strategy: u8,
+    pub owner: Pubkey,
+    pub ticket: u64,
+    pub base: u64,
+    pub quote: u64,
+}
+
+#[event]
+pub struct WithdrawCancelledEvent {
+    pub vault: u8,
+    pub strategy: u8,
+    pub owner: Pubkey,
+    pub ticket: u64,
+    /// shares given back to the statement
+    pub shares: u128,
+}
+
+#[event]
+pub struct SharesWrappedEvent {
+    pub vault: u8,
+    pub strategy: u8,
+    pub owner: Pubkey,
+    pub shares: u64,
+}
+
+#[event]
+pub struct SharesUnwrappedEvent {
+    pub vault: u8,
+    pub strategy: u8,
+    pub owner: Pubkey,
+    pub shares: u64,
+}
+
+#[event]
+pub struct BorrowEvent {
+    pub vault: u8,
+    pub owner: Pubkey,
+    /// borrowed quantity including the initial fee
+    pub amount: u64,
+    pub shares: u128,
+    pub base_price: u64,
+    pub utilization: u128,
+}
+
+#[event]
+pub struct RepayEvent {
+    pub vault: u8,
+    pub owner: Pubkey,
+    pub payer: Pubkey,
+    pub repaid: u64,
+    pub interest: u64,
+    pub principal: u64,
+    pub shares: u128,
+    pub base_price: u64,
+    pub utilization: u128,
+}
+
+#[event]
+pub struct SwapEvent {
+    pub vault: u8,
+    pub owner: Pubkey,
+    /// whether base token was sold for quote
+    pub sell: bool,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// fee charged in the output token, kept part included
+    pub fee: u64,
+    pub base_price: u64,
+    pub quote_price: u64,
+    pub utilization: u128,
+}
+
+#[event]
+pub struct DoubleSwapEvent {
+    pub vault_in: u8,
+    pub vault_out: u8,
+    pub owner: Pubkey,
+    pub amount_in: u64,
+    /// quote quantity passed between the vaults
+    pub amount_quote: u64,
+    pub amount_out: u64,
+    pub price_in: u64,
+    pub price_out: u64,
+}
+
+#[event]
+pub struct OpenPositionEvent {
+    pub vault: u8,
+    pub owner: Pubkey,
+    pub long: bool,
+    pub size: u64,
+    pub locked: u64,
+    pub open_price: u64,
+    pub quote_price: u64,
+}
+
+#[event]
+pub struct ClosePositionEvent {
+    pub vault: u8,
+    pub owner: Pubkey,
+    pub long: bool,
+    /// profit of the trader, zero when the position lost
+    pub profit: u64,
+    /// loss of the trader, zero when the position earned
+    pub loss: u64,
+    pub base_price: u64,
+    pub quote_price: u64,
+}
+
+#[event]
+pub struct LendFeesSettledEvent {
+    pub vault: u8,
+    pub settled: u64,
+    pub utilization: u128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DebtWrittenOffEvent {
+    pub vault: u8,
+    pub statement: Pubkey,
+    pub debt: u64,
+    pub covered: u64,
+    pub socialized: u64,
+    pub shares: u128,
+}
+
+#[event]
+pub struct PositionSettledEvent {
+    pub vault: u8,
+    pub owner: Pubkey,
+    pub long: bool,
+    pub profit: u64,
+    /// base booked as debt of the statement for the loss of the trader
+    pub debt: u64,
+    /// loss of the trader that lent liquidity could not cover, in the token of the position
+    pub forgiven: u64,
+    pub base_price: u64,
+    pub quote_price: u64,
+}
+
+#[event]
+pub struct KeptFeeSweptEvent {
+    pub vault: u8,
+    pub base: u64,
+    pub quote: u64,
+}
+
+#[event]
+pub struct KeptFeeBurnedEvent {
+    pub vault: u8,
+    pub base: u64,
+    pub quote: u64,
+}
+
+#[event]
+pub struct OracleEnabledEvent {
+    pub vault: u8,
+    pub base: bool,
+    pub price_feed: Pubkey,
+    pub max_update_interval: u32,
+}
+
+#[event]
+pub struct OracleOverriddenEvent {
+    pub vault: u8,
+    pub base: bool,
+    pub price: u64,
+    pub confidence: u64,
+    pub last_update: u32,
+}
+
+#[event]
+pub struct VaultDelistedEvent {
+    pub vault: u8,
+    /// final prices positions are settled at
+    pub base_price: u64,
+    pub quote_price: u64,
+}
+
+#[event]
+pub struct VaultClosedEvent {
+    pub vault: u8,
+    /// leftovers swept from the reserves before closing them
+    pub base: u64,
+    pub quote: u64,
+}
+
+#[event]
+pub struct StatementTransferredEvent {
+    pub statement: Pubkey,
+    pub owner: Pubkey,
+    pub new_owner: Pubkey,
+}
+
+#[event]
+pub struct StatementClosedEvent {
+    pub statement: Pubkey,
+    pub owner: Pubkey,
+}
+
+#[event]
+pub struct PauseChangedEvent {
+    /// vault the flag applies to, protocol-wide if none
+    pub vault: Option<u8>,
+    pub operation: u8,
+    pub paused: bool,
+}
+
+#[event]
+pub struct AdminProposedEvent {
+    pub pending_admin: Option<Pubkey>,
+}
+
+#[event]
+pub struct RoleChangedEvent {
+    pub role: u8,
+    pub authority: Pubkey,
+}
+
+#[event]
+pub struct ChangeScheduledEvent {
+    pub id: u64,
+    pub eta: u32,
+    pub kind: u8,
+    pub vault: u8,
+    pub strategy: u8,
+    pub values: [u64; 6],
+}
+
+#[event]
+pub struct ChangeExecutedEvent {
+    pub id: u64,
+    pub kind: u8,
+    pub vault: u8,
+    pub strategy: u8,
+    pub values: [u64; 6],
+}
+
+#[event]
+pub struct ChangeCancelledEvent {
+    pub id: u64,
+}
+
+#[event]
+pub struct ParamsChangedEvent {
+    pub vault: u8,
+    /// strategy the change applies to, if any
+    pub strategy: Option<u8>,
+    /// name of the admin instruction
+    pub param: String,
+    /// raw arguments of the instruction
+    pub values: Vec<u64>,
+}
+
+/// Oracle prices of the vault, zero when an oracle is missing
+pub fn prices(vault: &Vault) -> (u64, u64) {
+    (
+        vault.oracle().map_or(0, |oracle| oracle.price.val),
+        vault.quote_oracle().map_or(0, |oracle| oracle.price.val),
+    )
+}
+
+/// Utilization of lending in the vault, zero without lending service
+pub fn utilization(vault: &Vault) -> u128 {
+    vault
+        .lend_service_not_mut()
+        .map_or(0, |lend| lend.utilization.get())
+}
+
+/// Fees charged by swaps of the vault so far, kept part included
+pub fn swap_fees(vault: &Vault) -> Balances {
+    vault
+        .swap_service_not_mut()
+        .map_or(Balances::default(), |swap| {
+            swap.total_earned_fee + swap.total_kept_fee
+        })
+}
@@ -0,0 +1,244 @@
+use super::IndexerError;
+use sha2::{Digest, Sha256};
+
+/// Instructions of the program that change state of vaults or statements
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    Deposit {
+        vault: u8,
+        strategy: u8,
+        quantity: u64,
+        base: bool,
+    },
+    Withdraw {
+        vault: u8,
+        strategy: u8,
+        quantity: u64,
+        base: bool,
+    },
+    SingleSwap {
+        vault: u8,
+        amount: u64,
+        min_expected: u64,
+        from_base: bool,
+        by_amount_out: bool,
+    },
+    Borrow {
+        vault: u8,
+        amount: u64,
+    },
+    Repay {
+        vault: u8,
+        amount: u64,
+    },
+    OpenPosition {
+        vault: u8,
+        amount: u64,
+        long: bool,
+    },
+    ClosePosition {
+        vault: u8,
+    },
+    /// Instruction that is not replayed, state it changes has to be loaded from a snapshot
+    Unsupported([u8; 8]),
+}
+
+/// Anchor discriminator of the instruction with given name
+pub fn discriminator(name: &str) -> [u8; 8] {
+    let hash = Sha256::digest(format!("global:{}", name).as_bytes());
+
+    let mut discriminator = [0u8; 8];
+    discriminator.copy_from_slice(&hash[..8]);
+    discriminator
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], IndexerError> {
+        if self.data.len() < len {
+            return Err(IndexerError::DataTooShort);
+        }
+
+        let (taken, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, IndexerError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u64(&mut self) -> Result<u64, IndexerError> {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(self.take(8)?);
+        Ok(u64::from_le_bytes(bytes))
+    }
+
+    fn bool(&mut self) -> Result<bool, IndexerError> {
+        Ok(self.u8()? != 0)
+    }
+}
+
+impl Instruction {
+    /// Decodes instruction data: discriminator followed by borsh encoded arguments
+    pub fn decode(data: &[u8]) -> Result<Self, IndexerError> {
+        let mut reader = Reader { data };
+
+        let mut tag = [0u8; 8];
+        tag.copy_from_slice(reader.take(8)?);
+        let r = &mut reader;
+
+        Ok(match tag {
+            tag if tag == discriminator("deposit") => Self::Deposit {
+                vault: r.u8()?,
+                strategy: r.u8()?,
+                quantity: r.u64()?,
+                base: r.bool()?,
+            },
+            tag if tag == discriminator("withdraw") => Self::Withdraw {
+                vault: r.u8()?,
+                strategy: r.u8()?,
+                quantity: r.u64()?,
+                base: r.bool()?,
+            },
+            tag if tag == discriminator("single_swap") => Self::SingleSwap {
+                vault: r.u8()?,
+                amount: r.u64()?,
+                min_expected: r.u64()?,
+                from_base: r.bool()?,
+                by_amount_out: r.bool()?,
+            },
+            tag if tag == discriminator("borrow") => Self::Borrow {
+                vault: r.u8()?,
+                amount: r.u64()?,
+            },
+            tag if tag == discriminator("repay") => Self::Repay {
+                vault: r.u8()?,
+                amount: r.u64()?,
+            },
+            tag if tag == discriminator("open_position") => Self::OpenPosition {
+                vault: r.u8()?,
+                amount: r.u64()?,
+                long: r.bool()?,
+            },
+            tag if tag == discriminator("close_position") => Self::ClosePosition { vault: r.u8()? },
+            tag => Self::Unsupported(tag),
+        })
+    }
+
+    /// Encodes the instruction the same way the program expects it
+    pub fn encode(&self) -> Vec<u8> {
+        let (name, mut args) = match *self {
+            Self::Deposit {
+                vault,
+                strategy,
+                quantity,
+                base,
+            } => ("deposit", Self::lp_args(vault, strategy, quantity, base)),
+            Self::Withdraw {
+                vault,
+                strategy,
+                quantity,
+                base,
+            } => ("withdraw", Self::lp_args(vault, strategy, quantity, base)),
+            Self::SingleSwap {
+                vault,
+                amount,
+                min_expected,
+                from_base,
+                by_amount_out,
+            } => {
+                let mut args = vec![vault];
+                args.extend(amount.to_le_bytes());
+                args.extend(min_expected.to_le_bytes());
+                args.extend([from_base as u8, by_amount_out as u8]);
+                ("single_swap", args)
+            }
+            Self::Borrow { vault, amount } => {
+                let mut args = vec![vault];
+                args.extend(amount.to_le_bytes());
+                ("borrow", args)
+            }
+            Self::Repay { vault, amount } => {
+                let mut args = vec![vault];
+                args.extend(amount.to_le_bytes());
+                ("repay", args)
+            }
+            Self::OpenPosition {
+                vault,
+                amount,
+                long,
+            } => {
+                let mut args = vec![vault];
+                args.extend(amount.to_le_bytes());
+                args.push(long as u8);
+                ("open_position", args)
+            }
+            Self::ClosePosition { vault } => ("close_position", vec![vault]),
+            Self::Unsupported(tag) => return tag.to_vec(),
+        };
+
+        let mut data = discriminator(name).to_vec();
+        data.append(&mut args);
+        data
+    }
+
+    fn lp_args(vault: u8, strategy: u8, quantity: u64, base: bool) -> Vec<u8> {
+        let mut args = vec![vault, strategy];
+        args.extend(quantity.to_le_bytes());
+        args.push(base as u8);
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode() -> Result<(), IndexerError> {
+        let instructions = [
+            Instruction::Deposit {
+                vault: 1,
+                strategy: 2,
+                quantity: 1000,
+                base: true,
+            },
+            Instruction::SingleSwap {
+                vault: 0,
+                amount: 500,
+                min_expected: 400,
+                from_base: false,
+                by_amount_out: false,
+            },
+            Instruction::OpenPosition {
+                vault: 3,
+                amount: 7,
+                long: true,
+            },
+            Instruction::ClosePosition { vault: 3 },
+        ];
+
+        for instruction in instructions {
+            assert_eq!(Instruction::decode(&instruction.encode())?, instruction);
+        }
+
+        let tag = discriminator("init_vault");
+        assert_eq!(Instruction::decode(&tag)?, Instruction::Unsupported(tag));
+
+        let data = Instruction::Borrow {
+            vault: 0,
+            amount: 10,
+        }
+        .encode();
+        assert!(matches!(
+            Instruction::decode(&data[..12]),
+            Err(IndexerError::DataTooShort)
+        ));
+
+        Ok(())
+    }
+}
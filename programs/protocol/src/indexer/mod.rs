@@ -0,0 +1,499 @@
+//! Rebuilds histories of positions, trading results and fees by replaying instructions of the
+//! program on copies of the accounts, starting from snapshots of them
+
+mod instruction;
+mod sink;
+
+pub use instruction::*;
+pub use sink::*;
+
+use crate::{
+    core_lib::{
+        decimal::{BalanceChange, Quantity, Shares},
+        errors::LibErrors,
+        structs::Side,
+        user::UserStatement,
+        vault::VaultStatus,
+        LiquidityChange, Token,
+    },
+    structs::{Statement, Vaults},
+    ZeroCopyDecoder,
+};
+use checked_decimal_macro::Decimal;
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum IndexerError {
+    #[error("Instruction data is too short")]
+    DataTooShort,
+    #[error("Account data has unexpected size")]
+    AccountSize,
+    #[error("Price read by the transaction is missing")]
+    PriceMissing,
+    #[error("Swaps by amount out are not supported by the program")]
+    SwapByAmountOut,
+    #[error("State changed by an instruction that is not replayed, a snapshot has to be loaded")]
+    SnapshotRequired,
+    #[error(transparent)]
+    Lib(#[from] LibErrors),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+}
+
+/// Successful transaction of the program
+#[derive(Clone, Debug)]
+pub struct Transaction {
+    pub slot: u64,
+    pub timestamp: i64,
+    pub signer: [u8; 32],
+    pub data: Vec<u8>,
+    /// Prices of the oracles of refreshed vaults, decoded from the price feed accounts it read
+    pub prices: Vec<PriceUpdate>,
+}
+
+/// Price of an oracle of the vault, scaled by `10^exp` as in the price feed account
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PriceUpdate {
+    pub vault: u8,
+    pub base: bool,
+    pub price: i64,
+    pub conf: u64,
+    pub exp: i32,
+}
+
+pub struct Indexer<S: Sink> {
+    vaults: Vaults,
+    statements: HashMap<[u8; 32], UserStatement>,
+    sink: S,
+    /// set after an instruction that is not replayed, until vaults are loaded again
+    stale: bool,
+}
+
+impl<S: Sink> Indexer<S> {
+    pub fn new(sink: S) -> Self {
+        Self {
+            vaults: Vaults::default(),
+            statements: HashMap::new(),
+            sink,
+            stale: false,
+        }
+    }
+
+    /// Replaces state of vaults with a snapshot of the account, needed after instructions that are not replayed
+    pub fn load_vaults(&mut self, data: &Vec<u8>) -> Result<(), IndexerError> {
+        if data.len() != std::mem::size_of::<Vaults>() {
+            return Err(IndexerError::AccountSize);
+        }
+
+        self.vaults = *ZeroCopyDecoder::decode::<Vaults>(data);
+        self.stale = false;
+        Ok(())
+    }
+
+    pub fn load_statement(&mut self, data: &Vec<u8>) -> Result<(), IndexerError> {
+        if data.len() != std::mem::size_of::<Statement>() {
+            return Err(IndexerError::AccountSize);
+        }
+
+        let account = ZeroCopyDecoder::decode::<Statement>(data);
+        self.statements.insert(account.owner, account.statement);
+        Ok(())
+    }
+
+    pub fn vaults(&self) -> &Vaults {
+        &self.vaults
+    }
+
+    pub fn statement(&self, owner: &[u8; 32]) -> Option<&UserStatement> {
+        self.statements.get(owner)
+    }
+
+    pub fn sink(&mut self) -> &mut S {
+        &mut self.sink
+    }
+
+    /// Applies the transaction the same way its handler does, with the prices it read.
+    /// Instructions that are not replayed fail with `SnapshotRequired` until vaults and statements
+    /// they changed are loaded again
+    pub fn replay(&mut self, transaction: &Transaction) -> Result<(), IndexerError> {
+        let instruction = Instruction::decode(&transaction.data)?;
+
+        if let Instruction::Unsupported(..) = instruction {
+            self.stale = true;
+        }
+        if self.stale {
+            return Err(IndexerError::SnapshotRequired);
+        }
+
+        let Self {
+            vaults,
+            statements,
+            sink,
+            ..
+        } = self;
+        let Transaction {
+            slot,
+            timestamp,
+            signer: owner,
+            ..
+        } = *transaction;
+
+        let statement = statements.entry(owner).or_default();
+
+        let vault_index = match instruction {
+            Instruction::Deposit { vault, .. }
+            | Instruction::Withdraw { vault, .. }
+            | Instruction::SingleSwap { vault, .. }
+            | Instruction::Borrow { vault, .. }
+            | Instruction::Repay { vault, .. }
+            | Instruction::OpenPosition { vault, .. }
+            | Instruction::ClosePosition { vault } => vault,
+            Instruction::Unsupported(..) => unreachable!(),
+        };
+
+        if let Instruction::SingleSwap {
+            by_amount_out: true,
+            ..
+        } = instruction
+        {
+            return Err(IndexerError::SwapByAmountOut);
+        }
+
+        let indexes = match instruction {
+            Instruction::Deposit { .. } | Instruction::SingleSwap { .. } => {
+                HashSet::from([vault_index])
+            }
+            _ => statement.get_vaults_indexes(&vault_index),
+        };
+        Self::refresh(vaults, sink, &indexes, &transaction.prices, slot, timestamp)?;
+
+        if !matches!(
+            instruction,
+            Instruction::Deposit { .. }
+                | Instruction::Withdraw { .. }
+                | Instruction::SingleSwap { .. }
+        ) {
+            statement.refresh(&vaults.arr.elements)?;
+        }
+
+        let vault = vaults.vault_checked_mut(vault_index)?;
+        let position =
+            |strategy, action, base: Quantity, quote: Quantity, shares: Shares| Row::Position {
+                slot,
+                owner,
+                vault: vault_index,
+                strategy,
+                action,
+                base: base.get(),
+                quote: quote.get(),
+                shares: shares.get(),
+            };
+
+        let row = match instruction {
+            Instruction::Deposit {
+                strategy,
+                quantity,
+                base,
+                ..
+            }
+            | Instruction::Withdraw {
+                strategy,
+                quantity,
+                base,
+                ..
+            } => {
+                let token = if base { Token::Base } else { Token::Quote };
+                let quantity = Quantity::new(quantity);
+
+                let (
+                    action,
+                    LiquidityChange {
+                        base,
+                        quote,
+                        shares,
+                    },
+                ) = match instruction {
+                    Instruction::Deposit { .. } => (
+                        "deposit",
+                        vault.deposit(statement, token, quantity, strategy)?,
+                    ),
+                    _ => (
+                        "withdraw",
+                        vault.withdraw(statement, token, quantity, strategy)?,
+                    ),
+                };
+
+                position(Some(strategy), action, base, quote, shares)
+            }
+            Instruction::SingleSwap {
+                amount, from_base, ..
+            } => {
+                let quantity = Quantity::new(amount);
+
+                if from_base {
+                    position(
+                        None,
+                        "sell",
+                        quantity,
+                        vault.sell(quantity)?,
+                        Shares::new(0),
+                    )
+                } else {
+                    position(None, "buy", vault.buy(quantity)?, quantity, Shares::new(0))
+                }
+            }
+            Instruction::Borrow { amount, .. } => {
+                let (borrowed, shares) = vault.borrow(statement, Quantity::new(amount))?;
+
+                position(None, "borrow", borrowed, Quantity::new(0), shares)
+            }
+            Instruction::Repay { amount, .. } => {
+                let repayment = vault.repay(statement, Quantity::new(amount))?;
+
+                position(
+                    None,
+                    "repay",
+                    repayment.repaid,
+                    Quantity::new(0),
+                    repayment.shares,
+                )
+            }
+            Instruction::OpenPosition { amount, long, .. } => {
+                let side = if long { Side::Long } else { Side::Short };
+                let receipt = vault.open_position(statement, Quantity::new(amount), side)?;
+
+                position(None, "open", receipt.size, receipt.locked, Shares::new(0))
+            }
+            Instruction::ClosePosition { .. } => {
+                let (balance_change, side) = vault.close_position(statement, timestamp as u32)?;
+
+                let (profit, loss) = match balance_change {
+                    BalanceChange::Profit(profit) => (profit.get(), 0),
+                    BalanceChange::Loss(loss) => (0, loss.get()),
+                };
+
+                Row::Pnl {
+                    slot,
+                    owner,
+                    vault: vault_index,
+                    long: side == Side::Long,
+                    profit,
+                    loss,
+                }
+            }
+            Instruction::Unsupported(..) => unreachable!(),
+        };
+
+        sink.write(row)?;
+        Ok(())
+    }
+
+    /// Settles fees and updates oracles of the vaults, same as `Vaults::refresh` of the program
+    fn refresh(
+        vaults: &mut Vaults,
+        sink: &mut S,
+        indexes: &HashSet<u8>,
+        prices: &[PriceUpdate],
+        slot: u64,
+        timestamp: i64,
+    ) -> Result<(), IndexerError> {
+        for index in indexes {
+            let vault = vaults.vault_checked_mut(*index)?;
+            let settled = vault.refresh(timestamp as u32)?;
+
+            if !settled.is_zero() {
+                sink.write(Row::Fee {
+                    slot,
+                    vault: *index,
+                    kind: "lend",
+                    quantity: settled.get(),
+                })?;
+            }
+
+            // oracles of delisted vaults keep the final price
+            if vault.status != VaultStatus::Active {
+                continue;
+            }
+
+            let oracles = [(true, &mut vault.oracle), (false, &mut vault.quote_oracle)];
+
+            for (base, oracle) in oracles {
+                if let Some(oracle) = oracle {
+                    let update = prices
+                        .iter()
+                        .find(|update| update.vault == *index && update.base == base)
+                        .ok_or(IndexerError::PriceMissing)?;
+
+                    oracle.update_scaled(update.price, update.conf, update.exp, timestamp)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_lib::{decimal::Price, Vault};
+    use checked_decimal_macro::Factories;
+
+    #[test]
+    fn replay() -> Result<(), IndexerError> {
+        let owner = [1; 32];
+        let mut vaults = Vaults::default();
+        vaults
+            .arr
+            .add(Vault::new_vault_for_tests()?)
+            .map_err(|_| LibErrors::AddVault)?;
+
+        let mut indexer = Indexer::new(Vec::new());
+        indexer.load_vaults(&ZeroCopyDecoder::encode(&vaults).to_vec())?;
+        assert!(matches!(
+            indexer.load_vaults(&vec![0; 8]),
+            Err(IndexerError::AccountSize)
+        ));
+
+        let mut prices = vec![
+            PriceUpdate {
+                vault: 0,
+                base: true,
+                price: 2000,
+                conf: 5,
+                exp: -3,
+            },
+            PriceUpdate {
+                vault: 0,
+                base: false,
+                price: 1000,
+                conf: 1,
+                exp: -3,
+            },
+        ];
+        let transaction = |slot, instruction: Instruction, prices: &Vec<PriceUpdate>| Transaction {
+            slot,
+            timestamp: 0,
+            signer: owner,
+            data: instruction.encode(),
+            prices: prices.clone(),
+        };
+
+        let deposit = Instruction::Deposit {
+            vault: 0,
+            strategy: 1,
+            quantity: 1000000,
+            base: true,
+        };
+        assert!(matches!(
+            indexer.replay(&transaction(0, deposit, &vec![])),
+            Err(IndexerError::PriceMissing)
+        ));
+        indexer.replay(&transaction(0, deposit, &prices))?;
+
+        // nothing is replayed until a snapshot is loaded
+        let open = Instruction::OpenPosition {
+            vault: 0,
+            amount: 100000,
+            long: true,
+        };
+        let unsupported = Instruction::Unsupported(discriminator("init_vault"));
+        assert!(matches!(
+            indexer.replay(&transaction(1, unsupported, &prices)),
+            Err(IndexerError::SnapshotRequired)
+        ));
+        assert!(matches!(
+            indexer.replay(&transaction(2, open, &prices)),
+            Err(IndexerError::SnapshotRequired)
+        ));
+
+        let snapshot = ZeroCopyDecoder::encode(indexer.vaults()).to_vec();
+        indexer.load_vaults(&snapshot)?;
+        indexer.replay(&transaction(2, open, &prices))?;
+        indexer.replay(&transaction(
+            3,
+            Instruction::ClosePosition { vault: 0 },
+            &prices,
+        ))?;
+
+        let sell = Instruction::SingleSwap {
+            vault: 0,
+            amount: 1000,
+            min_expected: 0,
+            from_base: true,
+            by_amount_out: true,
+        };
+        assert!(matches!(
+            indexer.replay(&transaction(4, sell, &prices)),
+            Err(IndexerError::SwapByAmountOut)
+        ));
+
+        // base price goes up to 3
+        prices[0].price = 3000;
+        let borrow = Instruction::Borrow {
+            vault: 0,
+            amount: 1000,
+        };
+        indexer.replay(&transaction(4, borrow, &prices))?;
+        assert_eq!(
+            indexer.vaults().arr.elements[0].oracle()?.price,
+            Price::from_integer(3)
+        );
+
+        assert_eq!(
+            indexer.sink().clone(),
+            vec![
+                Row::Position {
+                    slot: 0,
+                    owner,
+                    vault: 0,
+                    strategy: Some(1),
+                    action: "deposit",
+                    base: 1000000,
+                    quote: 2000000,
+                    shares: 1000000,
+                },
+                Row::Position {
+                    slot: 2,
+                    owner,
+                    vault: 0,
+                    strategy: None,
+                    action: "open",
+                    base: 100000,
+                    quote: 100000,
+                    shares: 0,
+                },
+                Row::Pnl {
+                    slot: 3,
+                    owner,
+                    vault: 0,
+                    long: true,
+                    profit: 0,
+                    loss: 500,
+                },
+                Row::Position {
+                    slot: 4,
+                    owner,
+                    vault: 0,
+                    strategy: None,
+                    action: "borrow",
+                    base: 1000,
+                    quote: 0,
+                    shares: 1000,
+                },
+            ]
+        );
+        // the trade is closed, the liquidity and the borrow are left
+        assert_eq!(
+            indexer
+                .statement(&owner)
+                .ok_or(LibErrors::PositionNotFound)?
+                .positions
+                .head,
+            2
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// Histories with their columns and sqlite types, amounts are kept as text since shares do not fit
+/// 64 bit integers
+const TABLES: [(&str, &[(&str, &str)]); 3] = [
+    (
+        "positions",
+        &[
+            ("slot", "INTEGER"),
+            ("owner", "TEXT"),
+            ("vault", "INTEGER"),
+            ("strategy", "INTEGER"),
+            ("action", "TEXT"),
+            ("base", "TEXT"),
+            ("quote", "TEXT"),
+            ("shares", "TEXT"),
+        ],
+    ),
+    (
+        "pnl",
+        &[
+            ("slot", "INTEGER"),
+            ("owner", "TEXT"),
+            ("vault", "INTEGER"),
+            ("long", "INTEGER"),
+            ("profit", "TEXT"),
+            ("loss", "TEXT"),
+        ],
+    ),
+    (
+        "fees",
+        &[
+            ("slot", "INTEGER"),
+            ("vault", "INTEGER"),
+            ("kind", "TEXT"),
+            ("quantity", "TEXT"),
+        ],
+    ),
+];
+
+/// Single entry of a rebuilt history
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Row {
+    /// Change of a liquidity, borrow or trading position, or a swap done by the owner
+    Position {
+        slot: u64,
+        owner: [u8; 32],
+        vault: u8,
+        strategy: Option<u8>,
+        action: &'static str,
+        base: u64,
+        quote: u64,
+        shares: u128,
+    },
+    /// Realized result of a closed trading position
+    Pnl {
+        slot: u64,
+        owner: [u8; 32],
+        vault: u8,
+        long: bool,
+        profit: u64,
+        loss: u64,
+    },
+    /// Fees earned by strategies of the vault
+    Fee {
+        slot: u64,
+        vault: u8,
+        kind: &'static str,
+        quantity: u64,
+    },
+}
+
+/// Value of a single column
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Cell {
+    Null,
+    Integer(u64),
+    Text(String),
+}
+
+impl Cell {
+    fn csv(&self) -> String {
+        match self {
+            Self::Null => String::new(),
+            Self::Integer(value) => value.to_string(),
+            Self::Text(text) => text.clone(),
+        }
+    }
+
+    fn sql(&self) -> String {
+        match self {
+            Self::Null => "NULL".to_string(),
+            Self::Integer(value) => value.to_string(),
+            Self::Text(text) => format!("'{}'", text.replace('\'', "''")),
+        }
+    }
+}
+
+impl Row {
+    pub fn table(&self) -> &'static str {
+        match self {
+            Self::Position { .. } => "positions",
+            Self::Pnl { .. } => "pnl",
+            Self::Fee { .. } => "fees",
+        }
+    }
+
+    pub fn columns(&self) -> Vec<Cell> {
+        let text = |value: &dyn ToString| Cell::Text(value.to_string());
+
+        match self {
+            Self::Position {
+                slot,
+                owner,
+                vault,
+                strategy,
+                action,
+                base,
+                quote,
+                shares,
+            } => vec![
+                Cell::Integer(*slot),
+                Cell::Text(to_hex(owner)),
+                Cell::Integer(*vault as u64),
+                strategy.map_or(Cell::Null, |s| Cell::Integer(s as u64)),
+                text(action),
+                text(base),
+                text(quote),
+                text(shares),
+            ],
+            Self::Pnl {
+                slot,
+                owner,
+                vault,
+                long,
+                profit,
+                loss,
+            } => vec![
+                Cell::Integer(*slot),
+                Cell::Text(to_hex(owner)),
+                Cell::Integer(*vault as u64),
+                Cell::Integer(*long as u64),
+                text(profit),
+                text(loss),
+            ],
+            Self::Fee {
+                slot,
+                vault,
+                kind,
+                quantity,
+            } => vec![
+                Cell::Integer(*slot),
+                Cell::Integer(*vault as u64),
+                text(kind),
+                text(quantity),
+            ],
+        }
+    }
+}
+
+fn to_hex(key: &[u8; 32]) -> String {
+    key.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Destination of rows produced by the indexer
+pub trait Sink {
+    fn write(&mut self, row: Row) -> io::Result<()>;
+}
+
+impl Sink for Vec<Row> {
+    fn write(&mut self, row: Row) -> io::Result<()> {
+        self.push(row);
+        Ok(())
+    }
+}
+
+/// Writes each history into its own csv file in the given directory
+pub struct CsvSink {
+    positions: BufWriter<File>,
+    pnl: BufWriter<File>,
+    fees: BufWriter<File>,
+}
+
+impl CsvSink {
+    pub fn create(dir: &Path) -> io::Result<Self> {
+        let open = |table: usize| -> io::Result<BufWriter<File>> {
+            let (name, columns) = TABLES[table];
+            let mut file = BufWriter::new(File::create(dir.join(format!("{}.csv", name)))?);
+            let header: Vec<_> = columns.iter().map(|(column, _)| *column).collect();
+            writeln!(file, "{}", header.join(","))?;
+            Ok(file)
+        };
+
+        Ok(Self {
+            positions: open(0)?,
+            pnl: open(1)?,
+            fees: open(2)?,
+        })
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.positions.flush()?;
+        self.pnl.flush()?;
+        self.fees.flush()
+    }
+}
+
+impl Sink for CsvSink {
+    fn write(&mut self, row: Row) -> io::Result<()> {
+        let file = match row {
+            Row::Position { .. } => &mut self.positions,
+            Row::Pnl { .. } => &mut self.pnl,
+            Row::Fee { .. } => &mut self.fees,
+        };
+        let columns: Vec<_> = row.columns().iter().map(Cell::csv).collect();
+
+        writeln!(file, "{}", columns.join(","))
+    }
+}
+
+/// Writes the histories as a sqlite script creating and filling their tables,
+/// `sqlite3 history.db < history.sql` loads it into a database
+pub struct SqliteSink<W: Write> {
+    script: W,
+}
+
+impl SqliteSink<BufWriter<File>> {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?))
+    }
+}
+
+impl<W: Write> SqliteSink<W> {
+    pub fn new(mut script: W) -> io::Result<Self> {
+        for (name, columns) in TABLES {
+            let columns: Vec<_> = columns
+                .iter()
+                .map(|(column, kind)| format!("{} {}", column, kind))
+                .collect();
+
+            writeln!(
+                script,
+                "CREATE TABLE IF NOT EXISTS {} ({});",
+                name,
+                columns.join(", ")
+            )?;
+        }
+
+        Ok(Self { script })
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.script.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.script
+    }
+}
+
+impl<W: Write> Sink for SqliteSink<W> {
+    fn write(&mut self, row: Row) -> io::Result<()> {
+        let values: Vec<_> = row.columns().iter().map(Cell::sql).collect();
+
+        writeln!(
+            self.script,
+            "INSERT INTO {} VALUES ({});",
+            row.table(),
+            values.join(", ")
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqlite_script() -> io::Result<()> {
+        let mut sink = SqliteSink::new(Vec::new())?;
+
+        sink.write(Row::Position {
+            slot: 1,
+            owner: [0xab; 32],
+            vault: 0,
+            strategy: None,
+            action: "borrow",
+            base: 10,
+            quote: 0,
+            shares: u64::MAX as u128 + 1,
+        })?;
+        sink.write(Row::Pnl {
+            slot: 2,
+            owner: [0; 32],
+            vault: 1,
+            long: true,
+            profit: 0,
+            loss: 5,
+        })?;
+
+        let script = String::from_utf8(sink.into_inner()).unwrap_or_default();
+        let lines: Vec<_> = script.lines().collect();
+
+        assert_eq!(
+            lines[0],
+            "CREATE TABLE IF NOT EXISTS positions (slot INTEGER, owner TEXT, vault INTEGER, \
+             strategy INTEGER, action TEXT, base TEXT, quote TEXT, shares TEXT);"
+        );
+        assert_eq!(
+            lines[3],
+            format!(
+                "INSERT INTO positions VALUES (1, '{}', 0, NULL, 'borrow', '10', '0', '18446744073709551616');",
+                "ab".repeat(32)
+            )
+        );
+        assert_eq!(
+            lines[4],
+            format!(
+                "INSERT INTO pnl VALUES (2, '{}', 1, 1, '0', '5');",
+                "00".repeat(32)
+            )
+        );
+
+        Ok(())
+    }
+}
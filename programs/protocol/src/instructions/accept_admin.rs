@@ -0,0 +1,28 @@
+use crate::{
+    events::RoleChangedEvent,
+    structs::{Role, State},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    pub new_admin: Signer<'info>,
+}
+
+impl AcceptAdmin<'_> {
+    pub fn handler(ctx: Context<AcceptAdmin>) -> anchor_lang::Result<()> {
+        msg!("DotWave: Accept admin");
+
+        let authority = ctx.accounts.new_admin.key();
+        ctx.accounts.state.load_mut()?.accept_admin(authority)?;
+
+        emit!(RoleChangedEvent {
+            role: Role::Admin as u8,
+            authority,
+        });
+
+        Ok(())
+    }
+}
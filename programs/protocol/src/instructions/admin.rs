@@ -1,21 +1,37 @@
 use crate::{
     core_lib::{
-        decimal::{Factories, Fraction, Price, Quantity, Utilization},
+        decimal::{Factories, Fraction, Price, Quantity, Utilization, Value},
         errors::LibErrors,
-        structs::FeeCurve,
+        strategy::StrategyState,
+        structs::{fee_curve::CurveSegment, ChangeKind, FeeCurve, Operation},
+        Vault,
     },
-    structs::{State, Vaults},
+    events::{
+        AdminProposedEvent, ChangeCancelledEvent, ChangeScheduledEvent, OracleOverriddenEvent,
+        ParamsChangedEvent, PauseChangedEvent, RoleChangedEvent,
+    },
+    structs::{Role, State, Vaults},
 };
 use anchor_lang::prelude::*;
 use checked_decimal_macro::{BetweenDecimals, Decimal};
 
+/// Segment of a fee curve, `bound` is the upper utilization bound of the segment.
+/// `Kinked` segment starts at the bound of the previous one
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub enum FeeCurveSegment {
+    Constant { c: u64, bound: u64 },
+    Linear { a: u64, b: u64, bound: u64 },
+    Kinked { a: u64, b: u64, bound: u64 },
+}
+
 #[derive(Accounts)]
 pub struct Admin<'info> {
     #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
     pub state: AccountLoader<'info, State>,
     #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
     pub vaults: AccountLoader<'info, Vaults>,
-    #[account(mut, constraint = admin.key() == state.load()?.admin)]
+    /// admin or holder of the role required by the instruction
+    #[account(mut)]
     pub admin: Signer<'info>,
 }
 
@@ -30,6 +46,7 @@ impl Admin<'_> {
         time: Option<u32>, // can be overridden as well
     ) -> anchor_lang::Result<()> {
         msg!("DotWave: Force override oracle");
+        self.authorize(Role::OracleManager)?;
 
         let vaults = &mut self.vaults.load_mut()?;
         let vault = vaults.vault_checked_mut(index)?;
@@ -68,6 +85,14 @@ impl Admin<'_> {
 
         oracle.update(price, confidence, time)?;
 
+        emit!(OracleOverriddenEvent {
+            vault: index,
+            base,
+            price: oracle.price.val,
+            confidence: oracle.confidence.val,
+            last_update: oracle.last_update,
+        });
+
         Ok(())
     }
 
@@ -79,6 +104,7 @@ impl Admin<'_> {
         initial_fee_time: u32,
     ) -> anchor_lang::Result<()> {
         msg!("DotWave: Enabling lending");
+        self.authorize(Role::RiskManager)?;
 
         let vaults = &mut self.vaults.load_mut()?;
         let vault = vaults.vault_checked_mut(index)?;
@@ -91,6 +117,17 @@ impl Admin<'_> {
             Clock::get()?.unix_timestamp as u32,
         )?;
 
+        Self::params_changed(
+            vault,
+            None,
+            "enable_lending",
+            vec![
+                max_utilization as u64,
+                max_total_borrow,
+                initial_fee_time as u64,
+            ],
+        );
+
         Ok(())
     }
 
@@ -101,6 +138,7 @@ impl Admin<'_> {
         _max_total_sold: u64,
     ) -> anchor_lang::Result<()> {
         msg!("DotWave: Enabling swapping");
+        self.authorize(Role::RiskManager)?;
 
         let vaults = &mut self.vaults.load_mut()?;
         let vault = vaults.vault_checked_mut(index)?;
@@ -111,6 +149,8 @@ impl Admin<'_> {
             Fraction::new(kept_fee as u64),
         )?;
 
+        Self::params_changed(vault, None, "enable_swapping", vec![kept_fee as u64]);
+
         Ok(())
     }
 
@@ -123,6 +163,7 @@ impl Admin<'_> {
         liquidation_threshold: u32,
     ) -> anchor_lang::Result<()> {
         msg!("DotWave: Enabling trading");
+        self.authorize(Role::RiskManager)?;
 
         let vaults = &mut self.vaults.load_mut()?;
         let vault = vaults.vault_checked_mut(index)?;
@@ -135,6 +176,18 @@ impl Admin<'_> {
             Clock::get()?.unix_timestamp as u32,
         )?;
 
+        Self::params_changed(
+            vault,
+            None,
+            "enable_trading",
+            vec![
+                open_fee as u64,
+                max_leverage as u64,
+                collateral_ratio as u64,
+                liquidation_threshold as u64,
+            ],
+        );
+
         Ok(())
     }
 
@@ -149,25 +202,497 @@ impl Admin<'_> {
         c: u64,
     ) -> Result<()> {
         msg!("DotWave: Modify fee curve");
+        self.authorize(Role::RiskManager)?;
 
         let vaults = &mut self.vaults.load_mut()?;
         let vault = vaults.vault_checked_mut(vault)?;
+        vault.check_setup()?;
 
-        let curve = match (service, base) {
-            (1, true) => vault.lend_service()?.fee_curve(),
-            (2, true) => vault.swap_service()?.fee_curve_sell(),
-            (2, false) => vault.swap_service()?.fee_curve_buy(),
-            _ => return Err(LibErrors::InvalidService.into()),
-        };
+        let curve = vault.fee_curve_mut(service, base)?;
+
+        if curve.is_full() {
+            return Err(LibErrors::InvalidFeeCurve.into());
+        }
 
         let bound = Fraction::new(bound);
 
+        // fee = a * u^2 + b * u + c, quadratic segments are not supported
         match (a, b, c) {
             (0, 0, c) => curve.add_constant_fee(Fraction::new(c), bound),
-            (0, b, c) => curve.add_linear_fee(Fraction::new(c), Fraction::new(b), bound),
-            _ => unimplemented!("fee not yet implemented"),
+            (0, b, c) => curve.add_linear_fee(Fraction::new(b), Fraction::new(c), bound),
+            _ => return Err(LibErrors::InvalidFeeCurve.into()),
         };
 
+        Self::params_changed(
+            vault,
+            None,
+            "fee_curve",
+            vec![service as u64, base as u64, bound.get() as u64, a, b, c],
+        );
+
+        Ok(())
+    }
+
+    pub fn set_fee_curve(
+        &self,
+        vault: u8,
+        service: u8,
+        base: bool,
+        segments: Vec<FeeCurveSegment>,
+    ) -> Result<()> {
+        msg!("DotWave: Set fee curve");
+        self.authorize(Role::RiskManager)?;
+
+        self.replace_fee_curve(vault, service, base, Self::fee_curve_from(&segments)?)
+    }
+
+    /// Queues replacement of the fee curve, the way to change it once the vault has users
+    pub fn schedule_fee_curve(
+        &self,
+        vault: u8,
+        service: u8,
+        base: bool,
+        segments: Vec<FeeCurveSegment>,
+    ) -> Result<()> {
+        msg!("DotWave: Schedule fee curve");
+        self.authorize(Role::RiskManager)?;
+
+        let curve = Self::fee_curve_from(&segments)?;
+        // fails early for services the vault doesn't have
+        self.vaults
+            .load_mut()?
+            .vault_checked_mut(vault)?
+            .fee_curve_mut(service, base)?;
+
+        let values = [service as u64, base as u64, 0, 0, 0, 0];
+        let timelock = &mut self.state.load_mut()?.timelock;
+        let id = timelock.schedule_with_curve(
+            ChangeKind::FeeCurve,
+            vault,
+            0,
+            values,
+            curve,
+            Clock::get()?.unix_timestamp as u32,
+        )?;
+
+        emit!(ChangeScheduledEvent {
+            id,
+            eta: timelock.get(id)?.eta,
+            kind: ChangeKind::FeeCurve as u8,
+            vault,
+            strategy: 0,
+            values,
+        });
+
+        Ok(())
+    }
+
+    fn fee_curve_from(segments: &[FeeCurveSegment]) -> Result<FeeCurve> {
+        let mut previous_bound = Fraction::new(0);
+        let segments = segments
+            .iter()
+            .map(|segment| {
+                let (curve_segment, bound) = match *segment {
+                    FeeCurveSegment::Constant { c, bound } => (
+                        CurveSegment::Constant {
+                            c: Fraction::new(c),
+                        },
+                        bound,
+                    ),
+                    FeeCurveSegment::Linear { a, b, bound } => (
+                        CurveSegment::Linear {
+                            a: Fraction::new(a),
+                            b: Fraction::new(b),
+                        },
+                        bound,
+                    ),
+                    FeeCurveSegment::Kinked { a, b, bound } => (
+                        CurveSegment::Kinked {
+                            a: Fraction::new(a),
+                            b: Fraction::new(b),
+                            kink: previous_bound,
+                        },
+                        bound,
+                    ),
+                };
+                previous_bound = Fraction::new(bound);
+
+                (curve_segment, previous_bound)
+            })
+            .collect::<Vec<_>>();
+
+        Ok(FeeCurve::from_segments(&segments)?)
+    }
+
+    pub fn set_jump_rate_curve(
+        &self,
+        vault: u8,
+        service: u8,
+        base: bool,
+        base_fee: u64,
+        slope: u64,
+        kink: u64,
+        jump_slope: u64,
+    ) -> Result<()> {
+        msg!("DotWave: Set jump rate curve");
+        self.authorize(Role::RiskManager)?;
+
+        let new_curve = FeeCurve::jump_rate(
+            Fraction::new(base_fee),
+            Fraction::new(slope),
+            Fraction::new(kink),
+            Fraction::new(jump_slope),
+        )?;
+
+        self.replace_fee_curve(vault, service, base, new_curve)
+    }
+
+    fn replace_fee_curve(
+        &self,
+        vault: u8,
+        service: u8,
+        base: bool,
+        new_curve: FeeCurve,
+    ) -> Result<()> {
+        let vaults = &mut self.vaults.load_mut()?;
+        let vault = vaults.vault_checked_mut(vault)?;
+        vault.check_setup()?;
+
+        // fees accrued so far are settled with the previous curve
+        vault.refresh(Clock::get()?.unix_timestamp as u32)?;
+
+        *vault.fee_curve_mut(service, base)? = new_curve;
+
+        Self::params_changed(vault, None, "fee_curve", vec![service as u64, base as u64]);
+
+        Ok(())
+    }
+
+    fn authorize(&self, role: Role) -> Result<()> {
+        Ok(self.state.load()?.authorize(&self.admin.key(), role)?)
+    }
+
+    fn params_changed(vault: &Vault, strategy: Option<u8>, param: &str, values: Vec<u64>) {
+        emit!(ParamsChangedEvent {
+            vault: vault.id,
+            strategy,
+            param: param.to_string(),
+            values,
+        });
+    }
+
+    pub fn set_debt_ceiling(&self, vault: u8, debt_ceiling: Option<u64>) -> Result<()> {
+        msg!("DotWave: Set debt ceiling");
+        self.authorize(Role::RiskManager)?;
+
+        let vaults = &mut self.vaults.load_mut()?;
+        let vault = vaults.vault_checked_mut(vault)?;
+        vault.check_setup()?;
+
+        vault.set_debt_ceiling(debt_ceiling.map(|ceiling| Value::new(ceiling as u128)));
+
+        Self::params_changed(
+            vault,
+            None,
+            "debt_ceiling",
+            debt_ceiling.into_iter().collect(),
+        );
+
+        Ok(())
+    }
+
+    /// Limits value leaving the vault within the window, zero window or limit disables them
+    pub fn set_outflow_limits(
+        &self,
+        vault: u8,
+        window: u32,
+        vault_limit: u64,
+        statement_limit: u64,
+    ) -> Result<()> {
+        msg!("DotWave: Set outflow limits");
+        self.authorize(Role::RiskManager)?;
+
+        let vaults = &mut self.vaults.load_mut()?;
+        let vault = vaults.vault_checked_mut(vault)?;
+
+        vault.outflow.set(
+            window,
+            Value::new(vault_limit as u128),
+            Value::new(statement_limit as u128),
+        );
+
+        Self::params_changed(
+            vault,
+            None,
+            "outflow_limits",
+            vec![window as u64, vault_limit, statement_limit],
+        );
+
+        Ok(())
+    }
+
+    pub fn set_kept_fee_split(&self, vault: u8, pol_part: u64, burn_part: u64) -> Result<()> {
+        msg!("DotWave: Set kept fee split");
+        self.authorize(Role::Treasury)?;
+
+        let vaults = &mut self.vaults.load_mut()?;
+        let vault = vaults.vault_checked_mut(vault)?;
+
+        vault
+            .swap_service()?
+            .kept
+            .set_split(Fraction::new(pol_part), Fraction::new(burn_part))?;
+
+        Self::params_changed(vault, None, "kept_fee_split", vec![pol_part, burn_part]);
+
+        Ok(())
+    }
+
+    pub fn set_inventory_skew(&self, vault: u8, target: u64, factor: u64) -> Result<()> {
+        msg!("DotWave: Set inventory skew");
+        self.authorize(Role::RiskManager)?;
+
+        let vaults = &mut self.vaults.load_mut()?;
+        let vault = vaults.vault_checked_mut(vault)?;
+        vault.check_setup()?;
+
+        vault
+            .swap_service()?
+            .set_inventory_skew(Fraction::new(target), Fraction::new(factor))?;
+
+        Self::params_changed(vault, None, "inventory_skew", vec![target, factor]);
+
+        Ok(())
+    }
+
+    pub fn set_shared_swap(&self, vault: u8, strategy: u8, shared: bool) -> Result<()> {
+        msg!("DotWave: Set shared swap");
+        self.authorize(Role::RiskManager)?;
+
+        let vaults = &mut self.vaults.load_mut()?;
+        let vault = vaults.vault_checked_mut(vault)?;
+
+        vault.set_shared_swap(strategy, shared)?;
+
+        Self::params_changed(vault, Some(strategy), "shared_swap", vec![shared as u64]);
+
+        Ok(())
+    }
+
+    pub fn set_strategy_risk_params(
+        &self,
+        vault: u8,
+        strategy: u8,
+        collateral_ratio: u64,
+        liquidation_threshold: u64,
+    ) -> Result<()> {
+        msg!("DotWave: Set strategy risk params");
+        self.authorize(Role::RiskManager)?;
+
+        let vaults = &mut self.vaults.load_mut()?;
+        let vault = vaults.vault_checked_mut(vault)?;
+        vault.check_setup()?;
+
+        vault.strategy_mut(strategy)?.set_risk_params(
+            Fraction::new(collateral_ratio),
+            Fraction::new(liquidation_threshold),
+        )?;
+
+        Self::params_changed(
+            vault,
+            Some(strategy),
+            "strategy_risk_params",
+            vec![collateral_ratio, liquidation_threshold],
+        );
+
+        Ok(())
+    }
+
+    pub fn set_strategy_services(
+        &self,
+        vault: u8,
+        strategy: u8,
+        lending: bool,
+        swapping: bool,
+        trading: bool,
+    ) -> Result<()> {
+        msg!("DotWave: Set strategy services");
+        self.authorize(Role::RiskManager)?;
+
+        let vaults = &mut self.vaults.load_mut()?;
+        let vault = vaults.vault_checked_mut(vault)?;
+        vault.check_setup()?;
+
+        vault.set_strategy_services(strategy, lending, swapping, trading)?;
+
+        Self::params_changed(
+            vault,
+            Some(strategy),
+            "strategy_services",
+            vec![lending as u64, swapping as u64, trading as u64],
+        );
+
+        Ok(())
+    }
+
+    pub fn set_strategy_state(&self, vault: u8, strategy: u8, state: u8) -> Result<()> {
+        msg!("DotWave: Set strategy state");
+        self.authorize(Role::RiskManager)?;
+
+        let vaults = &mut self.vaults.load_mut()?;
+        let vault = vaults.vault_checked_mut(vault)?;
+
+        let swept = vault.set_strategy_state(strategy, StrategyState::try_from(state)?)?;
+        msg!("swept dust: {} base, {} quote", swept.base, swept.quote);
+
+        Self::params_changed(vault, Some(strategy), "strategy_state", vec![state as u64]);
+
+        Ok(())
+    }
+
+    pub fn set_insurance_params(
+        &self,
+        vault: u8,
+        trade_fee_part: u64,
+        profit_threshold: u64,
+    ) -> Result<()> {
+        msg!("DotWave: Set insurance params");
+        self.authorize(Role::RiskManager)?;
+
+        let vaults = &mut self.vaults.load_mut()?;
+        let vault = vaults.vault_checked_mut(vault)?;
+
+        vault.insurance.set_params(
+            Fraction::new(trade_fee_part),
+            Fraction::new(profit_threshold),
+        )?;
+
+        Self::params_changed(
+            vault,
+            None,
+            "insurance_params",
+            vec![trade_fee_part, profit_threshold],
+        );
+
+        Ok(())
+    }
+
+    /// Pauses or resumes the operation in the vault, or in all of them when no vault is given
+    pub fn set_pause(&self, vault: Option<u8>, operation: u8, paused: bool) -> Result<()> {
+        msg!("DotWave: Set pause");
+        self.authorize(Role::Pauser)?;
+
+        let op = Operation::try_from(operation)?;
+
+        match vault {
+            Some(index) => self
+                .vaults
+                .load_mut()?
+                .vault_checked_mut(index)?
+                .pause
+                .set(op, paused),
+            None => self.state.load_mut()?.pause.set(op, paused),
+        }
+
+        emit!(PauseChangedEvent {
+            vault,
+            operation,
+            paused,
+        });
+
+        Ok(())
+    }
+
+    /// First step of the admin transfer, `None` withdraws the proposal
+    pub fn propose_admin(&self, pending_admin: Option<Pubkey>) -> Result<()> {
+        msg!("DotWave: Propose admin");
+        self.authorize(Role::Admin)?;
+
+        self.state.load_mut()?.propose_admin(pending_admin);
+
+        emit!(AdminProposedEvent { pending_admin });
+
+        Ok(())
+    }
+
+    pub fn set_role(&self, role: u8, authority: Pubkey) -> Result<()> {
+        msg!("DotWave: Set role");
+        self.authorize(Role::Admin)?;
+
+        self.state
+            .load_mut()?
+            .set_role(Role::try_from(role)?, authority)?;
+
+        emit!(RoleChangedEvent { role, authority });
+
+        Ok(())
+    }
+
+    /// Queues a risk parameter change, it can be executed by anyone once the timelock delay passes
+    pub fn schedule_change(
+        &self,
+        vault: u8,
+        strategy: u8,
+        kind: u8,
+        values: Vec<u64>,
+    ) -> Result<()> {
+        msg!("DotWave: Schedule change");
+
+        let change_kind = ChangeKind::try_from(kind)?;
+        // curves don't fit in the values, they are scheduled with `schedule_fee_curve`
+        if change_kind == ChangeKind::FeeCurve {
+            return Err(LibErrors::InvalidChangeKind.into());
+        }
+        self.authorize(match change_kind {
+            ChangeKind::Delay => Role::Admin,
+            _ => Role::RiskManager,
+        })?;
+
+        if change_kind != ChangeKind::Delay {
+            self.vaults.load()?.vault_checked(vault)?;
+        }
+
+        if values.len() > 6 {
+            return Err(LibErrors::DataTooLarge.into());
+        }
+        let mut padded = [0u64; 6];
+        padded[..values.len()].copy_from_slice(&values);
+
+        let timelock = &mut self.state.load_mut()?.timelock;
+        let id = timelock.schedule(
+            change_kind,
+            vault,
+            strategy,
+            padded,
+            Clock::get()?.unix_timestamp as u32,
+        )?;
+
+        emit!(ChangeScheduledEvent {
+            id,
+            eta: timelock.get(id)?.eta,
+            kind,
+            vault,
+            strategy,
+            values: padded,
+        });
+
+        Ok(())
+    }
+
+    pub fn cancel_change(&self, id: u64) -> Result<()> {
+        msg!("DotWave: Cancel change");
+
+        let kind = self.state.load()?.timelock.get(id)?.kind;
+        // changes of the delay are scheduled by the admin, so only the admin can drop them
+        self.authorize(match kind {
+            ChangeKind::Delay => Role::Admin,
+            _ => Role::RiskManager,
+        })?;
+
+        self.state.load_mut()?.timelock.cancel(id)?;
+
+        emit!(ChangeCancelledEvent { id });
+
         Ok(())
     }
 }
@@ -1,5 +1,6 @@
 use crate::{
-    core_lib::decimal::Quantity,
+    core_lib::{decimal::Quantity, structs::Operation},
+    events::{prices, utilization, BorrowEvent},
     structs::{State, Statement, Vaults},
 };
 use anchor_lang::prelude::*;
@@ -45,8 +46,31 @@ impl<'info> Borrow<'info> {
 
         user_statement.refresh(&vaults.arr.elements)?;
 
+        let vault_index = vault;
         let vault = vaults.vault_checked_mut(vault)?;
-        let borrow_amount = vault.borrow(user_statement, amount)?;
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vault, Operation::Lend)?;
+        let (borrow_amount, shares) = vault.borrow(user_statement, amount)?;
+        vault.record_outflow(
+            user_statement,
+            borrow_amount,
+            Quantity::new(0),
+            current_timestamp as u32,
+        )?;
+
+        emit!(BorrowEvent {
+            vault: vault_index,
+            owner: ctx.accounts.signer.key(),
+            amount: borrow_amount.get(),
+            shares: shares.get(),
+            base_price: prices(vault).0,
+            utilization: utilization(vault),
+        });
+
+        // debt only isolated collateral can back counts against the debt ceilings
+        user_statement.back_isolated_debt(&mut vaults.arr.elements)?;
 
         let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
         let signer = &[&seeds[..]];
@@ -0,0 +1,91 @@
+use crate::{
+    core_lib::services::swapping::KeptFeeBucket,
+    events::KeptFeeBurnedEvent,
+    structs::{Role, State, Vaults},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, burn, Burn, Mint, TokenAccount};
+use checked_decimal_macro::Decimal;
+
+#[derive(Accounts)]
+#[instruction(vault: u8)]
+pub struct BurnKeptFee<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = state.load()?.has_role(&admin.key(), Role::Treasury))]
+    pub admin: Signer<'info>,
+
+    #[account(mut, constraint = base.key() == reserve_base.mint)]
+    pub base: Account<'info, Mint>,
+    #[account(mut, constraint = quote.key() == reserve_quote.mint)]
+    pub quote: Account<'info, Mint>,
+    #[account(mut,
+      constraint = reserve_base.mint == vaults.load()?.keys.get(vault as usize).unwrap().base_token,
+      constraint = reserve_base.key() == vaults.load()?.keys.get(vault as usize).unwrap().base_reserve,
+      constraint = reserve_base.owner == state.key(),
+  )]
+    pub reserve_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_quote.mint == vaults.load()?.keys.get(vault as usize).unwrap().quote_token,
+      constraint = reserve_quote.key() == vaults.load()?.keys.get(vault as usize).unwrap().quote_reserve,
+      constraint = reserve_quote.owner == state.key(),
+  )]
+    pub reserve_quote: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, token::Token>,
+}
+
+impl<'info> BurnKeptFee<'info> {
+    pub fn handler(ctx: Context<BurnKeptFee>, vault: u8) -> anchor_lang::Result<()> {
+        msg!("DotWave: Burn kept fee");
+
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+        let vault_index = vault;
+        let vault = vaults.vault_checked_mut(vault)?;
+
+        let burned = vault.swap_service()?.kept.sweep(KeptFeeBucket::Burn);
+
+        let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
+        let signer = &[&seeds[..]];
+
+        burn(
+            ctx.accounts.burn_base().with_signer(signer),
+            burned.base.get(),
+        )?;
+        burn(
+            ctx.accounts.burn_quote().with_signer(signer),
+            burned.quote.get(),
+        )?;
+
+        emit!(KeptFeeBurnedEvent {
+            vault: vault_index,
+            base: burned.base.get(),
+            quote: burned.quote.get(),
+        });
+
+        Ok(())
+    }
+
+    fn burn_base(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Burn {
+                mint: self.base.to_account_info(),
+                from: self.reserve_base.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+
+    fn burn_quote(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Burn {
+                mint: self.quote.to_account_info(),
+                from: self.reserve_quote.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{
+    events::WithdrawCancelledEvent,
+    structs::{State, Statement, Vaults},
+};
+use anchor_lang::prelude::*;
+use checked_decimal_macro::Decimal;
+
+#[derive(Accounts)]
+pub struct CancelWithdraw<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = statement.load()?.owner == signer.key())]
+    pub statement: AccountLoader<'info, Statement>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+impl<'info> CancelWithdraw<'info> {
+    pub fn handler(
+        ctx: Context<CancelWithdraw>,
+        vault: u8,
+        strategy: u8,
+        ticket: u64,
+    ) -> anchor_lang::Result<()> {
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+        let statement = &mut ctx.accounts.statement.load_mut()?.statement;
+
+        let shares = vaults.vault_checked_mut(vault)?.cancel_withdraw(
+            statement,
+            ctx.accounts.signer.key().to_bytes(),
+            strategy,
+            ticket,
+        )?;
+
+        emit!(WithdrawCancelledEvent {
+            vault,
+            strategy,
+            owner: ctx.accounts.signer.key(),
+            ticket,
+            shares: shares.get(),
+        });
+
+        Ok(())
+    }
+}
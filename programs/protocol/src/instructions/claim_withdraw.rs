@@ -0,0 +1,104 @@
+use crate::{
+    core_lib::{decimal::Balances, structs::Operation},
+    events::WithdrawClaimedEvent,
+    structs::{State, Vaults},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, transfer, TokenAccount, Transfer};
+use checked_decimal_macro::Decimal;
+
+#[derive(Accounts)]
+#[instruction(vault: u8)]
+pub struct ClaimWithdraw<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+      constraint = account_base.mint == reserve_base.mint,
+      constraint = account_base.owner == signer.key(),
+  )]
+    account_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = account_quote.mint == reserve_quote.mint,
+      constraint = account_quote.owner == signer.key(),
+  )]
+    account_quote: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_base.mint == vaults.load()?.keys.get(vault as usize).unwrap().base_token,
+      constraint = reserve_base.key() == vaults.load()?.keys.get(vault as usize).unwrap().base_reserve,
+      constraint = reserve_base.owner == state.key(),
+  )]
+    pub reserve_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_quote.mint == vaults.load()?.keys.get(vault as usize).unwrap().quote_token,
+      constraint = reserve_quote.key() == vaults.load()?.keys.get(vault as usize).unwrap().quote_reserve,
+      constraint = reserve_quote.owner == state.key(),
+  )]
+    pub reserve_quote: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, token::Token>,
+}
+
+impl<'info> ClaimWithdraw<'info> {
+    pub fn handler(
+        ctx: Context<ClaimWithdraw>,
+        vault: u8,
+        strategy: u8,
+        ticket: u64,
+    ) -> anchor_lang::Result<()> {
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vaults.vault_checked(vault)?, Operation::Withdraw)?;
+
+        let Balances { base, quote } = vaults.vault_checked_mut(vault)?.claim_withdraw(
+            ctx.accounts.signer.key().to_bytes(),
+            strategy,
+            ticket,
+        )?;
+
+        emit!(WithdrawClaimedEvent {
+            vault,
+            strategy,
+            owner: ctx.accounts.signer.key(),
+            ticket,
+            base: base.get(),
+            quote: quote.get(),
+        });
+
+        let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
+        let signer = &[&seeds[..]];
+
+        transfer(ctx.accounts.send_base().with_signer(signer), base.get())?;
+        transfer(ctx.accounts.send_quote().with_signer(signer), quote.get())?;
+
+        Ok(())
+    }
+
+    fn send_base(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_base.to_account_info(),
+                to: self.account_base.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+
+    fn send_quote(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_quote.to_account_info(),
+                to: self.account_quote.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+}
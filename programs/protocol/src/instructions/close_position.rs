@@ -1,5 +1,9 @@
 use crate::{
-    core_lib::{decimal::BalanceChange, structs::Side},
+    core_lib::{
+        decimal::{BalanceChange, Quantity},
+        structs::Side,
+    },
+    events::{prices, ClosePositionEvent},
     structs::{State, Statement, Vaults},
 };
 use anchor_lang::prelude::*;
@@ -13,7 +17,7 @@ pub struct ClosePosition<'info> {
     pub state: AccountLoader<'info, State>,
     #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
     pub vaults: AccountLoader<'info, Vaults>,
-    #[account(mut, seeds = [b"statement".as_ref(), signer.key.as_ref()], bump=statement.load()?.bump, constraint = statement.load()?.owner == signer.key())]
+    #[account(mut, constraint = statement.load()?.owner == signer.key())]
     pub statement: AccountLoader<'info, Statement>,
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -55,10 +59,35 @@ impl<'info> ClosePosition<'info> {
 
         user_statement.refresh(&vaults.arr.elements)?;
 
+        let vault_index = vault;
         let vault = vaults.vault_checked_mut(vault)?;
         let current_timestamp = Clock::get()?.unix_timestamp as u32;
         let (balance_change, side) = vault.close_position(user_statement, current_timestamp)?;
 
+        if let BalanceChange::Profit(profit) = &balance_change {
+            let (base, quote) = match side {
+                Side::Long => (*profit, Quantity::new(0)),
+                Side::Short => (Quantity::new(0), *profit),
+            };
+            vault.record_outflow(user_statement, base, quote, current_timestamp)?;
+        }
+
+        let (profit, loss) = match balance_change {
+            BalanceChange::Profit(profit) => (profit.get(), 0),
+            BalanceChange::Loss(loss) => (0, loss.get()),
+        };
+        let (base_price, quote_price) = prices(vault);
+
+        emit!(ClosePositionEvent {
+            vault: vault_index,
+            owner: ctx.accounts.signer.key(),
+            long: side == Side::Long,
+            profit,
+            loss,
+            base_price,
+            quote_price,
+        });
+
         match balance_change {
             BalanceChange::Profit(profit_amount) => {
                 let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
@@ -0,0 +1,28 @@
+use crate::{core_lib::errors::LibErrors, events::StatementClosedEvent, structs::Statement};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct CloseStatement<'info> {
+    #[account(mut, close = owner, constraint = statement.load()?.owner == owner.key())]
+    pub statement: AccountLoader<'info, Statement>,
+    #[account(mut)]
+    pub owner: Signer<'info>,
+}
+
+impl CloseStatement<'_> {
+    /// Closes a statement with nothing left in it, rent goes back to the owner
+    pub fn handler(ctx: Context<CloseStatement>) -> anchor_lang::Result<()> {
+        msg!("DotWave: Close statement");
+
+        if !ctx.accounts.statement.load()?.statement.is_settled() {
+            return Err(LibErrors::StatementNotSettled.into());
+        }
+
+        emit!(StatementClosedEvent {
+            statement: ctx.accounts.statement.key(),
+            owner: ctx.accounts.owner.key(),
+        });
+
+        Ok(())
+    }
+}
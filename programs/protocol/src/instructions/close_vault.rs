@@ -0,0 +1,116 @@
+use crate::{
+    events::VaultClosedEvent,
+    structs::{Role, State, Vaults},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, close_account, transfer, CloseAccount, TokenAccount, Transfer};
+
+#[derive(Accounts)]
+#[instruction(vault: u8)]
+pub struct CloseVault<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = state.load()?.has_role(&admin.key(), Role::Admin))]
+    pub admin: Signer<'info>,
+
+    #[account(mut,
+      constraint = account_base.mint == reserve_base.mint,
+  )]
+    account_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = account_quote.mint == reserve_quote.mint,
+  )]
+    account_quote: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_base.mint == vaults.load()?.keys.get(vault as usize).unwrap().base_token,
+      constraint = reserve_base.key() == vaults.load()?.keys.get(vault as usize).unwrap().base_reserve,
+      constraint = reserve_base.owner == state.key(),
+  )]
+    pub reserve_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_quote.mint == vaults.load()?.keys.get(vault as usize).unwrap().quote_token,
+      constraint = reserve_quote.key() == vaults.load()?.keys.get(vault as usize).unwrap().quote_reserve,
+      constraint = reserve_quote.owner == state.key(),
+  )]
+    pub reserve_quote: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, token::Token>,
+}
+
+impl<'info> CloseVault<'info> {
+    /// Closes reserves of an emptied delisted vault and frees its slot,
+    /// leftovers such as insurance and kept fees go to the given accounts
+    pub fn handler(ctx: Context<CloseVault>, vault: u8) -> anchor_lang::Result<()> {
+        msg!("DotWave: Close vault");
+
+        ctx.accounts
+            .vaults
+            .load()?
+            .vault_checked(vault)?
+            .check_closable()?;
+
+        let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
+        let signer = &[&seeds[..]];
+
+        let (base, quote) = (
+            ctx.accounts.reserve_base.amount,
+            ctx.accounts.reserve_quote.amount,
+        );
+
+        transfer(ctx.accounts.send_base().with_signer(signer), base)?;
+        transfer(ctx.accounts.send_quote().with_signer(signer), quote)?;
+        close_account(ctx.accounts.close_base().with_signer(signer))?;
+        close_account(ctx.accounts.close_quote().with_signer(signer))?;
+
+        ctx.accounts.vaults.load_mut()?.free(vault)?;
+
+        emit!(VaultClosedEvent { vault, base, quote });
+
+        Ok(())
+    }
+
+    fn send_base(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_base.to_account_info(),
+                to: self.account_base.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+
+    fn send_quote(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_quote.to_account_info(),
+                to: self.account_quote.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+
+    fn close_base(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.reserve_base.to_account_info(),
+                destination: self.admin.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+
+    fn close_quote(&self) -> CpiContext<'_, '_, '_, 'info, CloseAccount<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            CloseAccount {
+                account: self.reserve_quote.to_account_info(),
+                destination: self.admin.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+}
@@ -1,12 +1,15 @@
 use crate::{
-    core_lib::errors::LibErrors,
-    structs::{State, Vaults},
+    core_lib::{
+        errors::LibErrors,
+        structs::{PauseFlags, Timelock},
+    },
+    structs::{Roles, State, Vaults},
 };
 use anchor_lang::prelude::*;
 
 #[derive(Accounts)]
 pub struct CreateState<'info> {
-    #[account(init, seeds = [b"state".as_ref()], bump, payer = admin, space = 8 + 65)]
+    #[account(init, seeds = [b"state".as_ref()], bump, payer = admin, space = 8 + std::mem::size_of::<State>())]
     pub state: AccountLoader<'info, State>,
     #[account(zero)]
     pub vaults: AccountLoader<'info, Vaults>,
@@ -25,6 +28,10 @@ pub fn handler(ctx: Context<CreateState>) -> Result<()> {
         admin: ctx.accounts.admin.key(),
         vaults_acc: ctx.accounts.vaults.key(),
         bump: *ctx.bumps.get("state").ok_or(LibErrors::BumpNotFound)?,
+        pause: PauseFlags::default(),
+        pending_admin: None,
+        roles: Roles::default(),
+        timelock: Timelock::new(),
     };
 
     ctx.accounts.vaults.load_init()?;
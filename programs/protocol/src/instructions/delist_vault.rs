@@ -0,0 +1,44 @@
+use crate::{
+    events::{prices, VaultDelistedEvent},
+    structs::{Role, State, Vaults},
+};
+use anchor_lang::prelude::*;
+use std::collections::HashSet;
+
+#[derive(Accounts)]
+pub struct DelistVault<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = state.load()?.has_role(&admin.key(), Role::RiskManager))]
+    pub admin: Signer<'info>,
+}
+
+impl<'info> DelistVault<'info> {
+    /// Freezes the vault at current oracle prices, from then on positions are only closed and liquidity withdrawn
+    pub fn handler(ctx: Context<DelistVault>, vault: u8) -> anchor_lang::Result<()> {
+        msg!("DotWave: Delist vault");
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+
+        vaults.refresh(
+            &HashSet::from([vault]),
+            ctx.remaining_accounts,
+            current_timestamp,
+        )?;
+
+        let vault_index = vault;
+        let vault = vaults.vault_checked_mut(vault)?;
+        vault.delist()?;
+
+        let (base_price, quote_price) = prices(vault);
+        emit!(VaultDelistedEvent {
+            vault: vault_index,
+            base_price,
+            quote_price,
+        });
+
+        Ok(())
+    }
+}
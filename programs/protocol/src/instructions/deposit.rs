@@ -1,7 +1,8 @@
 use std::collections::HashSet;
 
 use crate::{
-    core_lib::{decimal::Quantity, Token},
+    core_lib::{decimal::Quantity, structs::Operation, Token},
+    events::{prices, utilization, DepositEvent},
     structs::{State, Statement, Vaults},
 };
 use anchor_lang::prelude::*;
@@ -15,7 +16,7 @@ pub struct Deposit<'info> {
     pub state: AccountLoader<'info, State>,
     #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
     pub vaults: AccountLoader<'info, Vaults>,
-    #[account(mut, seeds = [b"statement".as_ref(), signer.key.as_ref()], bump=statement.load()?.bump, constraint = statement.load()?.owner == signer.key())]
+    #[account(mut, constraint = statement.load()?.owner == signer.key())]
     pub statement: AccountLoader<'info, Statement>,
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -61,23 +62,35 @@ impl<'info> Deposit<'info> {
         vaults_indexes.insert(vault);
         vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
 
+        let vault_index = vault;
         let vault = vaults.vault_checked_mut(vault)?;
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vault, Operation::Deposit)?;
 
-        let other_quantity = vault.deposit(
+        let change = vault.deposit(
             statement,
             if base { Token::Base } else { Token::Quote },
             Quantity::new(quantity),
             strategy,
         )?;
 
-        let (base_amount, quote_amount) = if base {
-            (quantity, other_quantity.get())
-        } else {
-            (other_quantity.get(), quantity)
-        };
+        transfer(ctx.accounts.take_base(), change.base.get())?;
+        transfer(ctx.accounts.take_quote(), change.quote.get())?;
 
-        transfer(ctx.accounts.take_base(), base_amount)?;
-        transfer(ctx.accounts.take_quote(), quote_amount)?;
+        let (base_price, quote_price) = prices(vault);
+        emit!(DepositEvent {
+            vault: vault_index,
+            strategy,
+            owner: ctx.accounts.signer.key(),
+            base: change.base.get(),
+            quote: change.quote.get(),
+            shares: change.shares.get(),
+            base_price,
+            quote_price,
+            utilization: utilization(vault),
+        });
 
         Ok(())
     }
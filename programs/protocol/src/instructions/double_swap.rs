@@ -3,6 +3,8 @@ use std::collections::HashSet;
 use crate::{
     core_lib::decimal::Quantity,
     core_lib::errors::LibErrors,
+    core_lib::structs::Operation,
+    events::{prices, DoubleSwapEvent},
     structs::{State, Vaults},
 };
 use anchor_lang::prelude::*;
@@ -79,18 +81,42 @@ impl<'info> DoubleSwap<'info> {
         vaults_indexes.insert(vault_out);
         vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
 
+        let vault_in_index = vault_in;
+        let vault_out_index = vault_out;
+
         let vault_in = vaults.vault_checked_mut(vault_in)?;
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vault_in, Operation::Swap)?;
         let quote_quantity = vault_in.sell(quantity)?;
+        let price_in = prices(vault_in).0;
         msg!("quantity quote: {}", quote_quantity);
 
         let vault_out = vaults.vault_checked_mut(vault_out)?;
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vault_out, Operation::Swap)?;
         let quantity_out = vault_out.buy(quote_quantity)?;
+        let price_out = prices(vault_out).0;
         msg!("quantity out: {}", quantity_out);
 
         if quantity_out < Quantity::new(min_expected) {
             return Err(LibErrors::NoMinAmountOut.into());
         }
 
+        emit!(DoubleSwapEvent {
+            vault_in: vault_in_index,
+            vault_out: vault_out_index,
+            owner: ctx.accounts.signer.key(),
+            amount_in: amount,
+            amount_quote: quote_quantity.get(),
+            amount_out: quantity_out.get(),
+            price_in,
+            price_out,
+        });
+
         let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
         let signer = &[&seeds[..]];
 
@@ -4,7 +4,8 @@ use crate::{
         errors::LibErrors,
         Token,
     },
-    structs::{State, Vaults},
+    events::OracleEnabledEvent,
+    structs::{Role, State, Vaults},
 };
 use anchor_lang::prelude::*;
 use checked_decimal_macro::Factories;
@@ -15,7 +16,7 @@ pub struct EnableOracle<'info> {
     pub state: AccountLoader<'info, State>,
     #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
     pub vaults: AccountLoader<'info, Vaults>,
-    #[account(mut, constraint = admin.key() == state.load()?.admin)]
+    #[account(mut, constraint = state.load()?.has_role(&admin.key(), Role::OracleManager))]
     pub admin: Signer<'info>,
     /// CHECK: deserialized in code for now
     pub price_feed: AccountInfo<'info>,
@@ -77,6 +78,13 @@ impl EnableOracle<'_> {
             keys.quote_oracle = Some(self.price_feed.key());
         }
 
+        emit!(OracleEnabledEvent {
+            vault: index,
+            base,
+            price_feed: self.price_feed.key(),
+            max_update_interval,
+        });
+
         Ok(())
     }
 }
@@ -0,0 +1,46 @@
+use crate::{
+    core_lib::structs::ChangeKind,
+    events::ChangeExecutedEvent,
+    structs::{State, Vaults},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct ExecuteChange<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    pub signer: Signer<'info>,
+}
+
+impl ExecuteChange<'_> {
+    /// Applies a scheduled change whose delay passed, can be called by anyone
+    pub fn handler(ctx: Context<ExecuteChange>, id: u64) -> anchor_lang::Result<()> {
+        msg!("DotWave: Execute change");
+        let current_timestamp = Clock::get()?.unix_timestamp as u32;
+        let state = &mut ctx.accounts.state.load_mut()?;
+
+        let change = state.timelock.take(id, current_timestamp)?;
+
+        match change.kind {
+            ChangeKind::Delay => state.timelock.set_delay(change.values[0] as u32)?,
+            _ => ctx
+                .accounts
+                .vaults
+                .load_mut()?
+                .vault_checked_mut(change.vault)?
+                .apply_change(&change, current_timestamp)?,
+        }
+
+        emit!(ChangeExecutedEvent {
+            id,
+            kind: change.kind as u8,
+            vault: change.vault,
+            strategy: change.strategy,
+            values: change.values,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,133 @@
+use crate::{
+    core_lib::{
+        decimal::{BalanceChange, Quantity},
+        structs::Side,
+    },
+    events::{prices, PositionSettledEvent},
+    structs::{Role, State, Statement, Vaults},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, transfer, TokenAccount, Transfer};
+use checked_decimal_macro::Decimal;
+use std::collections::HashSet;
+
+#[derive(Accounts)]
+#[instruction(vault: u8)]
+pub struct ForceClosePosition<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut)]
+    pub statement: AccountLoader<'info, Statement>,
+    #[account(mut, constraint = state.load()?.has_role(&admin.key(), Role::RiskManager))]
+    pub admin: Signer<'info>,
+
+    #[account(mut,
+      constraint = account_base.mint == reserve_base.mint,
+      constraint = account_base.owner == statement.load()?.owner,
+  )]
+    account_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = account_quote.mint == reserve_quote.mint,
+      constraint = account_quote.owner == statement.load()?.owner,
+  )]
+    account_quote: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_base.mint == vaults.load()?.keys.get(vault as usize).unwrap().base_token,
+      constraint = reserve_base.key() == vaults.load()?.keys.get(vault as usize).unwrap().base_reserve,
+      constraint = reserve_base.owner == state.key(),
+  )]
+    pub reserve_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_quote.mint == vaults.load()?.keys.get(vault as usize).unwrap().quote_token,
+      constraint = reserve_quote.key() == vaults.load()?.keys.get(vault as usize).unwrap().quote_reserve,
+      constraint = reserve_quote.owner == state.key(),
+  )]
+    pub reserve_quote: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, token::Token>,
+}
+
+impl<'info> ForceClosePosition<'info> {
+    /// Settles a position left in a delisted vault, profit goes to the owner and losses become debt of the statement
+    pub fn handler(ctx: Context<ForceClosePosition>, vault: u8) -> anchor_lang::Result<()> {
+        msg!("DotWave: Force close position");
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let user_statement = &mut ctx.accounts.statement.load_mut()?.statement;
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+
+        vaults.refresh(
+            &HashSet::from([vault]),
+            ctx.remaining_accounts,
+            current_timestamp,
+        )?;
+
+        let vault_index = vault;
+        let vault = vaults.vault_checked_mut(vault)?;
+        let settlement = vault.force_close_position(user_statement)?;
+        let side = settlement.side;
+
+        let profit = match settlement.change {
+            BalanceChange::Profit(profit) => profit.get(),
+            BalanceChange::Loss(..) => 0,
+        };
+        let (base, quote) = match side {
+            Side::Long => (profit, 0),
+            Side::Short => (0, profit),
+        };
+        vault.record_outflow(
+            user_statement,
+            Quantity::new(base),
+            Quantity::new(quote),
+            current_timestamp as u32,
+        )?;
+        let (base_price, quote_price) = prices(vault);
+
+        emit!(PositionSettledEvent {
+            vault: vault_index,
+            owner: ctx.accounts.statement.load()?.owner,
+            long: side == Side::Long,
+            profit,
+            debt: settlement.debt.get(),
+            forgiven: settlement.forgiven.get(),
+            base_price,
+            quote_price,
+        });
+
+        if profit > 0 {
+            let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
+            let signer = &[&seeds[..]];
+
+            let send_ctx = match side {
+                Side::Long => ctx.accounts.send_base(),
+                Side::Short => ctx.accounts.send_quote(),
+            };
+
+            transfer(send_ctx.with_signer(signer), profit)?;
+        }
+
+        Ok(())
+    }
+
+    fn send_base(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_base.to_account_info(),
+                to: self.account_base.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+
+    fn send_quote(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_quote.to_account_info(),
+                to: self.account_quote.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+}
@@ -0,0 +1,51 @@
+use crate::{
+    core_lib::errors::LibErrors,
+    structs::{State, Vaults},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{Mint, Token};
+
+#[derive(Accounts)]
+#[instruction(vault: u8)]
+pub struct InitShareMint<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = admin.key() == state.load()?.admin)]
+    pub admin: Signer<'info>,
+
+    #[account(init,
+        mint::decimals = base.decimals,
+        mint::authority = state.to_account_info(),
+        payer = admin,
+    )]
+    pub share_mint: Account<'info, Mint>,
+    #[account(constraint = base.key() == vaults.load()?.keys.get(vault as usize).unwrap().base_token)]
+    pub base: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+impl InitShareMint<'_> {
+    pub fn handler(&mut self, vault: u8, strategy: u8) -> anchor_lang::Result<()> {
+        msg!("DotWave: Initializing share mint");
+
+        let vaults = &mut self.vaults.load_mut()?;
+        vaults.vault_checked(vault)?.strategy(strategy)?;
+
+        let share_mint = vaults
+            .keys_checked_mut(vault)?
+            .share_mints
+            .get_mut(strategy as usize)
+            .ok_or(LibErrors::StrategyMissing)?;
+
+        if share_mint.is_some() {
+            return Err(LibErrors::ShareMintExists.into());
+        }
+
+        *share_mint = Some(self.share_mint.key());
+
+        Ok(())
+    }
+}
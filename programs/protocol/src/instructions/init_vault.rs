@@ -1,6 +1,12 @@
 use crate::{
     core_lib::errors::LibErrors,
-    core_lib::{services::Services, strategy::Strategies, vault::Vault},
+    core_lib::{
+        decimal::Value,
+        services::Services,
+        strategy::Strategies,
+        structs::{InsuranceFund, OutflowLimits, PauseFlags},
+        vault::{Vault, VaultStatus},
+    },
     structs::{State, VaultKeys, Vaults},
 };
 use anchor_lang::prelude::*;
@@ -44,23 +50,39 @@ impl InitVault<'_> {
             quote_reserve: self.reserve_quote.key(),
             base_oracle: None,
             quote_oracle: None,
+            share_mints: [None; 6],
         };
 
         let vaults = &mut self.vaults.load_mut()?;
+        let free_slot = vaults.free_slot();
         let created_vault = Vault {
             services: Services::default(),
             strategies: Strategies::default(),
             oracle: None,
             quote_oracle: None,
-            id: vaults.arr.head,
+            debt_ceiling: None,
+            isolated_debt: Value::default(),
+            insurance: InsuranceFund::default(),
+            pause: PauseFlags::default(),
+            outflow: OutflowLimits::default(),
+            status: VaultStatus::Active,
+            id: free_slot.unwrap_or(vaults.arr.head),
         };
 
-        vaults
-            .arr
-            .add(created_vault)
-            .map_err(|_| LibErrors::AddVault)?;
+        match free_slot {
+            Some(index) => {
+                *vaults.vault_checked_mut(index)? = created_vault;
+                *vaults.keys_checked_mut(index)? = keys;
+            }
+            None => {
+                vaults
+                    .arr
+                    .add(created_vault)
+                    .map_err(|_| LibErrors::AddVault)?;
 
-        vaults.keys.add(keys).map_err(|_| LibErrors::AddKeys)?;
+                vaults.keys.add(keys).map_err(|_| LibErrors::AddKeys)?;
+            }
+        }
 
         Ok(())
     }
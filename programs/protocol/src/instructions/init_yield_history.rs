@@ -0,0 +1,43 @@
+use crate::{
+    core_lib::errors::LibErrors,
+    structs::{State, Vaults, YieldHistory},
+};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+#[instruction(vault: u8)]
+pub struct InitYieldHistory<'info> {
+    #[account(seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(
+      init,
+      seeds = [b"yields".as_ref(), &[vault]],
+      bump,
+      payer = payer,
+      space = 8 + std::mem::size_of::<YieldHistory>()
+    )]
+    pub yield_history: AccountLoader<'info, YieldHistory>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+}
+
+impl InitYieldHistory<'_> {
+    pub fn handler(ctx: Context<InitYieldHistory>, vault: u8) -> anchor_lang::Result<()> {
+        msg!("DotWave: Initializing yield history");
+        ctx.accounts.vaults.load()?.vault_checked(vault)?;
+
+        let yield_history = &mut ctx.accounts.yield_history.load_init()?;
+
+        yield_history.vault = vault;
+        yield_history.bump = *ctx
+            .bumps
+            .get("yield_history")
+            .ok_or(LibErrors::BumpNotFound)?;
+
+        Ok(())
+    }
+}
@@ -1,29 +1,69 @@
+pub mod accept_admin;
 pub mod add_strategy;
 pub mod admin;
 pub mod borrow;
+pub mod burn_kept_fee;
+pub mod cancel_withdraw;
+pub mod claim_withdraw;
 pub mod close_position;
+pub mod close_statement;
+pub mod close_vault;
 pub mod create_state;
 pub mod create_statement;
+pub mod delist_vault;
 pub mod deposit;
 pub mod double_swap;
 pub mod enable_oracle;
+pub mod execute_change;
+pub mod force_close_position;
+pub mod init_share_mint;
 pub mod init_vault;
+pub mod init_yield_history;
 pub mod open_position;
 pub mod repay;
+pub mod request_withdraw;
 pub mod single_swap;
+pub mod snapshot_yields;
+pub mod sweep_kept_fee;
+pub mod transfer_statement;
+pub mod unwrap_shares;
 pub mod withdraw;
+pub mod withdraw_single;
+pub mod wrap_shares;
+pub mod write_off_debt;
+pub mod zap_deposit;
 
+pub use accept_admin::*;
 pub use add_strategy::*;
 pub use admin::*;
 pub use borrow::*;
+pub use burn_kept_fee::*;
+pub use cancel_withdraw::*;
+pub use claim_withdraw::*;
 pub use close_position::*;
+pub use close_statement::*;
+pub use close_vault::*;
 pub use create_state::*;
 pub use create_statement::*;
+pub use delist_vault::*;
 pub use deposit::*;
 pub use double_swap::*;
 pub use enable_oracle::*;
+pub use execute_change::*;
+pub use force_close_position::*;
+pub use init_share_mint::*;
 pub use init_vault::*;
+pub use init_yield_history::*;
 pub use open_position::*;
 pub use repay::*;
+pub use request_withdraw::*;
 pub use single_swap::*;
+pub use snapshot_yields::*;
+pub use sweep_kept_fee::*;
+pub use transfer_statement::*;
+pub use unwrap_shares::*;
 pub use withdraw::*;
+pub use withdraw_single::*;
+pub use wrap_shares::*;
+pub use write_off_debt::*;
+pub use zap_deposit::*;
@@ -1,5 +1,9 @@
 use crate::{
-    core_lib::{decimal::Quantity, structs::Side},
+    core_lib::{
+        decimal::Quantity,
+        structs::{Operation, Side},
+    },
+    events::{prices, OpenPositionEvent},
     structs::{State, Statement, Vaults},
 };
 use anchor_lang::prelude::*;
@@ -12,7 +16,7 @@ pub struct OpenPosition<'info> {
     pub state: AccountLoader<'info, State>,
     #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
     pub vaults: AccountLoader<'info, Vaults>,
-    #[account(mut, seeds = [b"statement".as_ref(), signer.key.as_ref()], bump=statement.load()?.bump, constraint = statement.load()?.owner == signer.key())]
+    #[account(mut, constraint = statement.load()?.owner == signer.key())]
     pub statement: AccountLoader<'info, Statement>,
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -35,10 +39,28 @@ impl<'info> OpenPosition<'info> {
 
         user_statement.refresh(&vaults.arr.elements)?;
 
+        let vault_index = vault;
         let vault = vaults.vault_checked_mut(vault)?;
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vault, Operation::Trade)?;
         let quantity = Quantity::new(amount);
         let side = if long { Side::Long } else { Side::Short };
-        vault.open_position(user_statement, quantity, side)?;
+        let receipt = vault.open_position(user_statement, quantity, side)?;
+
+        emit!(OpenPositionEvent {
+            vault: vault_index,
+            owner: ctx.accounts.signer.key(),
+            long,
+            size: receipt.size.get(),
+            locked: receipt.locked.get(),
+            open_price: receipt.open_price.val,
+            quote_price: prices(vault).1,
+        });
+
+        // debt left for isolated shares to back counts against their debt ceilings
+        user_statement.back_isolated_debt(&mut vaults.arr.elements)?;
 
         Ok(())
     }
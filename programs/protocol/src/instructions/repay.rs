@@ -1,11 +1,14 @@
 use crate::{
-    core_lib::decimal::Quantity,
+    core_lib::{decimal::Quantity, errors::LibErrors, structs::Operation},
+    events::{prices, utilization, RepayEvent},
     structs::{State, Statement, Vaults},
 };
 use anchor_lang::prelude::*;
 use anchor_spl::token::{self, transfer, TokenAccount, Transfer};
 use checked_decimal_macro::Decimal;
 
+/// Accounts of repaying debt of a statement, shared by repaying with the signer tokens,
+/// on behalf of the owner and with collateral
 #[derive(Accounts)]
 #[instruction(vault: u8)]
 pub struct Repay<'info> {
@@ -13,7 +16,8 @@ pub struct Repay<'info> {
     pub state: AccountLoader<'info, State>,
     #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
     pub vaults: AccountLoader<'info, Vaults>,
-    #[account(mut, constraint = statement.load()?.owner == signer.key())]
+    /// owned by the signer, except for repaying on behalf of the owner
+    #[account(mut)]
     pub statement: AccountLoader<'info, Statement>,
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -33,9 +37,11 @@ pub struct Repay<'info> {
 }
 
 impl<'info> Repay<'info> {
+    /// Repays debt of the statement with tokens of the signer, who does not have to be the owner
     pub fn handler(ctx: Context<Repay>, vault: u8, amount: u64) -> anchor_lang::Result<()> {
         msg!("DotWave: Repay");
         let current_timestamp = Clock::get()?.unix_timestamp;
+        let owner = ctx.accounts.statement.load()?.owner;
         let vaults = &mut ctx.accounts.vaults.load_mut()?;
         let user_statement = &mut ctx.accounts.statement.load_mut()?.statement;
         let amount = Quantity::new(amount);
@@ -44,14 +50,104 @@ impl<'info> Repay<'info> {
         vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
         user_statement.refresh(&vaults.arr.elements)?;
 
+        let vault_index = vault;
+        let vault = vaults.vault_checked_mut(vault)?;
+        let repayment = vault.repay(user_statement, amount)?;
+
+        transfer(ctx.accounts.take_base(), repayment.repaid.get())?;
+
+        emit!(RepayEvent {
+            vault: vault_index,
+            owner,
+            payer: ctx.accounts.signer.key(),
+            repaid: repayment.repaid.get(),
+            interest: repayment.interest.get(),
+            principal: repayment.principal.get(),
+            shares: repayment.shares.get(),
+            base_price: prices(vault).0,
+            utilization: utilization(vault),
+        });
+
+        user_statement.release_isolated_debt(&mut vaults.arr.elements)?;
+
+        Ok(())
+    }
+
+    /// Repays debt of the signer with their liquidity in the strategy, leftover base is sent to them
+    pub fn with_collateral_handler(
+        ctx: Context<Repay>,
+        vault: u8,
+        strategy: u8,
+        amount: u64,
+        min_base_out: u64,
+    ) -> anchor_lang::Result<()> {
+        msg!("DotWave: Repay with collateral");
+        ctx.accounts.check_owner()?;
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+        let user_statement = &mut ctx.accounts.statement.load_mut()?.statement;
+
+        let vaults_indexes = user_statement.get_vaults_indexes(&vault);
+        vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
+
+        let vault_index = vault;
         let vault = vaults.vault_checked_mut(vault)?;
-        let repay_amount = vault.repay(user_statement, amount)?;
+        // collateral is withdrawn from the strategy and swapped to base before repaying
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vault, Operation::Withdraw)?;
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vault, Operation::Swap)?;
+
+        let (repayment, leftover) = vault.repay_with_collateral(
+            user_statement,
+            strategy,
+            Quantity::new(amount),
+            Quantity::new(min_base_out),
+        )?;
+        vault.record_outflow(
+            user_statement,
+            leftover,
+            Quantity::new(0),
+            current_timestamp as u32,
+        )?;
+
+        emit!(RepayEvent {
+            vault: vault_index,
+            owner: ctx.accounts.signer.key(),
+            payer: ctx.accounts.signer.key(),
+            repaid: repayment.repaid.get(),
+            interest: repayment.interest.get(),
+            principal: repayment.principal.get(),
+            shares: repayment.shares.get(),
+            base_price: prices(vault).0,
+            utilization: utilization(vault),
+        });
+
+        user_statement.release_isolated_debt(&mut vaults.arr.elements)?;
 
-        transfer(ctx.accounts.take_base(), repay_amount.get())?;
+        if !user_statement.collateralized() {
+            return Err(LibErrors::UserNotCollateralized.into());
+        }
+
+        let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
+        let signer = &[&seeds[..]];
+
+        transfer(ctx.accounts.send_base().with_signer(signer), leftover.get())?;
 
         Ok(())
     }
 
+    pub fn check_owner(&self) -> anchor_lang::Result<()> {
+        match self.statement.load()?.owner == self.signer.key() {
+            true => Ok(()),
+            false => Err(LibErrors::NotStatementOwner.into()),
+        }
+    }
+
     fn take_base(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
         CpiContext::new(
             self.token_program.to_account_info(),
@@ -62,4 +158,15 @@ impl<'info> Repay<'info> {
             },
         )
     }
+
+    fn send_base(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_base.to_account_info(),
+                to: self.account_base.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
 }
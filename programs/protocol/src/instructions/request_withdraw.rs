@@ -0,0 +1,75 @@
+use crate::{
+    core_lib::{decimal::Quantity, errors::LibErrors, structs::Operation, Token},
+    events::WithdrawRequestedEvent,
+    structs::{State, Statement, Vaults},
+};
+use anchor_lang::prelude::*;
+use checked_decimal_macro::Decimal;
+
+#[derive(Accounts)]
+pub struct RequestWithdraw<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = statement.load()?.owner == signer.key())]
+    pub statement: AccountLoader<'info, Statement>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+}
+
+impl<'info> RequestWithdraw<'info> {
+    pub fn handler(
+        ctx: Context<RequestWithdraw>,
+        vault: u8,
+        strategy: u8,
+        quantity: u64,
+        base: bool,
+    ) -> anchor_lang::Result<()> {
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+        let statement = &mut ctx.accounts.statement.load_mut()?.statement;
+
+        let vaults_indexes = statement.get_vaults_indexes(&vault);
+        vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
+
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vaults.vault_checked(vault)?, Operation::Withdraw)?;
+
+        let vault_index = vault;
+        let vault = vaults.vault_checked_mut(vault)?;
+        let (ticket, owed) = vault.request_withdraw(
+            statement,
+            ctx.accounts.signer.key().to_bytes(),
+            if base { Token::Base } else { Token::Quote },
+            Quantity::new(quantity),
+            strategy,
+        )?;
+        // value leaves the statement now, claiming it later only moves it out of the reserves
+        vault.record_outflow(statement, owed.base, owed.quote, current_timestamp as u32)?;
+
+        msg!("withdraw ticket: {}", ticket);
+
+        emit!(WithdrawRequestedEvent {
+            vault: vault_index,
+            strategy,
+            owner: ctx.accounts.signer.key(),
+            ticket,
+            base: owed.base.get(),
+            quote: owed.quote.get(),
+        });
+
+        statement.refresh(&vaults.arr.elements)?;
+
+        if !statement.collateralized() {
+            return Err(LibErrors::UserNotCollateralized.into());
+        }
+
+        // debt left for isolated shares to back counts against their debt ceilings
+        statement.back_isolated_debt(&mut vaults.arr.elements)?;
+
+        Ok(())
+    }
+}
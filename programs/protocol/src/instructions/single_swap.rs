@@ -3,6 +3,8 @@ use std::collections::HashSet;
 use crate::{
     core_lib::decimal::Quantity,
     core_lib::errors::LibErrors,
+    core_lib::structs::Operation,
+    events::{prices, swap_fees, utilization, SwapEvent},
     structs::{State, Vaults},
 };
 use anchor_lang::prelude::*;
@@ -60,13 +62,19 @@ impl<'info> SingleSwap<'info> {
         vaults_indexes.insert(vault);
         vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
 
+        let vault_index = vault;
         let vault = vaults.vault_checked_mut(vault)?;
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vault, Operation::Swap)?;
         let quantity = Quantity::new(amount);
 
         if by_amount_out {
             unimplemented!("swaps by amount out are not yet implemented")
         }
 
+        let fees_before = swap_fees(vault);
         let quantity_out = match from_base {
             true => vault.sell(quantity)?,
             false => vault.buy(quantity)?,
@@ -78,6 +86,20 @@ impl<'info> SingleSwap<'info> {
             return Err(LibErrors::NoMinAmountOut.into());
         }
 
+        let fees = swap_fees(vault) - fees_before;
+        let (base_price, quote_price) = prices(vault);
+        emit!(SwapEvent {
+            vault: vault_index,
+            owner: ctx.accounts.signer.key(),
+            sell: from_base,
+            amount_in: amount,
+            amount_out: quantity_out.get(),
+            fee: if from_base { fees.quote } else { fees.base }.get(),
+            base_price,
+            quote_price,
+            utilization: utilization(vault),
+        });
+
         let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
         let signer = &[&seeds[..]];
 
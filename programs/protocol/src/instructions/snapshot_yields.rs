@@ -0,0 +1,41 @@
+use crate::structs::{State, Vaults, YieldHistory};
+use anchor_lang::prelude::*;
+use std::collections::HashSet;
+
+#[derive(Accounts)]
+#[instruction(vault: u8)]
+pub struct SnapshotYields<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut,
+      seeds = [b"yields".as_ref(), &[vault]],
+      bump = yield_history.load()?.bump,
+    )]
+    pub yield_history: AccountLoader<'info, YieldHistory>,
+}
+
+impl SnapshotYields<'_> {
+    /// Snapshots cumulative yields of strategies of the vault, can be called by anyone,
+    /// at most one snapshot per interval is kept
+    pub fn handler(ctx: Context<SnapshotYields>, vault: u8) -> anchor_lang::Result<()> {
+        msg!("DotWave: Snapshot yields");
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+
+        // settles lending fees, so they are a part of the snapshot
+        vaults.refresh(
+            &HashSet::from([vault]),
+            ctx.remaining_accounts,
+            current_timestamp,
+        )?;
+
+        vaults.vault_checked(vault)?.strategies.snapshot_yields(
+            &mut ctx.accounts.yield_history.load_mut()?.strategies,
+            current_timestamp as u32,
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,102 @@
+use crate::{
+    core_lib::{errors::LibErrors, services::swapping::KeptFeeBucket},
+    events::KeptFeeSweptEvent,
+    structs::{Role, State, Vaults},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, transfer, TokenAccount, Transfer};
+use checked_decimal_macro::Decimal;
+
+#[derive(Accounts)]
+#[instruction(vault: u8)]
+pub struct SweepKeptFee<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = state.load()?.has_role(&admin.key(), Role::Treasury))]
+    pub admin: Signer<'info>,
+
+    #[account(mut,
+      constraint = account_base.mint == reserve_base.mint,
+  )]
+    account_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = account_quote.mint == reserve_quote.mint,
+  )]
+    account_quote: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_base.mint == vaults.load()?.keys.get(vault as usize).unwrap().base_token,
+      constraint = reserve_base.key() == vaults.load()?.keys.get(vault as usize).unwrap().base_reserve,
+      constraint = reserve_base.owner == state.key(),
+  )]
+    pub reserve_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_quote.mint == vaults.load()?.keys.get(vault as usize).unwrap().quote_token,
+      constraint = reserve_quote.key() == vaults.load()?.keys.get(vault as usize).unwrap().quote_reserve,
+      constraint = reserve_quote.owner == state.key(),
+  )]
+    pub reserve_quote: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, token::Token>,
+}
+
+impl<'info> SweepKeptFee<'info> {
+    /// Moves kept fee of protocol owned liquidity to given accounts, insurance part feeds the insurance fund
+    pub fn handler(ctx: Context<SweepKeptFee>, vault: u8, bucket: u8) -> anchor_lang::Result<()> {
+        msg!("DotWave: Sweep kept fee");
+
+        let bucket = KeptFeeBucket::try_from(bucket)?;
+
+        if bucket != KeptFeeBucket::Pol {
+            return Err(LibErrors::InvalidKeptFeeBucket.into());
+        }
+
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+        let vault_index = vault;
+        let vault = vaults.vault_checked_mut(vault)?;
+
+        let swept = vault.swap_service()?.kept.sweep(bucket);
+
+        let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
+        let signer = &[&seeds[..]];
+
+        transfer(
+            ctx.accounts.send_base().with_signer(signer),
+            swept.base.get(),
+        )?;
+        transfer(
+            ctx.accounts.send_quote().with_signer(signer),
+            swept.quote.get(),
+        )?;
+
+        emit!(KeptFeeSweptEvent {
+            vault: vault_index,
+            base: swept.base.get(),
+            quote: swept.quote.get(),
+        });
+
+        Ok(())
+    }
+
+    fn send_base(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_base.to_account_info(),
+                to: self.account_base.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+
+    fn send_quote(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_quote.to_account_info(),
+                to: self.account_quote.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+}
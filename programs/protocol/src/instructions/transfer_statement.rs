@@ -0,0 +1,28 @@
+use crate::{events::StatementTransferredEvent, structs::Statement};
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct TransferStatement<'info> {
+    #[account(mut, constraint = statement.load()?.owner == owner.key())]
+    pub statement: AccountLoader<'info, Statement>,
+    pub owner: Signer<'info>,
+    pub new_owner: Signer<'info>,
+}
+
+impl TransferStatement<'_> {
+    /// Hands the statement with all its positions to another wallet, both have to sign
+    pub fn handler(ctx: Context<TransferStatement>) -> anchor_lang::Result<()> {
+        msg!("DotWave: Transfer statement");
+
+        let new_owner = ctx.accounts.new_owner.key();
+        ctx.accounts.statement.load_mut()?.owner = new_owner;
+
+        emit!(StatementTransferredEvent {
+            statement: ctx.accounts.statement.key(),
+            owner: ctx.accounts.owner.key(),
+            new_owner,
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use crate::{
+    core_lib::{decimal::Shares, structs::Operation},
+    events::SharesUnwrappedEvent,
+    structs::{State, Statement, Vaults},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, burn, Burn, Mint, TokenAccount};
+use checked_decimal_macro::Decimal;
+
+#[derive(Accounts)]
+#[instruction(vault: u8, strategy: u8)]
+pub struct UnwrapShares<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = statement.load()?.owner == signer.key())]
+    pub statement: AccountLoader<'info, Statement>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+        constraint = share_mint.key() == *vaults.load()?.keys_checked(vault)?.share_mint(strategy)?,
+    )]
+    pub share_mint: Account<'info, Mint>,
+    #[account(mut,
+        constraint = account_shares.mint == share_mint.key(),
+        constraint = account_shares.owner == signer.key(),
+    )]
+    account_shares: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, token::Token>,
+}
+
+impl<'info> UnwrapShares<'info> {
+    pub fn handler(
+        ctx: Context<UnwrapShares>,
+        vault: u8,
+        strategy: u8,
+        shares: u64,
+    ) -> anchor_lang::Result<()> {
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+        let statement = &mut ctx.accounts.statement.load_mut()?.statement;
+
+        // unwrapping is a way out, it stays open while deposits are paused or the vault is delisted
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vaults.vault_checked(vault)?, Operation::Withdraw)?;
+
+        vaults.vault_checked_mut(vault)?.unwrap_shares(
+            statement,
+            strategy,
+            Shares::new(shares as u128),
+        )?;
+
+        burn(ctx.accounts.burn_shares(), shares)?;
+
+        emit!(SharesUnwrappedEvent {
+            vault,
+            strategy,
+            owner: ctx.accounts.signer.key(),
+            shares,
+        });
+
+        Ok(())
+    }
+
+    fn burn_shares(&self) -> CpiContext<'_, '_, '_, 'info, Burn<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Burn {
+                mint: self.share_mint.to_account_info(),
+                from: self.account_shares.to_account_info(),
+                authority: self.signer.to_account_info(),
+            },
+        )
+    }
+}
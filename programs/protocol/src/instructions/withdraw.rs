@@ -1,9 +1,6 @@
 use crate::{
-    core_lib::{
-        decimal::{BothQuantities, Quantity},
-        errors::LibErrors,
-        Token,
-    },
+    core_lib::{decimal::Quantity, errors::LibErrors, structs::Operation, LiquidityChange, Token},
+    events::{prices, utilization, WithdrawEvent},
     structs::{State, Statement, Vaults},
 };
 use anchor_lang::prelude::*;
@@ -17,7 +14,7 @@ pub struct Withdraw<'info> {
     pub state: AccountLoader<'info, State>,
     #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
     pub vaults: AccountLoader<'info, Vaults>,
-    #[account(mut, seeds = [b"statement".as_ref(), signer.key.as_ref()], bump=statement.load()?.bump, constraint = statement.load()?.owner == signer.key())]
+    #[account(mut, constraint = statement.load()?.owner == signer.key())]
     pub statement: AccountLoader<'info, Statement>,
     #[account(mut)]
     pub signer: Signer<'info>,
@@ -62,14 +59,37 @@ impl<'info> Withdraw<'info> {
         let vaults_indexes = statement.get_vaults_indexes(&vault);
         vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
 
+        let vault_index = vault;
         let vault = vaults.vault_checked_mut(vault)?;
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vault, Operation::Withdraw)?;
 
-        let BothQuantities { base, quote } = vault.withdraw(
+        let LiquidityChange {
+            base,
+            quote,
+            shares,
+        } = vault.withdraw(
             statement,
             if base { Token::Base } else { Token::Quote },
             Quantity::new(quantity),
             strategy,
         )?;
+        vault.record_outflow(statement, base, quote, current_timestamp as u32)?;
+
+        let (base_price, quote_price) = prices(vault);
+        emit!(WithdrawEvent {
+            vault: vault_index,
+            strategy,
+            owner: ctx.accounts.signer.key(),
+            base: base.get(),
+            quote: quote.get(),
+            shares: shares.get(),
+            base_price,
+            quote_price,
+            utilization: utilization(vault),
+        });
 
         statement.refresh(&vaults.arr.elements)?;
 
@@ -77,6 +97,9 @@ impl<'info> Withdraw<'info> {
             return Err(LibErrors::UserNotCollateralized.into());
         }
 
+        // debt left for isolated shares to back counts against their debt ceilings
+        statement.back_isolated_debt(&mut vaults.arr.elements)?;
+
         let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
         let signer = &[&seeds[..]];
 
@@ -0,0 +1,167 @@
+use crate::{
+    core_lib::{decimal::Quantity, errors::LibErrors, structs::Operation, Token},
+    events::{prices, swap_fees, utilization, SwapEvent, WithdrawEvent},
+    structs::{State, Statement, Vaults},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, transfer, TokenAccount, Transfer};
+use checked_decimal_macro::Decimal;
+
+#[derive(Accounts)]
+#[instruction(vault: u8)]
+pub struct WithdrawSingle<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = statement.load()?.owner == signer.key())]
+    pub statement: AccountLoader<'info, Statement>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+      constraint = account_base.mint == reserve_base.mint,
+      constraint = account_base.owner == signer.key(),
+  )]
+    account_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = account_quote.mint == reserve_quote.mint,
+      constraint = account_quote.owner == signer.key(),
+  )]
+    account_quote: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_base.mint == vaults.load()?.keys.get(vault as usize).unwrap().base_token,
+      constraint = reserve_base.key() == vaults.load()?.keys.get(vault as usize).unwrap().base_reserve,
+      constraint = reserve_base.owner == state.key(),
+  )]
+    pub reserve_base: Account<'info, TokenAccount>,
+    #[account(mut,
+      constraint = reserve_quote.mint == vaults.load()?.keys.get(vault as usize).unwrap().quote_token,
+      constraint = reserve_quote.key() == vaults.load()?.keys.get(vault as usize).unwrap().quote_reserve,
+      constraint = reserve_quote.owner == state.key(),
+  )]
+    pub reserve_quote: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, token::Token>,
+}
+
+impl<'info> WithdrawSingle<'info> {
+    pub fn handler(
+        ctx: Context<WithdrawSingle>,
+        vault: u8,
+        strategy: u8,
+        quantity: u64,
+        min_expected: u64,
+        base: bool,
+    ) -> anchor_lang::Result<()> {
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+        let statement = &mut ctx.accounts.statement.load_mut()?.statement;
+
+        let vaults_indexes = statement.get_vaults_indexes(&vault);
+        vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
+        statement.refresh(&vaults.arr.elements)?;
+
+        let vault_index = vault;
+        let vault = vaults.vault_checked_mut(vault)?;
+        let state = ctx.accounts.state.load()?;
+        state.check_active(vault, Operation::Withdraw)?;
+        state.check_active(vault, Operation::Swap)?;
+
+        let fees_before = swap_fees(vault);
+        let (quantity_out, change) = vault.withdraw_single(
+            statement,
+            if base { Token::Base } else { Token::Quote },
+            Quantity::new(quantity),
+            strategy,
+            Quantity::new(min_expected),
+        )?;
+        let (outflow_base, outflow_quote) = match base {
+            true => (quantity_out, Quantity::new(0)),
+            false => (Quantity::new(0), quantity_out),
+        };
+        vault.record_outflow(
+            statement,
+            outflow_base,
+            outflow_quote,
+            current_timestamp as u32,
+        )?;
+
+        let fees = swap_fees(vault) - fees_before;
+        let (base_price, quote_price) = prices(vault);
+        let (swapped, kept) = if base {
+            (change.quote, change.base)
+        } else {
+            (change.base, change.quote)
+        };
+
+        emit!(WithdrawEvent {
+            vault: vault_index,
+            strategy,
+            owner: ctx.accounts.signer.key(),
+            base: change.base.get(),
+            quote: change.quote.get(),
+            shares: change.shares.get(),
+            base_price,
+            quote_price,
+            utilization: utilization(vault),
+        });
+
+        if !swapped.is_zero() {
+            emit!(SwapEvent {
+                vault: vault_index,
+                owner: ctx.accounts.signer.key(),
+                sell: !base,
+                amount_in: swapped.get(),
+                amount_out: (quantity_out - kept).get(),
+                fee: if base { fees.base } else { fees.quote }.get(),
+                base_price,
+                quote_price,
+                utilization: utilization(vault),
+            });
+        }
+
+        statement.refresh(&vaults.arr.elements)?;
+
+        if !statement.collateralized() {
+            return Err(LibErrors::UserNotCollateralized.into());
+        }
+
+        // debt left for isolated shares to back counts against their debt ceilings
+        statement.back_isolated_debt(&mut vaults.arr.elements)?;
+
+        let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
+        let signer = &[&seeds[..]];
+
+        let send = if base {
+            ctx.accounts.send_base()
+        } else {
+            ctx.accounts.send_quote()
+        };
+
+        transfer(send.with_signer(signer), quantity_out.get())?;
+
+        Ok(())
+    }
+
+    fn send_base(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_base.to_account_info(),
+                to: self.account_base.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+
+    fn send_quote(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_quote.to_account_info(),
+                to: self.account_quote.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+}
@@ -0,0 +1,93 @@
+use crate::{
+    core_lib::{decimal::Shares, errors::LibErrors, structs::Operation},
+    events::SharesWrappedEvent,
+    structs::{State, Statement, Vaults},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, mint_to, Mint, MintTo, TokenAccount};
+use checked_decimal_macro::Decimal;
+
+#[derive(Accounts)]
+#[instruction(vault: u8, strategy: u8)]
+pub struct WrapShares<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = statement.load()?.owner == signer.key())]
+    pub statement: AccountLoader<'info, Statement>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+        constraint = share_mint.key() == *vaults.load()?.keys_checked(vault)?.share_mint(strategy)?,
+    )]
+    pub share_mint: Account<'info, Mint>,
+    #[account(mut,
+        constraint = account_shares.mint == share_mint.key(),
+        constraint = account_shares.owner == signer.key(),
+    )]
+    account_shares: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, token::Token>,
+}
+
+impl<'info> WrapShares<'info> {
+    pub fn handler(
+        ctx: Context<WrapShares>,
+        vault: u8,
+        strategy: u8,
+        shares: u64,
+    ) -> anchor_lang::Result<()> {
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+        let statement = &mut ctx.accounts.statement.load_mut()?.statement;
+
+        let vaults_indexes = statement.get_vaults_indexes(&vault);
+        vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
+
+        ctx.accounts
+            .state
+            .load()?
+            .check_active(vaults.vault_checked(vault)?, Operation::Withdraw)?;
+
+        vaults.vault_checked_mut(vault)?.wrap_shares(
+            statement,
+            strategy,
+            Shares::new(shares as u128),
+        )?;
+
+        statement.refresh(&vaults.arr.elements)?;
+
+        if !statement.collateralized() {
+            return Err(LibErrors::UserNotCollateralized.into());
+        }
+
+        // debt left for isolated shares to back counts against their debt ceilings
+        statement.back_isolated_debt(&mut vaults.arr.elements)?;
+
+        emit!(SharesWrappedEvent {
+            vault,
+            strategy,
+            owner: ctx.accounts.signer.key(),
+            shares,
+        });
+
+        let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
+        let signer = &[&seeds[..]];
+
+        mint_to(ctx.accounts.mint_shares().with_signer(signer), shares)?;
+
+        Ok(())
+    }
+
+    fn mint_shares(&self) -> CpiContext<'_, '_, '_, 'info, MintTo<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            MintTo {
+                mint: self.share_mint.to_account_info(),
+                to: self.account_shares.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    events::DebtWrittenOffEvent,
+    structs::{Role, State, Statement, Vaults},
+};
+use anchor_lang::prelude::*;
+use checked_decimal_macro::Decimal;
+
+#[derive(Accounts)]
+pub struct WriteOffDebt<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut)]
+    pub statement: AccountLoader<'info, Statement>,
+    #[account(mut, constraint = state.load()?.has_role(&admin.key(), Role::RiskManager))]
+    pub admin: Signer<'info>,
+}
+
+impl<'info> WriteOffDebt<'info> {
+    /// Clears residual debt of an insolvent statement, no tokens are moved
+    pub fn handler(ctx: Context<WriteOffDebt>, vault: u8) -> anchor_lang::Result<()> {
+        msg!("DotWave: Write off debt");
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+        let user_statement = &mut ctx.accounts.statement.load_mut()?.statement;
+
+        let vaults_indexes = user_statement.get_vaults_indexes(&vault);
+        vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
+        user_statement.refresh(&vaults.arr.elements)?;
+
+        let vault_index = vault;
+        let vault = vaults.vault_checked_mut(vault)?;
+        let write_off = vault.write_off(user_statement)?;
+
+        emit!(DebtWrittenOffEvent {
+            vault: vault_index,
+            statement: ctx.accounts.statement.key(),
+            debt: write_off.debt.get(),
+            covered: write_off.covered.get(),
+            socialized: write_off.socialized.get(),
+            shares: write_off.shares.get(),
+        });
+
+        user_statement.release_isolated_debt(&mut vaults.arr.elements)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,177 @@
+use std::collections::HashSet;
+
+use crate::{
+    core_lib::{
+        decimal::{Quantity, Shares},
+        structs::Operation,
+        Token,
+    },
+    events::{prices, swap_fees, utilization, DepositEvent, SwapEvent},
+    structs::{State, Statement, Vaults},
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token::{self, transfer, TokenAccount, Transfer};
+use checked_decimal_macro::Decimal;
+
+#[derive(Accounts)]
+#[instruction(vault: u8)]
+pub struct ZapDeposit<'info> {
+    #[account(mut, seeds = [b"state".as_ref()], bump=state.load()?.bump)]
+    pub state: AccountLoader<'info, State>,
+    #[account(mut, constraint = vaults.key() == state.load()?.vaults_acc)]
+    pub vaults: AccountLoader<'info, Vaults>,
+    #[account(mut, constraint = statement.load()?.owner == signer.key())]
+    pub statement: AccountLoader<'info, Statement>,
+    #[account(mut)]
+    pub signer: Signer<'info>,
+
+    #[account(mut,
+        constraint = account_base.mint == reserve_base.mint,
+        constraint = account_base.owner == signer.key(),
+    )]
+    account_base: Account<'info, TokenAccount>,
+    #[account(mut,
+        constraint = account_quote.mint == reserve_quote.mint,
+        constraint = account_quote.owner == signer.key(),
+    )]
+    account_quote: Account<'info, TokenAccount>,
+    #[account(mut,
+        constraint = reserve_base.mint == vaults.load()?.keys.get(vault as usize).unwrap().base_token,
+        constraint = reserve_base.key() == vaults.load()?.keys.get(vault as usize).unwrap().base_reserve,
+        constraint = reserve_base.owner == state.key(),
+    )]
+    pub reserve_base: Account<'info, TokenAccount>,
+    #[account(mut,
+        constraint = reserve_quote.mint == vaults.load()?.keys.get(vault as usize).unwrap().quote_token,
+        constraint = reserve_quote.key() == vaults.load()?.keys.get(vault as usize).unwrap().quote_reserve,
+        constraint = reserve_quote.owner == state.key(),
+    )]
+    pub reserve_quote: Account<'info, TokenAccount>,
+    pub token_program: Program<'info, token::Token>,
+}
+
+impl<'info> ZapDeposit<'info> {
+    pub fn handler(
+        ctx: Context<ZapDeposit>,
+        vault: u8,
+        strategy: u8,
+        quantity: u64,
+        min_shares: u128,
+        base: bool,
+    ) -> anchor_lang::Result<()> {
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let vaults = &mut ctx.accounts.vaults.load_mut()?;
+        let statement = &mut ctx.accounts.statement.load_mut()?.statement;
+
+        let mut vaults_indexes = HashSet::new();
+        vaults_indexes.insert(vault);
+        vaults.refresh(&vaults_indexes, ctx.remaining_accounts, current_timestamp)?;
+
+        let vault_index = vault;
+        let vault = vaults.vault_checked_mut(vault)?;
+        let state = ctx.accounts.state.load()?;
+        state.check_active(vault, Operation::Deposit)?;
+        state.check_active(vault, Operation::Swap)?;
+
+        let fees_before = swap_fees(vault);
+        let zap = vault.zap_deposit(
+            statement,
+            if base { Token::Base } else { Token::Quote },
+            Quantity::new(quantity),
+            strategy,
+            Shares::new(min_shares),
+        )?;
+
+        let seeds = &[b"state".as_ref(), &[ctx.accounts.state.load()?.bump]];
+        let signer = &[&seeds[..]];
+
+        let (take, send) = if base {
+            (ctx.accounts.take_base(), ctx.accounts.send_quote())
+        } else {
+            (ctx.accounts.take_quote(), ctx.accounts.send_base())
+        };
+
+        transfer(take, zap.taken().get())?;
+
+        if !zap.refund().is_zero() {
+            transfer(send.with_signer(signer), zap.refund().get())?;
+        }
+
+        let fees = swap_fees(vault) - fees_before;
+        let (base_price, quote_price) = prices(vault);
+        let (base_deposited, quote_deposited) = if base {
+            (zap.deposited, zap.deposited_other)
+        } else {
+            (zap.deposited_other, zap.deposited)
+        };
+
+        emit!(SwapEvent {
+            vault: vault_index,
+            owner: ctx.accounts.signer.key(),
+            sell: base,
+            amount_in: zap.swapped.get(),
+            amount_out: zap.received.get(),
+            fee: if base { fees.quote } else { fees.base }.get(),
+            base_price,
+            quote_price,
+            utilization: utilization(vault),
+        });
+        emit!(DepositEvent {
+            vault: vault_index,
+            strategy,
+            owner: ctx.accounts.signer.key(),
+            base: base_deposited.get(),
+            quote: quote_deposited.get(),
+            shares: zap.shares.get(),
+            base_price,
+            quote_price,
+            utilization: utilization(vault),
+        });
+
+        Ok(())
+    }
+
+    fn take_base(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.account_base.to_account_info(),
+                to: self.reserve_base.to_account_info(),
+                authority: self.signer.to_account_info(),
+            },
+        )
+    }
+
+    fn take_quote(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.account_quote.to_account_info(),
+                to: self.reserve_quote.to_account_info(),
+                authority: self.signer.to_account_info(),
+            },
+        )
+    }
+
+    fn send_base(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_base.to_account_info(),
+                to: self.account_base.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+
+    fn send_quote(&self) -> CpiContext<'_, '_, '_, 'info, Transfer<'info>> {
+        CpiContext::new(
+            self.token_program.to_account_info(),
+            Transfer {
+                from: self.reserve_quote.to_account_info(),
+                to: self.account_quote.to_account_info(),
+                authority: self.state.to_account_info(),
+            },
+        )
+    }
+}
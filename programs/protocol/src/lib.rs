@@ -4,6 +4,9 @@ mod structs;
 #[cfg(feature = "anchor")]
 mod pyth;
 
+#[cfg(feature = "anchor")]
+pub mod events;
+
 #[cfg(feature = "anchor")]
 mod instructions;
 
@@ -29,10 +32,26 @@ pub mod protocol {
         instructions::create_statement::handler(ctx)
     }
 
+    pub fn close_statement(ctx: Context<CloseStatement>) -> Result<()> {
+        CloseStatement::handler(ctx)
+    }
+
+    pub fn transfer_statement(ctx: Context<TransferStatement>) -> Result<()> {
+        TransferStatement::handler(ctx)
+    }
+
     pub fn init_vault(ctx: Context<InitVault>) -> Result<()> {
         ctx.accounts.handler()
     }
 
+    pub fn init_yield_history(ctx: Context<InitYieldHistory>, vault: u8) -> Result<()> {
+        InitYieldHistory::handler(ctx, vault)
+    }
+
+    pub fn snapshot_yields(ctx: Context<SnapshotYields>, vault: u8) -> Result<()> {
+        SnapshotYields::handler(ctx, vault)
+    }
+
     pub fn enable_oracle(
         ctx: Context<EnableOracle>,
         index: u8,
@@ -117,6 +136,10 @@ pub mod protocol {
         )
     }
 
+    pub fn init_share_mint(ctx: Context<InitShareMint>, vault: u8, strategy: u8) -> Result<()> {
+        ctx.accounts.handler(vault, strategy)
+    }
+
     pub fn deposit(
         ctx: Context<Deposit>,
         vault: u8,
@@ -127,6 +150,17 @@ pub mod protocol {
         Deposit::handler(ctx, vault, strategy, quantity, base)
     }
 
+    pub fn zap_deposit(
+        ctx: Context<ZapDeposit>,
+        vault: u8,
+        strategy: u8,
+        quantity: u64,
+        min_shares: u128,
+        base: bool,
+    ) -> Result<()> {
+        ZapDeposit::handler(ctx, vault, strategy, quantity, min_shares, base)
+    }
+
     pub fn withdraw(
         ctx: Context<Withdraw>,
         vault: u8,
@@ -137,6 +171,63 @@ pub mod protocol {
         Withdraw::handler(ctx, vault, strategy, quantity, base)
     }
 
+    pub fn request_withdraw(
+        ctx: Context<RequestWithdraw>,
+        vault: u8,
+        strategy: u8,
+        quantity: u64,
+        base: bool,
+    ) -> Result<()> {
+        RequestWithdraw::handler(ctx, vault, strategy, quantity, base)
+    }
+
+    pub fn claim_withdraw(
+        ctx: Context<ClaimWithdraw>,
+        vault: u8,
+        strategy: u8,
+        ticket: u64,
+    ) -> Result<()> {
+        ClaimWithdraw::handler(ctx, vault, strategy, ticket)
+    }
+
+    pub fn cancel_withdraw(
+        ctx: Context<CancelWithdraw>,
+        vault: u8,
+        strategy: u8,
+        ticket: u64,
+    ) -> Result<()> {
+        CancelWithdraw::handler(ctx, vault, strategy, ticket)
+    }
+
+    pub fn withdraw_single(
+        ctx: Context<WithdrawSingle>,
+        vault: u8,
+        strategy: u8,
+        quantity: u64,
+        min_expected: u64,
+        base: bool,
+    ) -> Result<()> {
+        WithdrawSingle::handler(ctx, vault, strategy, quantity, min_expected, base)
+    }
+
+    pub fn wrap_shares(
+        ctx: Context<WrapShares>,
+        vault: u8,
+        strategy: u8,
+        shares: u64,
+    ) -> Result<()> {
+        WrapShares::handler(ctx, vault, strategy, shares)
+    }
+
+    pub fn unwrap_shares(
+        ctx: Context<UnwrapShares>,
+        vault: u8,
+        strategy: u8,
+        shares: u64,
+    ) -> Result<()> {
+        UnwrapShares::handler(ctx, vault, strategy, shares)
+    }
+
     pub fn single_swap(
         ctx: Context<SingleSwap>,
         vault: u8,
@@ -180,14 +271,207 @@ pub mod protocol {
             .modify_fee_curve(vault, service, base, bound, a, b, c)
     }
 
+    pub fn set_fee_curve(
+        ctx: Context<Admin>,
+        vault: u8,
+        service: u8,
+        base: bool,
+        segments: Vec<FeeCurveSegment>,
+    ) -> Result<()> {
+        ctx.accounts.set_fee_curve(vault, service, base, segments)
+    }
+
+    pub fn schedule_fee_curve(
+        ctx: Context<Admin>,
+        vault: u8,
+        service: u8,
+        base: bool,
+        segments: Vec<FeeCurveSegment>,
+    ) -> Result<()> {
+        ctx.accounts
+            .schedule_fee_curve(vault, service, base, segments)
+    }
+
+    pub fn set_jump_rate_curve(
+        ctx: Context<Admin>,
+        vault: u8,
+        service: u8,
+        base: bool,
+        base_fee: u64,
+        slope: u64,
+        kink: u64,
+        jump_slope: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .set_jump_rate_curve(vault, service, base, base_fee, slope, kink, jump_slope)
+    }
+
+    pub fn set_debt_ceiling(
+        ctx: Context<Admin>,
+        vault: u8,
+        debt_ceiling: Option<u64>,
+    ) -> Result<()> {
+        ctx.accounts.set_debt_ceiling(vault, debt_ceiling)
+    }
+
+    pub fn set_outflow_limits(
+        ctx: Context<Admin>,
+        vault: u8,
+        window: u32,
+        vault_limit: u64,
+        statement_limit: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .set_outflow_limits(vault, window, vault_limit, statement_limit)
+    }
+
+    pub fn set_kept_fee_split(
+        ctx: Context<Admin>,
+        vault: u8,
+        pol_part: u64,
+        burn_part: u64,
+    ) -> Result<()> {
+        ctx.accounts.set_kept_fee_split(vault, pol_part, burn_part)
+    }
+
+    pub fn set_inventory_skew(
+        ctx: Context<Admin>,
+        vault: u8,
+        target: u64,
+        factor: u64,
+    ) -> Result<()> {
+        ctx.accounts.set_inventory_skew(vault, target, factor)
+    }
+
+    pub fn set_shared_swap(
+        ctx: Context<Admin>,
+        vault: u8,
+        strategy: u8,
+        shared: bool,
+    ) -> Result<()> {
+        ctx.accounts.set_shared_swap(vault, strategy, shared)
+    }
+
+    pub fn set_strategy_risk_params(
+        ctx: Context<Admin>,
+        vault: u8,
+        strategy: u8,
+        collateral_ratio: u64,
+        liquidation_threshold: u64,
+    ) -> Result<()> {
+        ctx.accounts.set_strategy_risk_params(
+            vault,
+            strategy,
+            collateral_ratio,
+            liquidation_threshold,
+        )
+    }
+
+    pub fn set_strategy_services(
+        ctx: Context<Admin>,
+        vault: u8,
+        strategy: u8,
+        lending: bool,
+        swapping: bool,
+        trading: bool,
+    ) -> Result<()> {
+        ctx.accounts
+            .set_strategy_services(vault, strategy, lending, swapping, trading)
+    }
+
+    pub fn set_strategy_state(
+        ctx: Context<Admin>,
+        vault: u8,
+        strategy: u8,
+        state: u8,
+    ) -> Result<()> {
+        ctx.accounts.set_strategy_state(vault, strategy, state)
+    }
+
+    pub fn set_insurance_params(
+        ctx: Context<Admin>,
+        vault: u8,
+        trade_fee_part: u64,
+        profit_threshold: u64,
+    ) -> Result<()> {
+        ctx.accounts
+            .set_insurance_params(vault, trade_fee_part, profit_threshold)
+    }
+
+    pub fn set_pause(
+        ctx: Context<Admin>,
+        vault: Option<u8>,
+        operation: u8,
+        paused: bool,
+    ) -> Result<()> {
+        ctx.accounts.set_pause(vault, operation, paused)
+    }
+
+    pub fn propose_admin(ctx: Context<Admin>, pending_admin: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.propose_admin(pending_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        AcceptAdmin::handler(ctx)
+    }
+
+    pub fn set_role(ctx: Context<Admin>, role: u8, authority: Pubkey) -> Result<()> {
+        ctx.accounts.set_role(role, authority)
+    }
+
+    pub fn schedule_change(
+        ctx: Context<Admin>,
+        vault: u8,
+        strategy: u8,
+        kind: u8,
+        values: Vec<u64>,
+    ) -> Result<()> {
+        ctx.accounts.schedule_change(vault, strategy, kind, values)
+    }
+
+    pub fn execute_change(ctx: Context<ExecuteChange>, id: u64) -> Result<()> {
+        ExecuteChange::handler(ctx, id)
+    }
+
+    pub fn cancel_change(ctx: Context<Admin>, id: u64) -> Result<()> {
+        ctx.accounts.cancel_change(id)
+    }
+
+    pub fn sweep_kept_fee(ctx: Context<SweepKeptFee>, vault: u8, bucket: u8) -> Result<()> {
+        SweepKeptFee::handler(ctx, vault, bucket)
+    }
+
+    pub fn burn_kept_fee(ctx: Context<BurnKeptFee>, vault: u8) -> Result<()> {
+        BurnKeptFee::handler(ctx, vault)
+    }
+
     pub fn borrow(ctx: Context<Borrow>, vault: u8, amount: u64) -> Result<()> {
         Borrow::handler(ctx, vault, amount)
     }
 
     pub fn repay(ctx: Context<Repay>, vault: u8, amount: u64) -> Result<()> {
+        ctx.accounts.check_owner()?;
         Repay::handler(ctx, vault, amount)
     }
 
+    pub fn repay_for(ctx: Context<Repay>, vault: u8, amount: u64) -> Result<()> {
+        Repay::handler(ctx, vault, amount)
+    }
+
+    pub fn repay_with_collateral(
+        ctx: Context<Repay>,
+        vault: u8,
+        strategy: u8,
+        amount: u64,
+        min_base_out: u64,
+    ) -> Result<()> {
+        Repay::with_collateral_handler(ctx, vault, strategy, amount, min_base_out)
+    }
+
+    pub fn write_off_debt(ctx: Context<WriteOffDebt>, vault: u8) -> Result<()> {
+        WriteOffDebt::handler(ctx, vault)
+    }
+
     pub fn open_position(
         ctx: Context<OpenPosition>,
         vault: u8,
@@ -200,11 +484,26 @@ pub mod protocol {
     pub fn close_position(ctx: Context<ClosePosition>, vault: u8) -> Result<()> {
         ClosePosition::handler(ctx, vault)
     }
+
+    pub fn delist_vault(ctx: Context<DelistVault>, vault: u8) -> Result<()> {
+        DelistVault::handler(ctx, vault)
+    }
+
+    pub fn force_close_position(ctx: Context<ForceClosePosition>, vault: u8) -> Result<()> {
+        ForceClosePosition::handler(ctx, vault)
+    }
+
+    pub fn close_vault(ctx: Context<CloseVault>, vault: u8) -> Result<()> {
+        CloseVault::handler(ctx, vault)
+    }
 }
 
 #[cfg(feature = "wasm")]
 pub mod wasm_wrapper;
 
+#[cfg(feature = "indexer")]
+pub mod indexer;
+
 #[cfg(feature = "wasm")]
 mod decoder {
     use bytemuck::{Pod, Zeroable};
@@ -1,7 +1,9 @@
 pub mod state;
 pub mod statement;
 pub mod vaults;
+pub mod yield_history;
 
 pub use state::*;
 pub use statement::*;
 pub use vaults::*;
+pub use yield_history::*;
@@ -1,7 +1,56 @@
+use crate::core_lib::{
+    errors::LibErrors,
+    structs::{Operation, PauseFlags, Timelock},
+    Vault,
+};
+
+/// Permissions that can be handed out by the admin, the admin itself holds all of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Role {
+    Admin = 0,
+    /// changes fee curves, ratios and other risk parameters
+    RiskManager = 1,
+    /// enables and overrides oracles
+    OracleManager = 2,
+    /// pauses and resumes operations
+    Pauser = 3,
+    /// collects kept fees and decides where they go
+    Treasury = 4,
+}
+
+impl TryFrom<u8> for Role {
+    type Error = LibErrors;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Admin),
+            1 => Ok(Self::RiskManager),
+            2 => Ok(Self::OracleManager),
+            3 => Ok(Self::Pauser),
+            4 => Ok(Self::Treasury),
+            _ => Err(LibErrors::InvalidRole),
+        }
+    }
+}
+
 #[cfg(feature = "anchor")]
 mod zero {
+    use super::{PauseFlags, Timelock};
     use anchor_lang::prelude::*;
 
+    pub type Authority = Pubkey;
+
+    #[zero_copy]
+    #[repr(C)]
+    #[derive(Debug, Default, PartialEq)]
+    pub struct Roles {
+        pub risk_manager: Pubkey,
+        pub oracle_manager: Pubkey,
+        pub pauser: Pubkey,
+        pub treasury: Pubkey,
+    }
+
     #[account(zero_copy)]
     #[repr(C)]
     #[derive(Debug, Default)]
@@ -9,11 +58,31 @@ mod zero {
         pub bump: u8,
         pub admin: Pubkey,
         pub vaults_acc: Pubkey,
+        /// operations stopped in every vault
+        pub pause: PauseFlags,
+        /// proposed admin, becomes the admin once it accepts
+        pub pending_admin: Option<Pubkey>,
+        /// keys holding roles other than admin, default key if not handed out
+        pub roles: Roles,
+        /// risk parameter changes waiting out their delay
+        pub timelock: Timelock,
     }
 }
 
 #[cfg(feature = "wasm")]
 mod non_zero {
+    use super::{PauseFlags, Timelock};
+
+    pub type Authority = [u8; 32];
+
+    #[repr(C)]
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct Roles {
+        pub risk_manager: [u8; 32],
+        pub oracle_manager: [u8; 32],
+        pub pauser: [u8; 32],
+        pub treasury: [u8; 32],
+    }
 
     #[repr(C)]
     #[derive(Debug, Default, Clone, Copy)]
@@ -22,6 +91,14 @@ mod non_zero {
         pub bump: u8,
         pub admin: [u8; 32],
         pub vaults_acc: [u8; 32],
+        /// operations stopped in every vault
+        pub pause: PauseFlags,
+        /// proposed admin, becomes the admin once it accepts
+        pub pending_admin: Option<[u8; 32]>,
+        /// keys holding roles other than admin, default key if not handed out
+        pub roles: Roles,
+        /// risk parameter changes waiting out their delay
+        pub timelock: Timelock,
     }
     #[automatically_derived]
     unsafe impl bytemuck::Pod for State {}
@@ -30,6 +107,98 @@ mod non_zero {
 }
 
 #[cfg(not(feature = "anchor"))]
-pub use non_zero::State;
+pub use non_zero::{Authority, State};
 #[cfg(feature = "anchor")]
-pub use zero::State;
+pub use zero::{Authority, Roles, State};
+
+impl State {
+    /// Fails if the operation is paused protocol-wide or in the given vault, or the vault is delisted
+    pub fn check_active(&self, vault: &Vault, operation: Operation) -> Result<(), LibErrors> {
+        self.pause.check(operation)?;
+        vault.pause.check(operation)?;
+        vault.check_listed(operation)
+    }
+
+    pub fn role(&self, role: Role) -> &Authority {
+        match role {
+            Role::Admin => &self.admin,
+            Role::RiskManager => &self.roles.risk_manager,
+            Role::OracleManager => &self.roles.oracle_manager,
+            Role::Pauser => &self.roles.pauser,
+            Role::Treasury => &self.roles.treasury,
+        }
+    }
+
+    /// Hands the role out to a key, admin can only be changed by a transfer
+    pub fn set_role(&mut self, role: Role, key: Authority) -> Result<(), LibErrors> {
+        let slot = match role {
+            Role::Admin => return Err(LibErrors::InvalidRole),
+            Role::RiskManager => &mut self.roles.risk_manager,
+            Role::OracleManager => &mut self.roles.oracle_manager,
+            Role::Pauser => &mut self.roles.pauser,
+            Role::Treasury => &mut self.roles.treasury,
+        };
+
+        *slot = key;
+        Ok(())
+    }
+
+    pub fn has_role(&self, key: &Authority, role: Role) -> bool {
+        *key == self.admin || *key == *self.role(role)
+    }
+
+    pub fn authorize(&self, key: &Authority, role: Role) -> Result<(), LibErrors> {
+        match self.has_role(key, role) {
+            true => Ok(()),
+            false => Err(LibErrors::MissingRole),
+        }
+    }
+
+    pub fn propose_admin(&mut self, pending_admin: Option<Authority>) {
+        self.pending_admin = pending_admin;
+    }
+
+    /// Second step of the admin transfer, has to be signed by the proposed key
+    pub fn accept_admin(&mut self, key: Authority) -> Result<(), LibErrors> {
+        if self.pending_admin != Some(key) {
+            return Err(LibErrors::NotPendingAdmin);
+        }
+
+        self.admin = key;
+        self.pending_admin = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roles() {
+        let mut state = State {
+            admin: [1; 32],
+            ..Default::default()
+        };
+
+        assert_eq!(state.set_role(Role::Pauser, [2; 32]), Ok(()));
+        assert_eq!(
+            state.set_role(Role::Admin, [2; 32]),
+            Err(LibErrors::InvalidRole)
+        );
+
+        assert!(state.has_role(&[2; 32], Role::Pauser));
+        assert!(state.has_role(&[1; 32], Role::Pauser));
+        assert_eq!(
+            state.authorize(&[2; 32], Role::RiskManager),
+            Err(LibErrors::MissingRole)
+        );
+
+        state.propose_admin(Some([3; 32]));
+        assert_eq!(state.accept_admin([2; 32]), Err(LibErrors::NotPendingAdmin));
+        assert_eq!(state.accept_admin([3; 32]), Ok(()));
+        assert_eq!(state.admin, [3; 32]);
+        assert_eq!(state.pending_admin, None);
+        assert!(!state.has_role(&[1; 32], Role::Treasury));
+    }
+}
@@ -1,10 +1,19 @@
-use crate::core_lib::{errors::LibErrors, Vault};
+use crate::core_lib::{
+    errors::LibErrors,
+    vault::{VaultStatus, MAX_VAULTS},
+    Vault,
+};
+
+// `SafeArray` needs a literal length, the arrays below are sized by hand
+const _: () = assert!(MAX_VAULTS == 10);
 
 #[cfg(feature = "anchor")]
 mod zero {
     use super::*;
     use crate::core_lib::structs::Oracle;
+    use crate::events::{utilization, LendFeesSettledEvent};
     use anchor_lang::prelude::*;
+    use checked_decimal_macro::Decimal;
 
     use checked_decimal_macro::num_traits::ToPrimitive;
     use std::collections::HashSet;
@@ -22,6 +31,7 @@ mod zero {
         pub quote_reserve: Pubkey,
         pub base_oracle: Option<Pubkey>,
         pub quote_oracle: Option<Pubkey>,
+        pub share_mints: [Option<Pubkey>; 6],
     }
 
     impl VaultKeys {
@@ -32,6 +42,14 @@ mod zero {
         pub fn quote_oracle(&self) -> std::result::Result<&Pubkey, LibErrors> {
             Ok(self.quote_oracle.as_ref().ok_or(LibErrors::PubkeyMissing)?)
         }
+
+        pub fn share_mint(&self, strategy: u8) -> std::result::Result<&Pubkey, LibErrors> {
+            Ok(self
+                .share_mints
+                .get(strategy as usize)
+                .and_then(|mint| mint.as_ref())
+                .ok_or(LibErrors::PubkeyMissing)?)
+        }
     }
 
     #[zero_copy]
@@ -83,7 +101,21 @@ mod zero {
             for index in vaults {
                 let (vault, vault_keys) = self.vault_with_keys(*index)?;
 
-                vault.refresh(current_timestamp as u32)?;
+                let settled = vault.refresh(current_timestamp as u32)?;
+
+                if !settled.is_zero() {
+                    emit!(LendFeesSettledEvent {
+                        vault: *index,
+                        settled: settled.get(),
+                        utilization: utilization(vault),
+                        timestamp: current_timestamp,
+                    });
+                }
+
+                // oracles of delisted vaults keep the final price
+                if vault.status != VaultStatus::Active {
+                    continue;
+                }
 
                 if let Some(ref mut base_oracle) = vault.oracle {
                     Self::update_oracle_from_accs(
@@ -127,6 +159,7 @@ mod non_zero {
         pub quote_reserve: [u8; 32],
         pub base_oracle: Option<[u8; 32]>,
         pub quote_oracle: Option<[u8; 32]>,
+        pub share_mints: [Option<[u8; 32]>; 6],
     }
 
     #[repr(C)]
@@ -190,6 +223,26 @@ impl Vaults {
             .ok_or(LibErrors::NoVaultOnIndex)?)
     }
 
+    /// First slot freed by a closed vault
+    pub fn free_slot(&self) -> Option<u8> {
+        self.arr
+            .iter()?
+            .position(|vault| vault.status == VaultStatus::Closed)
+            .map(|index| index as u8)
+    }
+
+    /// Wipes the vault and its keys, leaving the slot to be taken by a new vault
+    pub fn free(&mut self, index: u8) -> Result<(), LibErrors> {
+        *self.vault_checked_mut(index)? = Vault {
+            status: VaultStatus::Closed,
+            id: index,
+            ..Default::default()
+        };
+        *self.keys_checked_mut(index)? = VaultKeys::default();
+
+        Ok(())
+    }
+
     pub fn vault_with_keys(&mut self, index: u8) -> Result<(&mut Vault, &VaultKeys), LibErrors> {
         let Self { arr, keys, .. } = self;
 
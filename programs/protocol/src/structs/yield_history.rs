@@ -0,0 +1,52 @@
+use crate::core_lib::{
+    errors::LibErrors,
+    strategy::{YieldSnapshots, MAX_STRATEGIES},
+};
+
+#[cfg(feature = "anchor")]
+mod zero {
+    use super::{YieldSnapshots, MAX_STRATEGIES};
+    use anchor_lang::prelude::*;
+
+    /// Snapshots of cumulative yields of the vault strategies, kept apart from the vaults account
+    #[account(zero_copy)]
+    #[repr(C)]
+    #[derive(Debug, Default)]
+    pub struct YieldHistory {
+        pub vault: u8,
+        pub bump: u8,
+        /// one per strategy slot of the vault
+        pub strategies: [YieldSnapshots; MAX_STRATEGIES],
+    }
+}
+
+#[cfg(feature = "wasm")]
+mod non_zero {
+    use super::{YieldSnapshots, MAX_STRATEGIES};
+
+    #[repr(C)]
+    #[derive(Debug, Default, Clone, Copy)]
+    pub struct YieldHistory {
+        pub padding: [u8; 8],
+        pub vault: u8,
+        pub bump: u8,
+        /// one per strategy slot of the vault
+        pub strategies: [YieldSnapshots; MAX_STRATEGIES],
+    }
+
+    unsafe impl bytemuck::Pod for YieldHistory {}
+    unsafe impl bytemuck::Zeroable for YieldHistory {}
+}
+
+#[cfg(feature = "wasm")]
+pub use non_zero::YieldHistory;
+#[cfg(feature = "anchor")]
+pub use zero::YieldHistory;
+
+impl YieldHistory {
+    pub fn strategy(&self, index: u8) -> Result<&YieldSnapshots, LibErrors> {
+        self.strategies
+            .get(index as usize)
+            .ok_or(LibErrors::NoStrategyOnIndex)
+    }
+}
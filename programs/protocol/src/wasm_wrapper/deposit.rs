@@ -1,8 +1,8 @@
 use super::vault::VaultsAccount;
 use crate::core_lib::{
-    decimal::{BothQuantities, Decimal, Quantity},
+    decimal::{Decimal, Quantity},
     user::UserStatement,
-    Token,
+    LiquidityChange, Token,
 };
 use crate::wasm_wrapper::StatementAccount;
 use js_sys::Uint8Array;
@@ -35,7 +35,7 @@ impl VaultsAccount {
             Token::Quote
         };
 
-        let BothQuantities { base, quote } =
+        let LiquidityChange { base, quote, .. } =
             vault.withdraw(user_statement, deposit_token, amount, strategy)?;
 
         Ok(WithdrawAmounts {
@@ -67,6 +67,7 @@ impl VaultsAccount {
 
         Ok(vault
             .deposit(&mut temp_user_statement, deposit_token, amount, strategy)?
+            .other(deposit_token)
             .get())
     }
 }
@@ -1,9 +1,10 @@
 use std::ops::{Deref, DerefMut};
 
-use crate::structs::State;
+use crate::core_lib::structs::Operation;
+use crate::structs::{Role, State};
 use crate::wasm_wrapper::to_buffer;
 use crate::ZeroCopyDecoder;
-use js_sys::Uint8Array;
+use js_sys::{Array, Uint8Array};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen]
@@ -24,6 +25,17 @@ impl DerefMut for StateAccount {
     }
 }
 
+#[wasm_bindgen(getter_with_clone)]
+pub struct PendingChangeInfo {
+    pub id: u64,
+    /// time after which the change can be executed
+    pub eta: u32,
+    pub kind: u8,
+    pub vault: u8,
+    pub strategy: u8,
+    pub values: Vec<u64>,
+}
+
 #[wasm_bindgen]
 impl StateAccount {
     #[wasm_bindgen]
@@ -42,4 +54,52 @@ impl StateAccount {
     pub fn get_vaults_account(&self) -> Result<Uint8Array, JsValue> {
         Ok(to_buffer(&self.account.vaults_acc))
     }
+
+    /// Whether the operation is paused in every vault
+    #[wasm_bindgen]
+    pub fn is_paused(&self, operation: u8) -> Result<bool, JsError> {
+        Ok(self.pause.is_paused(Operation::try_from(operation)?))
+    }
+
+    #[wasm_bindgen]
+    pub fn get_admin(&self) -> Uint8Array {
+        to_buffer(&self.account.admin)
+    }
+
+    #[wasm_bindgen]
+    pub fn get_pending_admin(&self) -> Option<Uint8Array> {
+        self.account
+            .pending_admin
+            .as_ref()
+            .map(|key| to_buffer(key))
+    }
+
+    /// Key holding the role, default key if the role was not handed out
+    #[wasm_bindgen]
+    pub fn get_role(&self, role: u8) -> Result<Uint8Array, JsError> {
+        Ok(to_buffer(self.account.role(Role::try_from(role)?)))
+    }
+
+    #[wasm_bindgen]
+    pub fn timelock_delay(&self) -> u32 {
+        self.account.timelock.delay
+    }
+
+    #[wasm_bindgen]
+    pub fn pending_changes(&self) -> Array {
+        self.account
+            .timelock
+            .pending()
+            .map(|change| {
+                JsValue::from(PendingChangeInfo {
+                    id: change.id,
+                    eta: change.eta,
+                    kind: change.kind as u8,
+                    vault: change.vault,
+                    strategy: change.strategy,
+                    values: change.values.to_vec(),
+                })
+            })
+            .collect::<Array>()
+    }
 }
@@ -272,6 +272,31 @@ impl VaultsAccount {
 
         Ok(vault.oracle()?.calculate_quantity(value).get())
     }
+
+    #[wasm_bindgen]
+    pub fn max_borrow_for_statement(&self, id: u8, statement: &Uint8Array) -> Result<u64, JsError> {
+        let vault = self.vault_checked(id)?;
+        let mut statement_account = StatementAccount::load(statement);
+        statement_account.statement.refresh(&self.arr.elements)?;
+
+        // isolated collateral is capped at what its vault debt ceiling has left
+        let value = statement_account.statement.permitted_borrow();
+
+        Ok(vault.oracle()?.calculate_quantity(value).get())
+    }
+
+    #[wasm_bindgen]
+    pub fn debt_ceiling(&self, id: u8) -> Result<Option<u64>, JsError> {
+        let vault = self.vault_checked(id)?;
+
+        Ok(vault.debt_ceiling().map(|ceiling| ceiling.get() as u64))
+    }
+
+    /// Debt value backed by LP shares of the vault across all statements
+    #[wasm_bindgen]
+    pub fn isolated_debt(&self, id: u8) -> Result<u64, JsError> {
+        Ok(self.vault_checked(id)?.isolated_debt.get() as u64)
+    }
 }
 
 #[wasm_bindgen]
@@ -328,7 +353,7 @@ impl StatementAccount {
 
     #[wasm_bindgen]
     pub fn remaining_permitted_debt(&self) -> u64 {
-        self.statement.permitted_debt().get() as u64
+        self.statement.permitted_borrow().get() as u64
     }
 
     #[wasm_bindgen]
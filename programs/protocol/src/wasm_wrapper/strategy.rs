@@ -1,8 +1,19 @@
 use super::vault::VaultsAccount;
-use crate::core_lib::{decimal::Utilization, errors::LibErrors, strategy::Strategy};
+use crate::{
+    core_lib::{
+        decimal::{Time, Utilization},
+        errors::LibErrors,
+        strategy::{Strategy, SNAPSHOT_INTERVAL},
+    },
+    structs::YieldHistory,
+    ZeroCopyDecoder,
+};
 use checked_decimal_macro::Decimal;
+use js_sys::Uint8Array;
 use wasm_bindgen::prelude::*;
 
+const DAY: Time = SNAPSHOT_INTERVAL;
+
 #[wasm_bindgen]
 pub struct StrategyInfo {
     pub has_lend: bool,
@@ -67,6 +78,23 @@ impl VaultsAccount {
         Ok(self.strategy(vault, strategy)?.is_swapping_enabled())
     }
 
+    #[wasm_bindgen]
+    pub fn shares_swap(&self, vault: u8, strategy: u8) -> Result<bool, JsError> {
+        Ok(self.strategy(vault, strategy)?.shared_swap)
+    }
+
+    #[wasm_bindgen]
+    pub fn strategy_state(&self, vault: u8, strategy: u8) -> Result<u8, JsError> {
+        Ok(self.strategy(vault, strategy)?.state as u8)
+    }
+
+    #[wasm_bindgen]
+    pub fn pending_withdrawals(&self, vault: u8, strategy: u8, base: bool) -> Result<u64, JsError> {
+        let pending = self.strategy(vault, strategy)?.withdraw_queue.pending();
+
+        Ok(if base { pending.base } else { pending.quote }.get())
+    }
+
     #[wasm_bindgen]
     pub fn does_trade(&self, vault: u8, strategy: u8) -> Result<bool, JsError> {
         Ok(self.strategy(vault, strategy)?.is_trading_enabled())
@@ -91,4 +119,65 @@ impl VaultsAccount {
     pub fn lock_quote(&self, vault: u8, strategy: u8) -> Result<u64, JsError> {
         Ok(self.strategy(vault, strategy)?.locked_quote().get())
     }
+
+    /// Annualized yield of the strategy over the window, fees and losses included,
+    /// `yield_history` is the data of the yield history account of the vault
+    #[wasm_bindgen]
+    pub fn strategy_apy(
+        &self,
+        vault: u8,
+        strategy: u8,
+        yield_history: &Uint8Array,
+        window_in_secs: u32,
+        current_time: u32,
+    ) -> Result<i64, JsError> {
+        let history = *ZeroCopyDecoder::decode::<YieldHistory>(&yield_history.to_vec());
+
+        // refreshed copy, so fees accrued since the last refresh are included
+        let mut vault = *self.vault_checked(vault)?;
+        vault.refresh(current_time)?;
+
+        let (earned, lost) = vault.strategies.get_strategy(strategy)?.get_apy(
+            history.strategy(strategy)?,
+            window_in_secs,
+            current_time,
+            vault.oracle()?,
+            vault.quote_oracle()?,
+        );
+
+        Ok(earned.get() as i64 - lost.get() as i64)
+    }
+
+    #[wasm_bindgen]
+    pub fn strategy_apy_24h(
+        &self,
+        vault: u8,
+        strategy: u8,
+        yield_history: &Uint8Array,
+        current_time: u32,
+    ) -> Result<i64, JsError> {
+        self.strategy_apy(vault, strategy, yield_history, DAY, current_time)
+    }
+
+    #[wasm_bindgen]
+    pub fn strategy_apy_7d(
+        &self,
+        vault: u8,
+        strategy: u8,
+        yield_history: &Uint8Array,
+        current_time: u32,
+    ) -> Result<i64, JsError> {
+        self.strategy_apy(vault, strategy, yield_history, 7 * DAY, current_time)
+    }
+
+    #[wasm_bindgen]
+    pub fn strategy_apy_30d(
+        &self,
+        vault: u8,
+        strategy: u8,
+        yield_history: &Uint8Array,
+        current_time: u32,
+    ) -> Result<i64, JsError> {
+        self.strategy_apy(vault, strategy, yield_history, 30 * DAY, current_time)
+    }
 }
@@ -1,10 +1,23 @@
 use super::vault::VaultsAccount;
 use crate::core_lib::{
-    decimal::{Decimal, Quantity},
-    services::ServiceUpdate,
+    decimal::{Balances, Decimal, Fraction, Quantity},
+    services::{swapping::KeptFeeBucket, ServiceUpdate},
 };
 use wasm_bindgen::prelude::*;
 
+#[wasm_bindgen]
+pub struct SwapQuoteInfo {
+    pub amount_out: u64,
+    pub gross_out: u64,
+    pub fee: u64,
+    pub fee_fraction: u64,
+    pub kept_fee: u64,
+    pub proportion_before: u64,
+    pub proportion_after: u64,
+    pub price_impact: u64,
+    pub min_amount_out: u64,
+}
+
 #[wasm_bindgen]
 impl VaultsAccount {
     #[wasm_bindgen]
@@ -14,10 +27,26 @@ impl VaultsAccount {
         amount: u64,
         from_base: bool,
         by_amount_out: bool,
-        now: u32,
+        _now: u32,
     ) -> Result<i64, JsError> {
+        let quote = self.swap_quote(vault, amount, from_base, by_amount_out, 0)?;
+
+        Ok(quote.amount_out as i64)
+    }
+
+    /// Quote of the swap, `slippage` is a fraction used to calculate `min_amount_out`
+    #[wasm_bindgen]
+    pub fn swap_quote(
+        &self,
+        vault: u8,
+        amount: u64,
+        from_base: bool,
+        by_amount_out: bool,
+        slippage: u64,
+    ) -> Result<SwapQuoteInfo, JsError> {
         console_error_panic_hook::set_once();
-        let mut vault = self.vault_checked_mut(vault)?.clone();
+        let vault = self.vault_checked(vault)?;
+        let swap = vault.swap_with_shared()?;
 
         let quantity = Quantity::new(amount);
 
@@ -25,12 +54,22 @@ impl VaultsAccount {
             unimplemented!("swaps by amount out are not yet implemented")
         }
 
-        let quantity_out = match from_base {
-            true => vault.sell(quantity)?,
-            false => vault.buy(quantity)?,
+        let quote = match from_base {
+            true => swap.quote_sell(quantity, vault.oracle()?, vault.quote_oracle()?)?,
+            false => swap.quote_buy(quantity, vault.oracle()?, vault.quote_oracle()?)?,
         };
 
-        Ok(quantity_out.get() as i64)
+        Ok(SwapQuoteInfo {
+            amount_out: quote.output().get(),
+            gross_out: quote.gross.get(),
+            fee: quote.fee.get(),
+            fee_fraction: quote.fee_fraction.get(),
+            kept_fee: quote.kept_fee.get(),
+            proportion_before: quote.proportion_before.get(),
+            proportion_after: quote.proportion_after.get(),
+            price_impact: quote.price_impact.get(),
+            min_amount_out: quote.min_output(Fraction::new(slippage)).get(),
+        })
     }
 
     #[wasm_bindgen]
@@ -45,4 +84,73 @@ impl VaultsAccount {
         }
         .get())
     }
+
+    /// Inventory skew applied to the swap, quantity out is multiplied by it (in fraction denominator)
+    #[wasm_bindgen]
+    pub fn swap_skew(&self, vault: u8, from_base: bool) -> Result<u64, JsError> {
+        let vault = self.vault_checked(vault)?;
+
+        let skew = vault.swap_service_not_mut()?.current_skew(
+            vault.oracle()?,
+            vault.quote_oracle()?,
+            from_base,
+        );
+
+        Ok(skew.get())
+    }
+
+    #[wasm_bindgen]
+    pub fn total_kept_fee(&self, vault: u8, base: bool) -> Result<u64, JsError> {
+        let vault = self.vault_checked(vault)?;
+
+        let kept = vault.swap_service_not_mut()?.total_kept_fee;
+
+        Ok(side(kept, base))
+    }
+
+    /// Fee ever kept for the bucket (0 - insurance, 1 - PoL, 2 - burn)
+    #[wasm_bindgen]
+    pub fn kept_fee_total(&self, vault: u8, bucket: u8, base: bool) -> Result<u64, JsError> {
+        let vault = self.vault_checked(vault)?;
+        let bucket = KeptFeeBucket::try_from(bucket)?;
+
+        let kept = vault.swap_service_not_mut()?.kept.total(bucket);
+
+        Ok(side(kept, base))
+    }
+
+    /// Fee in the bucket not yet swept (0 - insurance, 1 - PoL, 2 - burn)
+    #[wasm_bindgen]
+    pub fn kept_fee_balance(&self, vault: u8, bucket: u8, base: bool) -> Result<u64, JsError> {
+        let vault = self.vault_checked(vault)?;
+        let bucket = KeptFeeBucket::try_from(bucket)?;
+
+        let kept = vault.swap_service_not_mut()?.kept.balance(bucket);
+
+        Ok(side(kept, base))
+    }
+
+    /// Tokens of the insurance fund available to cover losses
+    #[wasm_bindgen]
+    pub fn insurance_balance(&self, vault: u8, base: bool) -> Result<u64, JsError> {
+        let vault = self.vault_checked(vault)?;
+
+        Ok(side(vault.insurance.balance, base))
+    }
+
+    /// Losses ever covered by the insurance fund
+    #[wasm_bindgen]
+    pub fn insurance_covered(&self, vault: u8, base: bool) -> Result<u64, JsError> {
+        let vault = self.vault_checked(vault)?;
+
+        Ok(side(vault.insurance.total_covered, base))
+    }
+}
+
+fn side(balances: Balances, base: bool) -> u64 {
+    match base {
+        true => balances.base,
+        false => balances.quote,
+    }
+    .get()
 }
@@ -1,7 +1,11 @@
 use std::ops::{Deref, DerefMut};
 
 use crate::{
-    core_lib::{errors::LibErrors, structs::Side, Vault},
+    core_lib::{
+        errors::LibErrors,
+        structs::{Operation, Side},
+        Vault,
+    },
     structs::{VaultKeys, Vaults},
     wasm_wrapper::utils::to_buffer,
     ZeroCopyDecoder,
@@ -146,6 +150,19 @@ impl VaultsAccount {
         ))
     }
 
+    #[wasm_bindgen]
+    pub fn share_mint(&self, index: u8, strategy: u8) -> Result<Uint8Array, JsError> {
+        Ok(to_buffer(
+            &self
+                .keys_checked(index)?
+                .share_mints
+                .get(strategy as usize)
+                .copied()
+                .flatten()
+                .ok_or(LibErrors::PubkeyMissing)?,
+        ))
+    }
+
     #[wasm_bindgen]
     pub fn base_oracle_enabled(&self, index: u8) -> Result<bool, JsError> {
         Ok(self.vault_checked(index)?.oracle.is_some())
@@ -173,7 +190,9 @@ impl VaultsAccount {
 
     #[wasm_bindgen]
     pub fn refresh(&mut self, index: u8, current_time: u32) -> Result<(), JsError> {
-        Ok(self.vault_checked_mut(index)?.refresh(current_time)?)
+        self.vault_checked_mut(index)?.refresh(current_time)?;
+
+        Ok(())
     }
 
     #[wasm_bindgen]
@@ -185,6 +204,30 @@ impl VaultsAccount {
             .get())
     }
 
+    #[wasm_bindgen]
+    pub fn written_off(&self, index: u8) -> Result<u64, JsError> {
+        Ok(self
+            .vault_checked(index)?
+            .lend_service_not_mut()?
+            .written_off
+            .get())
+    }
+
+    /// Whether the operation is paused in this vault only, see also `StateAccount::is_paused`
+    #[wasm_bindgen]
+    pub fn is_paused(&self, index: u8, operation: u8) -> Result<bool, JsError> {
+        Ok(self
+            .vault_checked(index)?
+            .pause
+            .is_paused(Operation::try_from(operation)?))
+    }
+
+    /// 0 - active, 1 - delisting, 2 - closed and free for a new vault
+    #[wasm_bindgen]
+    pub fn vault_status(&self, index: u8) -> Result<u8, JsError> {
+        Ok(self.vault_checked(index)?.status as u8)
+    }
+
     #[wasm_bindgen]
     pub fn available_lend(&self, index: u8) -> Result<u64, JsError> {
         Ok(self
@@ -264,7 +307,7 @@ impl VaultsAccount {
     pub fn refresh_lend_fees(&mut self, current_time: u32) -> Result<(), JsError> {
         if let Some(mut iter) = self.arr.iter_mut() {
             while let Some(vault) = iter.next() {
-                vault.refresh(current_time)?
+                vault.refresh(current_time)?;
             }
         };
 
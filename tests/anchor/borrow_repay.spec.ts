@@ -246,7 +246,7 @@ describe('Borrow tests', function () {
 
   it('gives max borrow for user in token quantity', async () => {
     assert.equal(
-      vaults_account.max_borrow_for(0, statement_account.remaining_permitted_debt()),
+      vaults_account.max_borrow_for_statement(0, statement_account.buffer()),
       400000n
     )
   })